@@ -1,4 +1,5 @@
 use fenetre::{
+  app_event::AppEvent,
   button::Button,
   child_window::{
     ChildType,
@@ -9,6 +10,7 @@ use fenetre::{
     Orientation,
     UserEvent,
   },
+  event_proxy::EventProxy,
   label::Label,
   line_edit::LineEdit,
   MainApp,
@@ -16,14 +18,12 @@ use fenetre::{
   multi_line_edit::MultiLineEdit,
   row_layout::RowLayout,
   scroll_layout::ScrollLayout,
+  shortcut::Shortcut,
   window_utils::WindowUtils,
 };
 
 use winit::{
-  event_loop::{
-    EventLoop,
-    EventLoopProxy,
-  },
+  event_loop::EventLoop,
 };
 
 use tiny_skia::Color;
@@ -41,14 +41,16 @@ use std::{
 };
 use fenetre::status_bar::StatusBar;
 
-// Application-specific user events
-static SAVE_FILE: u64 = 1;
+// Application-specific event that saves the editor's contents
+struct SaveFile {
+  contents: String,
+}
 
 // File being edited
 static mut FILE_NAME: String = String::new();
 
 struct Editor {
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+  event_loop_proxy: Rc<EventProxy>,
   main_app: RefCell<MainApp>,
   main_win_id: Uuid,                      // Main window's ID
   status_bar_rc: Rc<RefCell<StatusBar>>,  // Main window's status bar
@@ -66,7 +68,7 @@ struct Editor {
 
 impl Editor {
   
-  fn new(event_loop_proxy: Rc<EventLoopProxy<UserEvent>>) -> Self {
+  fn new(event_loop_proxy: Rc<EventProxy>) -> Self {
 
     // Initialize the file name
     unsafe {
@@ -151,8 +153,9 @@ impl Editor {
     let event_loop_proxy_clone = self.event_loop_proxy.clone();
     let main_win_id = self.main_win_id;
     let open_uuid = self.open_id;
-    main_ref.add_menu_item(
+    main_ref.add_menu_item_with_shortcut(
       "Open".to_string(),
+      Shortcut::parse("Ctrl+O").unwrap(),
       move || {
         match event_loop_proxy_clone.send_event(UserEvent::CreateWindow(
           main_win_id,
@@ -176,8 +179,9 @@ impl Editor {
     let editor_rc_clone = self.editor_rc.clone();
     let message_dialog_id = self.message_dialog_id;
     let dialog_values_clone = self.dialog_values.clone();
-    main_ref.add_menu_item(
+    main_ref.add_menu_item_with_shortcut(
       "Save".to_string(),
+      Shortcut::parse("Ctrl+S").unwrap(),
       move || {
         
         let filename;
@@ -293,8 +297,6 @@ impl Editor {
   // Defines the handler for caret moved events
   fn define_caret_moved_handler(&self) {
 
-    let mut main_ref = self.main_app.borrow_mut();
-
     let editor_rc = match self.editor_rc {
       
       Some(ref editor) => editor,
@@ -303,24 +305,19 @@ impl Editor {
         return;
       }
     };
-    let editor_id = editor_rc.borrow().get_uuid();
-    
-    let status_bar_rc = self.status_bar_rc.clone();
-    main_ref.set_caret_moved_event_callback(Box::new(move |source_uuid, line, col| {
 
-      // Is this event from the multi-line editor?
-      if source_uuid == editor_id {
+    // Update the status bar with the new location
+    let status_bar_rc = self.status_bar_rc.clone();
+    editor_rc.borrow().on_caret_moved(move |line, col| {
 
-        // Update the status bar with the new location
-        let msg = format!("Line: {}, col: {} | Ready", line + 1, col + 1);
+      let msg = format!("Line: {}, col: {} | Ready", line + 1, col + 1);
 
-        let mut status_bar_ref = status_bar_rc.borrow_mut();
-        status_bar_ref.set_message(msg);
-      }
-    }));
+      let mut status_bar_ref = status_bar_rc.borrow_mut();
+      status_bar_ref.set_message(msg);
+    });
   }
 
-  // Defines a handler for user-defined events
+  // Defines a handler for the application's events
   fn define_custom_events_handler(&mut self) {
 
     let mut main_ref = self.main_app.borrow_mut();
@@ -330,56 +327,53 @@ impl Editor {
     let save_to_id = self.save_to_id;
     let program_ending = self.program_ending;
     let editor_rc_clone = self.editor_rc.clone();
-    main_ref.set_user_defined_event_callback(Box::new(move|event_id, args| {
-      
-      if SAVE_FILE == event_id {
+    main_ref.add_app_event_handler(move |event: &SaveFile| {
 
-        // If this is a new file, prompt the user for the file into which to save.
-        let filename;
-        unsafe {
-          filename = FILE_NAME.clone();
+      // If this is a new file, prompt the user for the file into which to save.
+      let filename;
+      unsafe {
+        filename = FILE_NAME.clone();
+      }
+      
+      if "New" == filename {
+        
+        // Create the Save To dialog. Once the dialog's Ok button is clicked,
+        // this custom event will be fired again.
+        match event_loop_proxy_clone.send_event(UserEvent::CreateWindow(
+          main_win_id,
+          save_to_id,
+          400.0,
+          300.0,
+          200.0,
+          65.0,
+          true
+        )) {
+          Ok(_) => {},
+          Err(err) => {
+            println!("Cannot send UserEvent event: {err}");
+          },
         }
+      } else {
         
-        if "New" == filename {
-          
-          // Create the Save To dialog. Once the dialog's Ok button is clicked,
-          // this custom event will be fired again.
-          match event_loop_proxy_clone.send_event(UserEvent::CreateWindow(
-            main_win_id,
-            save_to_id,
-            400.0,
-            300.0,
-            200.0,
-            65.0,
-            true
-          )) {
-            Ok(_) => {},
-            Err(err) => {
-              println!("Cannot send UserEvent event: {err}");
-            },
-          }
-        } else {
-          
-          // Save the data to the file
-          write_to_file(filename.clone(), args[0].clone());
+        // Save the data to the file
+        write_to_file(filename.clone(), event.contents.clone());
 
-          match &editor_rc_clone {
-          
-            Some(editor_rc) => {
-              let mut editor_ref = editor_rc.borrow_mut();
-              editor_ref.set_modified(false);
-            },
-            
-            None => {},
-          }
+        match &editor_rc_clone {
+        
+          Some(editor_rc) => {
+            let mut editor_ref = editor_rc.borrow_mut();
+            editor_ref.set_modified(false);
+          },
           
-          // If we are in the process of ending the program, do so.
-          if program_ending {
-            process::exit(0);
-          }
+          None => {},
+        }
+        
+        // If we are in the process of ending the program, do so.
+        if program_ending {
+          process::exit(0);
         }
       }
-    }));
+    });
   }
       
     // Defines the end program handler
@@ -707,17 +701,12 @@ impl Editor {
                   
                   Some(data) => {
 
-                    // Build the event's arguments
-                    let mut args: Vec<String> = Vec::new();
-                    args.push(data);
-
                     // Fire the custom event
                     WindowUtils::fire_user_event(
                       event_proxy_rc_clone_clone.clone(),
-                      UserEvent::UserDefined(
+                      UserEvent::App(
                         main_win_id,
-                        SAVE_FILE,
-                        args
+                        AppEvent::new(SaveFile { contents: data })
                       )
                     );
                   },
@@ -1014,7 +1003,7 @@ fn main() {
       process::exit(-1);
     },
   };
-  let event_loop_proxy = Rc::new(event_loop.create_proxy());
+  let event_loop_proxy = Rc::new(EventProxy::from(event_loop.create_proxy()));
 
   // Create the main application object
   let mut editor = Editor::new(event_loop_proxy.clone());
@@ -1051,7 +1040,7 @@ fn read_file(file: String) -> String {
 }
 
 fn show_message(
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+  event_loop_proxy: Rc<EventProxy>,
   main_win_id: Uuid,
   message_dialog_id: Uuid
 ) {
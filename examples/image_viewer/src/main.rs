@@ -10,6 +10,7 @@ use fenetre::{
     Orientation,
     UserEvent,
   },
+  event_proxy::EventProxy,
   image_view::{DisplayType, ImageView},
   label::Label,
   line_edit::LineEdit,
@@ -23,7 +24,7 @@ use fenetre::{
 };
 
 use winit::{
-  event_loop::{EventLoop, EventLoopProxy},
+  event_loop::EventLoop,
 };
 
 use tiny_skia::Color;
@@ -40,7 +41,7 @@ use std::{
 };
 
 struct ImageViewer {
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+  event_loop_proxy: Rc<EventProxy>,
   main_app: RefCell<MainApp>,
   main_win_id: Uuid,                      // Main window's ID
   status_bar_rc: Rc<RefCell<StatusBar>>,  // Main window's status bar
@@ -58,7 +59,7 @@ struct ImageViewer {
 
 impl ImageViewer {
 
-  fn new(event_loop_proxy: Rc<EventLoopProxy<UserEvent>>) -> Self {
+  fn new(event_loop_proxy: Rc<EventProxy>) -> Self {
 
     let main_win_id = Uuid::new_v4();
 
@@ -735,7 +736,7 @@ fn main() {
       process::exit(-1);
     },
   };
-  let event_loop_proxy = Rc::new(event_loop.create_proxy());
+  let event_loop_proxy = Rc::new(EventProxy::from(event_loop.create_proxy()));
 
   // Create the main application object
   let mut image_viewer = ImageViewer::new(event_loop_proxy.clone());
@@ -765,13 +766,30 @@ fn main() {
   image_viewer.run_event_loop(event_loop);
 }
 
+// Lists a directory's contents in the background, so that a slow disk
+// doesn't block the event loop, and then sends them to the List
 fn populate_directory_listing(
       main_win_uuid: Uuid,
       list_uuid: Uuid,
       dir_path: String,
-      event_loop_proxy_rc: Rc<EventLoopProxy<UserEvent>>
+      event_loop_proxy_rc: Rc<EventProxy>
 ) {
 
+  let event_loop_proxy_clone = event_loop_proxy_rc.clone();
+  event_loop_proxy_rc.spawn(
+    async move { read_directory(dir_path) },
+    move |_app, list| {
+      WindowUtils::fire_user_event(
+            event_loop_proxy_clone,
+            UserEvent::SetList(main_win_uuid, list_uuid, list)
+      );
+    }
+  );
+}
+
+// Gets the names of a directory's entries, with the directories first
+fn read_directory(dir_path: String) -> Vec<String> {
+
   // Populate the list with the current directories contents
   let mut list: Vec<String> = Vec::new();
   match fs::read_dir(dir_path) {
@@ -816,13 +834,5 @@ fn populate_directory_listing(
     Err(err) => println!("Cannot get directory contents: {err}"),
   }
 
-  // Fire the event
-  match event_loop_proxy_rc.send_event(UserEvent::SetList(
-        main_win_uuid,
-        list_uuid,
-        list
-  )) {
-    Ok(_) => {},
-    Err(err) => println!("Cannot send UserEvent event: {err}"),
-  }
+  list
 }
@@ -6,6 +6,7 @@ use fenetre::{
         Orientation,
         UserEvent,
     },
+    event_proxy::EventProxy,
     label::Label,
     MainApp,
     MainAppSize,
@@ -14,7 +15,7 @@ use fenetre::{
 
 use tiny_skia::Color;
 
-use winit::event_loop::{EventLoop, EventLoopProxy};
+use winit::event_loop::EventLoop;
 
 use uuid::Uuid;
 
@@ -25,7 +26,7 @@ use std::{
 use std::cell::RefCell;
 
 struct SimpleWindow {
-    event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+    event_loop_proxy: Rc<EventProxy>,
     main_app: RefCell<MainApp>,
     main_win_id: Uuid,                      // Main window's ID
     about_id: Uuid,
@@ -33,7 +34,7 @@ struct SimpleWindow {
 
 impl SimpleWindow {
 
-    fn new(event_loop_proxy: Rc<EventLoopProxy<UserEvent>>) -> Self {
+    fn new(event_loop_proxy: Rc<EventProxy>) -> Self {
 
         let main_win_id = Uuid::new_v4();
 
@@ -167,7 +168,7 @@ fn main() {
             process::exit(-1);
         },
     };
-    let event_loop_proxy = Rc::new(event_loop.create_proxy());
+    let event_loop_proxy = Rc::new(EventProxy::from(event_loop.create_proxy()));
 
     // Create the main application object
     let mut simple_window = SimpleWindow::new(event_loop_proxy.clone());
@@ -0,0 +1,55 @@
+use std::{
+  any::{self, Any, TypeId},
+  fmt,
+  sync::Arc,
+};
+
+/// Function that handles the App events whose data is of one type
+pub(crate) type AppEventHandler = Box<dyn Fn(&AppEvent)>;
+
+/// Data of an event that is defined by an application, sent in a UserEvent::App
+///
+/// The data can be of any type, such as a struct for each kind of event. The
+/// MainApp passes it to the handlers that were added for its type with
+/// MainApp::add_app_event_handler(), so they receive it already typed.
+#[derive(Clone)]
+pub struct AppEvent {
+  data: Arc<dyn Any + Send + Sync>,
+  type_name: &'static str,
+}
+
+impl AppEvent {
+
+  pub fn new<T: Any + Send + Sync>(data: T) -> Self {
+    Self {
+      data: Arc::new(data),
+      type_name: any::type_name::<T>(),
+    }
+  }
+
+  /// Gets the data if it is of type T
+  pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+    self.data.downcast_ref::<T>()
+  }
+
+  /// Gets the name of the data's type, which is used in diagnostic messages
+  pub fn get_type_name(&self) -> &'static str {
+    self.type_name
+  }
+
+  /// Gets the ID of the data's type
+  pub(crate) fn get_type_id(&self) -> TypeId {
+    (*self.data).type_id()
+  }
+
+  /// Returns true if the data is of type T
+  pub fn is<T: Any>(&self) -> bool {
+    self.data.is::<T>()
+  }
+}
+
+impl fmt::Debug for AppEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "AppEvent({})", self.type_name)
+  }
+}
@@ -6,13 +6,11 @@ use crate::child_window::{
   LayoutFill,
   LayoutType,
   Orientation,
-  UserEvent,
 };
+use crate::event_proxy::EventProxy;
 use crate::layout_base::LayoutBase;
 use crate::row_layout::RowLayout;
 
-use winit::event_loop::EventLoopProxy;
-
 use tiny_skia::{Pixmap};
 
 use uuid::Uuid;
@@ -33,7 +31,7 @@ pub struct BorderLayout {
 
 impl BorderLayout {
 
-  pub fn new(event_loop: Rc<EventLoopProxy<UserEvent>>, main_win_uuid: Uuid) -> BorderLayout {
+  pub fn new(event_loop: Rc<EventProxy>, main_win_uuid: Uuid) -> BorderLayout {
 
     // Create the internal layouts
     let mut rows = RowLayout::new(
@@ -61,7 +59,11 @@ impl BorderLayout {
       Err(err) => println!("Could not add center row to layout: {err}"),
     }
 
-    let layout_base = LayoutBase::new(event_loop, main_win_uuid);
+    let layout_base = LayoutBase::new(event_loop.clone(), main_win_uuid);
+
+    // Record the rows as this layout's child, so that the windows within it
+    // are removed from the registry along with this layout
+    event_loop.get_registry().borrow_mut().add_layout(layout_base.get_uuid(), rows.get_uuid());
 
     Self {
       layout_base: layout_base,
@@ -208,6 +210,13 @@ impl Layout for BorderLayout {
     }
   }
 
+  /// Gets the focusable children, from top to bottom and left to right
+  fn get_focus_chain(&self) -> Vec<Rc<RefCell<dyn ChildWindow>>> {
+
+    let rows_ref = self.rows.borrow();
+    rows_ref.get_focus_chain()
+  }
+
   /// Gets the child with the specified ID
   fn get_child_with_id(&mut self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>> {
 
@@ -253,6 +262,8 @@ impl Layout for BorderLayout {
     let mut rows_ref = self.rows.borrow_mut();
 
     let pixmap = rows_ref.layout(main_win_x, main_win_y, width, height);
+    self.layout_base.set_laid_out(main_win_x, main_win_y, width, height, pixmap.clone());
+
     pixmap
   }
 
@@ -280,10 +291,10 @@ impl Layout for BorderLayout {
     self.layout_base.set_uuid(uuid);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.layout_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.layout_base.set_event_loop(event_loop);
   }
 
@@ -301,6 +312,14 @@ impl Layout for BorderLayout {
     self.layout_base.set_pixmap(pixmap);
   }
 
+  fn is_dirty(&self) -> bool {
+    self.layout_base.is_dirty() || self.rows.borrow().is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.layout_base.set_dirty(dirty);
+    self.rows.borrow_mut().set_dirty(dirty);
+  }
+
   fn get_main_win_x(&self) -> f64 {
     self.layout_base.get_main_win_x()
   }
@@ -1,11 +1,10 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::{Key, ModifiersState, NamedKey},
   window::Window,
 };
 
@@ -26,8 +25,9 @@ use std::{
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::ContextMenuItem;
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::text_font::TextFont;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
@@ -35,7 +35,6 @@ use crate::window_utils::WindowUtils;
 
 // Used for internal margins
 const INTERNAL_MARGIN: f64 = 4.0;
-const DEFAULT_FONT_SIZE: f32 = 14.0;
 
 /// Child window that is a clickable button with text and/or an image
 pub struct Button {
@@ -43,7 +42,7 @@ pub struct Button {
   font: Option<TextFont>,
   image: Option<DynamicImage>,
   callback: Box<dyn Fn()>,    // function that is called when the button is clicked
-  color_text: Color,
+  color_text: Option<Color>,     // None uses the theme's text color
   color_background: Color,
   internal_padding: f64,
 }
@@ -52,7 +51,7 @@ impl Button {
 
   /// If both a PNG file path and text are specified, the text will be drawn over the image.
   pub fn new<F: Fn() + 'static> (
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     text: Option<String>,
     image_path: Option<String>,
@@ -98,7 +97,7 @@ impl Button {
     }
 
     // Load the font
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", DEFAULT_FONT_SIZE) {
+    let font = match TextFont::new_default() {
       Ok(font) => Some(font),
       Err(_err) => None,
     };
@@ -117,8 +116,8 @@ impl Button {
         };
 
         // Add margins
-        text_width = bounds_width as f64 + 10.0;
-        text_height = bounds_height as f64 + 4.0;
+        text_width = WindowUtils::to_logical(bounds_width as f64) + 10.0;
+        text_height = WindowUtils::to_logical(bounds_height as f64) + 4.0;
       },
       None => {},
     }
@@ -143,13 +142,14 @@ impl Button {
     window_base.set_min_size(width, height);
     window_base.set_max_size(width + INTERNAL_MARGIN, height + INTERNAL_MARGIN);
     window_base.set_window_type("Button".to_string());
+    window_base.set_accepts_focus(true);
 
     let mut inst = Self {
       window_base: window_base,
       font: font,
       image: image,
       callback: Box::new(callback),
-      color_text: Color::BLACK,
+      color_text: None,
       color_background: bg_color,
       internal_padding: 8.0,
     };
@@ -188,7 +188,7 @@ impl Button {
         self.window_base.set_location(x, y);
 
         // Draw the image into a Pixmap
-        WindowUtils::create_image_pixmap(&image)
+        WindowUtils::create_scaled_image_pixmap(&image)
       },
       None => {
 
@@ -205,16 +205,15 @@ impl Button {
       Some((width, height)) => (width, height),
       None => (1.0, 1.0),
     };
+    // The padding is added to the minimum size, so that redrawing the button doesn't grow it
     let mut new_width = width;
-    if new_width < min_width {
-      new_width = min_width;
+    if new_width < min_width + 2.0 {
+      new_width = min_width + 2.0;   // Extra padding on the right
     }
-    new_width += 2.0;   // Extra padding on the right
     let mut new_height = height;
-    if new_height < min_height {
-      new_height = min_height;
+    if new_height < min_height + 2.0 {
+      new_height = min_height + 2.0;   // Extra padding on the bottom
     }
-    new_height += 2.0;   // Extra padding on the bottom
 
     // Ensure that the size isn't bigger than the maximum size
     let (max_width, max_height) = match self.window_base.get_max_size() {
@@ -233,7 +232,9 @@ impl Button {
     self.window_base.set_size(new_width, new_height);
 
     // Create the pixmap into which we will draw
-    let mut pixmap = Pixmap::new(new_width as u32, new_height as u32).unwrap();
+    let pixmap_width = WindowUtils::to_physical(new_width);
+    let pixmap_height = WindowUtils::to_physical(new_height);
+    let mut pixmap = Pixmap::new(pixmap_width as u32, pixmap_height as u32).unwrap();
 
     // Determine the background color based on the enabled status
     let bg_color;
@@ -271,19 +272,19 @@ impl Button {
           // Center the text
           let (text_width, text_height) = font.get_bounds(text, None);
           let mut x = 0;
-          if new_width as u32 > text_width {         // button is wider than the text
-            x = (new_width as u32 - text_width) / 2;
+          if pixmap_width as u32 > text_width {         // button is wider than the text
+            x = (pixmap_width as u32 - text_width) / 2;
           }
           let mut y = 0;
-          if new_height as u32 > text_height {       // button is taller that the text
-            y = (new_height as u32 - text_height) / 2;
+          if pixmap_height as u32 > text_height {       // button is taller that the text
+            y = (pixmap_height as u32 - text_height) / 2;
           }
           font.draw_text(
             text.as_str(),
             &mut pixmap,
             x as i32,
             y as i32,
-            self.color_text,
+            self.color_text.unwrap_or(self.window_base.get_theme().palette.text),
             bg_color,
             -1,
             Color::BLACK,
@@ -300,10 +301,12 @@ impl Button {
 
   pub fn set_background_color(&mut self, color: Color) {
     self.color_background = color;
+    self.draw();
   }
 
   pub fn set_text_color(&mut self, color: Color) {
-    self.color_text = color;
+    self.color_text = Some(color);
+    self.draw();
   }
 }
 
@@ -344,6 +347,13 @@ impl ChildWindow for Button {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -358,10 +368,10 @@ impl ChildWindow for Button {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -389,6 +399,20 @@ impl ChildWindow for Button {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -427,6 +451,10 @@ impl ChildWindow for Button {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_max_size(&self) -> Option<(f64, f64)> {
     let width = self.window_base.get_width();
 
@@ -440,7 +468,7 @@ impl ChildWindow for Button {
             let (_, text_height) = font.get_bounds(&text, None);
 
             // Add internal padding
-            text_height as f64 + self.internal_padding
+            WindowUtils::to_logical(text_height as f64) + self.internal_padding
           },
           None => 0.0,
         }
@@ -513,20 +541,35 @@ impl ChildWindow for Button {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, event: KeyboardEvent, _modifiers: ModifiersState) {
+
+    // Enter and Space click the button when it has focus
+    match event.logical_key {
+      Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) if self.window_base.get_enabled() => {
+        (self.callback)();
+      },
+      _ => {},
+    }
+  }
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-      _mouse_x: f64, _mouse_y: f64) {
+      _mouse_x: f64, _mouse_y: f64, _modifiers: ModifiersState) {
 
     // If the user clicked the left button, call the callback. If the user clicked
     // the right button, display the tooltip, if there is one.
@@ -579,6 +622,10 @@ impl ChildWindow for Button {
     // Save the location
     self.window_base.set_location(x, y);
 
+    if self.window_base.is_theme_changed() {
+      self.draw();
+    }
+
     self.window_base.get_pixmap().clone()
   }
 
@@ -1,11 +1,12 @@
 use winit::{
   event::{
+    ElementState,
     KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::{Key, KeyLocation, ModifiersState, NativeKeyCode, PhysicalKey},
   window::{Window, WindowId},
 };
 
@@ -16,9 +17,13 @@ use tiny_skia::{
 
 use uuid::Uuid;
 
+use crate::app_event::AppEvent;
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::ui_handle::UiTask;
 
 use std::{
+    any::Any,
     cell::RefCell,
     fmt::Debug,
     rc::Rc,
@@ -30,6 +35,16 @@ pub enum Orientation {
   Vertical,
 }
 
+/// Editing action performed on a window, such as from its context menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditAction {
+  Copy,
+  Cut,
+  Paste,
+  Redo,
+  Undo,
+}
+
 /// Application-specific events sent to the event loop
 ///
 /// The following events have default processing if no handler is set:
@@ -38,6 +53,10 @@ pub enum Orientation {
 ///   RedrawAll
 #[derive(Debug, Clone)]
 pub enum UserEvent {
+  // An event that is defined by an application
+  // Uuid is the top-level parent window's ID
+  // AppEvent is the event's data, which is passed to the handlers for its type
+  App(Uuid, AppEvent),
   // Uuid is the editor window that generated the event
   // The first usize is the zero-based index of the line that the caret moved to
   // The second usize is the zero-based index of the character that the caret moved to
@@ -60,6 +79,11 @@ pub enum UserEvent {
   // The first Uuid is the top-level parent window's ID
   // The second Uuid is the ID for the window containing the items
   DeleteItems(Uuid, Uuid),
+  // Performs an editing action within a ChildWindow
+  // The first Uuid is the top-level parent window's ID
+  // The second Uuid is the ID of the window in which the action is performed
+  // EditAction is the action to perform
+  Edit(Uuid, Uuid, EditAction),
   // Sets the context menu to hidden
   // Uuid is the top-level parent window's ID
   HideContextMenu(Uuid),
@@ -76,6 +100,9 @@ pub enum UserEvent {
   // Redraws the entire main window' contents
   // Uuid is the top-level parent window's ID
   RedrawAll(Uuid),
+  // Calls a function on the UI thread, which is sent with UiHandle::run()
+  // UiTask is the function
+  Run(UiTask),
   // Fired when the value in a ScrollBar changes
   // The first Uuid is the top-level parent window's ID
   // The second Uuid is the ID of the ScrollBar in which the selection changed
@@ -103,8 +130,10 @@ pub enum UserEvent {
   // The String is the new text for the tooltip
   ShowToolTip(Uuid, Uuid, String),
   // Fired when a Slider's value is changed
+  // The first Uuid is the top-level parent window's ID
+  // The second Uuid is the ID of the Slider
   // The f64 is the new value
-  SliderValueChange(f64),
+  SliderValueChange(Uuid, Uuid, f64),
   // Fired when the user clicks on the title of a tab within a TabLayout
   // The first Uuid is the top-level parent window's ID
   // The second Uuid is the ID of the TabLayout
@@ -115,6 +144,7 @@ pub enum UserEvent {
   // The second Uuid is the ScrollLayout's ID
   UpdateScroller(Uuid, Uuid),
   // These are messages that are defined by an application's programmer
+  // App events are preferred, because their data is typed
   // Uuid is the top-level parent window's ID
   // The u64 is a unique number assigned by the programmer
   // The Vec<String> is user-defined data associated with the event
@@ -126,8 +156,87 @@ pub struct ContextMenuItem {
   pub callback: Box<dyn Fn()>
 }
 
+/// Keyboard event passed to windows
+///
+/// This contains the same information as winit's KeyEvent, but it can also be
+/// created by an application, such as when sending input to a headless MainApp.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyboardEvent {
+  pub physical_key: PhysicalKey,  // key's location on the keyboard
+  pub logical_key: Key,           // key after the keyboard layout has been applied
+  pub text: Option<String>,       // text produced by the key, if any
+  pub location: KeyLocation,      // which of several identical keys was used
+  pub state: ElementState,        // whether the key was pressed or released
+  pub repeat: bool,               // whether the key is being held down
+}
+
+impl KeyboardEvent {
+
+  /// Creates a key press event
+  ///
+  /// The text is what would be typed by the key, such as "a" or "\r".
+  pub fn pressed(logical_key: Key, text: Option<&str>) -> Self {
+    KeyboardEvent {
+      physical_key: PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+      logical_key,
+      text: text.map(|text| text.to_string()),
+      location: KeyLocation::Standard,
+      state: ElementState::Pressed,
+      repeat: false,
+    }
+  }
+
+  /// Creates a key release event
+  pub fn released(logical_key: Key) -> Self {
+    KeyboardEvent {
+      physical_key: PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+      logical_key,
+      text: None,
+      location: KeyLocation::Standard,
+      state: ElementState::Released,
+      repeat: false,
+    }
+  }
+
+  /// Sets the physical key
+  pub fn with_physical_key(mut self, physical_key: PhysicalKey) -> Self {
+    self.physical_key = physical_key;
+    self
+  }
+}
+
+impl From<KeyEvent> for KeyboardEvent {
+  fn from(event: KeyEvent) -> Self {
+    KeyboardEvent {
+      physical_key: event.physical_key,
+      logical_key: event.logical_key,
+      text: event.text.map(|text| text.to_string()),
+      location: event.location,
+      state: event.state,
+      repeat: event.repeat,
+    }
+  }
+}
+
+/// Gives access to a window as Any, so that it can be downcast to its own type
+///
+/// It is implemented for every type, so windows don't need to implement it.
+pub trait AsAny {
+  fn as_any(&self) -> &dyn Any;
+  fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+  fn as_any_mut(&mut self) -> &mut dyn Any {
+    self
+  }
+}
+
 /// Trait for all child windows
-pub trait ChildWindow : Debug {
+pub trait ChildWindow : AsAny + Debug + 'static {
 
   /// Called when a requested window is created.
   fn created_window(&self, window: Window);
@@ -147,14 +256,19 @@ pub trait ChildWindow : Debug {
 
   fn get_pixmap(&self) -> Pixmap;
 
+  /// Whether the window has been drawn since its layout last copied its pixmap
+  fn is_dirty(&self) -> bool;
+  fn set_dirty(&mut self, dirty: bool);
+
   fn get_name(&self) -> String;
+  /// Sets the name that MainApp::find() looks the window up by
   fn set_name(&mut self, name: String);
 
   fn get_window_type(&self) -> String;
   fn set_window_type(&mut self, window_type: String);
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>>;
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>);
+  fn get_event_loop(&self) -> Rc<EventProxy>;
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>);
 
   fn get_enabled(&self) -> bool;
   fn set_enabled(&mut self, enabled: bool);
@@ -162,6 +276,16 @@ pub trait ChildWindow : Debug {
   fn get_focused(&self) -> bool;
   fn set_focused(&mut self, focused: bool);
 
+  /// Whether Tab and Shift+Tab can move the input focus to this window
+  fn get_accepts_focus(&self) -> bool;
+  fn set_accepts_focus(&mut self, accepts_focus: bool);
+
+  /// Whether this window processes Tab itself, such as to insert a tab character
+  ///
+  /// Ctrl+Tab and Ctrl+Shift+Tab always move the input focus.
+  fn get_accepts_tab(&self) -> bool;
+  fn set_accepts_tab(&mut self, accepts_tab: bool);
+
   fn get_location(&self) -> (f64, f64);
   fn set_location(&mut self, x: f64, y: f64);
 
@@ -181,6 +305,13 @@ pub trait ChildWindow : Debug {
 
   fn get_drawing_size(&self) -> (f64, f64);
 
+  /// Gets the height the window needs when it is width pixels wide
+  ///
+  /// Returns None if the window's height doesn't depend on its width. It is
+  /// used by layouts for windows, such as a Label that wraps its text, whose
+  /// contents are rearranged to fit their width.
+  fn get_height_for_width(&self, width: f64) -> Option<f64>;
+
   fn get_x_scroll(&self) -> f64;
   fn set_x_scroll(&mut self, x_scroll: f64);
   fn get_x_scroll_min(&self) -> f64;
@@ -206,22 +337,34 @@ pub trait ChildWindow : Debug {
   fn set_background_color(&mut self, color: Color);
   /// End WindowBase pass-through functions ///
 
+  /// Performs an editing action, such as pasting the clipboard's text
+  fn handle_edit_action(&mut self, action: EditAction);
+
   /// Processes keyboard events when this window has focus
-  fn handle_keyboard_pressed_event(&mut self, event: KeyEvent);
-  fn handle_keyboard_released_event(&mut self, event: KeyEvent);
+  ///
+  /// modifiers are the modifier keys, such as Ctrl and Shift, that are held down
+  fn handle_keyboard_pressed_event(&mut self, event: KeyboardEvent, modifiers: ModifiersState);
+  fn handle_keyboard_released_event(&mut self, event: KeyboardEvent);
 
   /// Processes mouse click events when this window has focus
   ///
-  /// mouse_x and mouse_y are relative to the main window
+  /// mouse_x and mouse_y are relative to the main window, and modifiers are the
+  /// modifier keys that are held down
   fn handle_mouse_pressed(&mut self, _button: MouseButton,
-      mouse_x: f64, mouse_y: f64);
+      mouse_x: f64, mouse_y: f64, modifiers: ModifiersState);
   fn handle_mouse_released(&mut self, _button: MouseButton,
       mouse_x: f64, mouse_y: f64);
 
+  /// Processes a double click, which follows the second call to handle_mouse_pressed()
+  ///
+  /// mouse_x and mouse_y are relative to the main window
+  fn handle_mouse_double_click(&mut self, button: MouseButton,
+      mouse_x: f64, mouse_y: f64);
+
   /// Processes mouse drag event
-  fn handle_mouse_drag(&mut self, main_win_x: f64, main_win_y: f64);
-  fn handle_mouse_drag_start(&mut self, main_win_x: f64, main_win_y: f64);
-  fn handle_mouse_drag_end(&mut self, main_win_x: f64, main_win_y: f64);
+  fn handle_mouse_drag(&mut self, main_win_x: f64, main_win_y: f64, modifiers: ModifiersState);
+  fn handle_mouse_drag_start(&mut self, main_win_x: f64, main_win_y: f64, modifiers: ModifiersState);
+  fn handle_mouse_drag_end(&mut self, main_win_x: f64, main_win_y: f64, modifiers: ModifiersState);
 
   /// Processes mouse movement events when this window has focus
   fn handle_mouse_movement(&mut self, main_win_x: f64, main_win_y: f64);
@@ -254,6 +397,19 @@ pub enum ChildType {
   Layout(Rc<RefCell<dyn Layout>>),
 }
 
+impl ChildType {
+
+  /// Whether the window or layout has changed since its parent last drew it
+  pub fn is_dirty(&self) -> bool {
+
+    // A child that is already borrowed is being changed
+    match self {
+      ChildType::Window(window) => window.try_borrow().map_or(true, |window_ref| window_ref.is_dirty()),
+      ChildType::Layout(layout) => layout.try_borrow().map_or(true, |layout_ref| layout_ref.is_dirty()),
+    }
+  }
+}
+
 /// Indicates a child's location within a BorderLayout
 #[derive(Eq, Hash, PartialEq, Clone)]
 pub enum BorderLocation {
@@ -264,7 +420,8 @@ pub enum BorderLocation {
   CENTER,
 }
 
-/// Alignment of a window within a Layout
+/// Alignment of a window within a Layout, or of text within a window
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Alignment {
   BOTTOM,
   CENTER,
@@ -323,6 +480,9 @@ pub trait Layout : Debug {
   /// Gets the child that contains the specified screen pixel location
   fn get_child_at(&self, x: f64, y: f64) -> Option<Rc<RefCell<dyn ChildWindow>>>;
 
+  /// Gets the child windows that can receive the input focus, in Tab order
+  fn get_focus_chain(&self) -> Vec<Rc<RefCell<dyn ChildWindow>>>;
+
   /// Gets the child window with the specified ID
   fn get_child_with_id(&mut self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>>;
 
@@ -349,8 +509,8 @@ pub trait Layout : Debug {
   fn get_uuid(&self) -> Uuid;
   fn set_uuid(&mut self, uuid: Uuid);
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>>;
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>);
+  fn get_event_loop(&self) -> Rc<EventProxy>;
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>);
 
   fn get_main_win_uuid(&self) -> Uuid;
   fn set_main_win_uuid(&mut self, main_win_uuid: Uuid);
@@ -358,6 +518,10 @@ pub trait Layout : Debug {
   fn get_pixmap(&self) -> Pixmap;
   fn set_pixmap(&mut self, pixmap: Pixmap);
 
+  /// Whether the layout, or any of its descendants, has changed since it was last laid out
+  fn is_dirty(&self) -> bool;
+  fn set_dirty(&mut self, dirty: bool);
+
   fn get_main_win_x(&self) -> f64;
   fn set_main_win_x(&mut self, main_win_x: f64);
 
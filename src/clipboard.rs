@@ -0,0 +1,303 @@
+use std::{
+  cell::RefCell,
+  env,
+  io::{Read, Write},
+  path::Path,
+  process::{Child, Command, ExitStatus, Stdio},
+  sync::mpsc,
+  thread,
+  time::{Duration, Instant},
+};
+
+// How long the clipboard programs are given to finish by default
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+// How often a clipboard program is checked to see whether it has finished
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+thread_local! {
+  // Backend used by the Clipboard functions; created when the clipboard is first used
+  static BACKEND: RefCell<Option<Box<dyn ClipboardBackend>>> = const { RefCell::new(None) };
+}
+
+/// Trait for the storage behind the Clipboard
+///
+/// Applications can provide their own backend, such as one based on a
+/// platform clipboard crate, with Clipboard::set_backend().
+pub trait ClipboardBackend {
+
+  /// Gets the text on the clipboard, if there is any
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  fn get_text(&mut self) -> Result<Option<String>, String>;
+
+  /// Replaces the contents of the clipboard with text
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+/// Clipboard used for copying, cutting, and pasting text
+///
+/// By default, the system clipboard is used if one of the following programs
+/// is installed: wl-copy/wl-paste (Wayland), xclip or xsel (X11), or
+/// pbcopy/pbpaste (macOS). Otherwise, and when running headless, the text is
+/// only shared within the application.
+pub struct Clipboard {
+}
+
+impl Clipboard {
+
+  /// Gets the text on the clipboard, if there is any
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  pub fn get_text() -> Result<Option<String>, String> {
+    Clipboard::with_backend(|backend| backend.get_text())
+  }
+
+  /// Replaces the contents of the clipboard with text
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  pub fn set_text(text: &str) -> Result<(), String> {
+    Clipboard::with_backend(|backend| backend.set_text(text))
+  }
+
+  /// Replaces the clipboard's backend for the current thread
+  pub fn set_backend(backend: Box<dyn ClipboardBackend>) {
+    BACKEND.with(|current| *current.borrow_mut() = Some(backend));
+  }
+
+  // Calls a function with the backend, creating the default backend if necessary
+  fn with_backend<T, F: FnOnce(&mut Box<dyn ClipboardBackend>) -> T>(func: F) -> T {
+
+    BACKEND.with(|current| {
+      let mut current = current.borrow_mut();
+      let backend = current.get_or_insert_with(|| {
+        match CommandClipboard::detect() {
+          Some(system) => Box::new(system),
+          None => Box::new(InProcessClipboard::new()),
+        }
+      });
+      func(backend)
+    })
+  }
+}
+
+/// Clipboard backend that only shares text within the application
+#[derive(Debug, Default)]
+pub struct InProcessClipboard {
+  text: Option<String>,
+}
+
+impl InProcessClipboard {
+
+  pub fn new() -> Self {
+    Self {
+      text: None,
+    }
+  }
+}
+
+impl ClipboardBackend for InProcessClipboard {
+
+  fn get_text(&mut self) -> Result<Option<String>, String> {
+    Ok(self.text.clone())
+  }
+
+  fn set_text(&mut self, text: &str) -> Result<(), String> {
+    self.text = Some(text.to_string());
+    Ok(())
+  }
+}
+
+/// Clipboard backend that uses the system clipboard's command-line programs
+///
+/// The programs run on the UI thread, so one that hasn't finished after the
+/// timeout, such as when the application that owns the clipboard doesn't
+/// respond, is killed and the copy or paste fails.
+#[derive(Debug)]
+pub struct CommandClipboard {
+  copy_command: Vec<String>,      // program and arguments that read the text from stdin
+  paste_command: Vec<String>,     // program and arguments that write the text to stdout
+  timeout: Duration,              // how long the programs are given to finish
+}
+
+impl CommandClipboard {
+
+  pub fn new(copy_command: Vec<String>, paste_command: Vec<String>) -> Self {
+    Self {
+      copy_command,
+      paste_command,
+      timeout: COMMAND_TIMEOUT,
+    }
+  }
+
+  /// Sets how long the programs are given to finish; the default is two seconds
+  pub fn set_timeout(&mut self, timeout: Duration) {
+    self.timeout = timeout;
+  }
+
+  /// Finds the programs for the current display's clipboard
+  ///
+  /// Returns None if there is no display or none of the programs are installed.
+  pub fn detect() -> Option<Self> {
+
+    let mut candidates: Vec<(&str, Vec<&str>, Vec<&str>)> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+      candidates.push(("wl-copy", vec!["wl-copy"], vec!["wl-paste", "--no-newline"]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+      candidates.push(("xclip", vec!["xclip", "-selection", "clipboard"], vec!["xclip", "-selection", "clipboard", "-o"]));
+      candidates.push(("xsel", vec!["xsel", "--clipboard", "--input"], vec!["xsel", "--clipboard", "--output"]));
+    }
+    if cfg!(target_os = "macos") {
+      candidates.push(("pbcopy", vec!["pbcopy"], vec!["pbpaste"]));
+    }
+
+    for (program, copy_command, paste_command) in candidates {
+      if CommandClipboard::is_installed(program) {
+        return Some(CommandClipboard::new(
+              copy_command.iter().map(|arg| arg.to_string()).collect(),
+              paste_command.iter().map(|arg| arg.to_string()).collect()
+        ));
+      }
+    }
+
+    None
+  }
+
+  // Waits for a program to exit, killing it if it is still running at the deadline
+  fn wait_until(child: &mut Child, program: &str, deadline: Instant) -> Result<ExitStatus, String> {
+
+    loop {
+
+      match child.try_wait() {
+        Ok(Some(status)) => return Ok(status),
+        Ok(None) => {},
+        Err(err) => return Err(format!("{program} failed: {err}")),
+      }
+
+      if Instant::now() >= deadline {
+        CommandClipboard::kill(child);
+        return Err(format!("{program} did not finish in time"));
+      }
+
+      thread::sleep(COMMAND_POLL_INTERVAL);
+    }
+  }
+
+  // Stops a program that is taking too long
+  fn kill(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+  }
+
+  // Returns true if a program is in one of the PATH directories
+  fn is_installed(program: &str) -> bool {
+
+    match env::var_os("PATH") {
+      Some(paths) => env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file()),
+      None => false,
+    }
+  }
+}
+
+impl ClipboardBackend for CommandClipboard {
+
+  fn get_text(&mut self) -> Result<Option<String>, String> {
+
+    let program = &self.paste_command[0];
+    let deadline = Instant::now() + self.timeout;
+    let mut child = match Command::new(program)
+          .args(&self.paste_command[1..])
+          .stdin(Stdio::null())
+          .stdout(Stdio::piped())
+          .stderr(Stdio::null())
+          .spawn() {
+      Ok(child) => child,
+      Err(err) => return Err(format!("Cannot run {program}: {err}")),
+    };
+
+    // The output is read on another thread, so that the program can be
+    // killed if it doesn't finish writing it in time
+    let mut stdout = match child.stdout.take() {
+      Some(stdout) => stdout,
+      None => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("Cannot read from {program}"));
+      },
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+      let mut data = Vec::new();
+      let _ = sender.send(stdout.read_to_end(&mut data).map(|_count| data));
+    });
+
+    let data = match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+      Ok(Ok(data)) => data,
+      Ok(Err(err)) => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("Cannot read from {program}: {err}"));
+      },
+      Err(_err) => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("{program} did not finish in time"));
+      },
+    };
+
+    // The programs fail when the clipboard doesn't contain text
+    match CommandClipboard::wait_until(&mut child, program, deadline)? {
+      status if status.success() => Ok(Some(String::from_utf8_lossy(&data).to_string())),
+      _status => Ok(None),
+    }
+  }
+
+  fn set_text(&mut self, text: &str) -> Result<(), String> {
+
+    let program = &self.copy_command[0];
+    let deadline = Instant::now() + self.timeout;
+    let mut child = match Command::new(program)
+          .args(&self.copy_command[1..])
+          .stdin(Stdio::piped())
+          .stdout(Stdio::null())
+          .stderr(Stdio::null())
+          .spawn() {
+      Ok(child) => child,
+      Err(err) => return Err(format!("Cannot run {program}: {err}")),
+    };
+
+    // The text is written on another thread, so that the program can be
+    // killed if it stops reading it. The program exits once its input is
+    // closed, which happens when the thread drops stdin.
+    let mut stdin = match child.stdin.take() {
+      Some(stdin) => stdin,
+      None => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("Cannot write to {program}"));
+      },
+    };
+    let data = text.as_bytes().to_vec();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+      let _ = sender.send(stdin.write_all(&data));
+    });
+
+    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+      Ok(Ok(())) => {},
+      Ok(Err(err)) => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("Cannot write to {program}: {err}"));
+      },
+      Err(_err) => {
+        CommandClipboard::kill(&mut child);
+        return Err(format!("{program} did not finish in time"));
+      },
+    }
+
+    match CommandClipboard::wait_until(&mut child, program, deadline) {
+      Ok(status) if status.success() => Ok(()),
+      Ok(status) => Err(format!("{program} failed: {status}")),
+      Err(err) => Err(err),
+    }
+  }
+}
@@ -1,20 +1,12 @@
 use winit::{
-  dpi::{LogicalSize, PhysicalPosition},
+  dpi::{LogicalPosition, LogicalSize},
   event::{ElementState, WindowEvent},
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::{ Window },
 };
 
-use softbuffer::{
-  Context,
-  Surface,
-};
-
 use tiny_skia::{
-  Color,
   Pixmap,
-  PixmapPaint,
-  Transform,
 };
 
 use uuid::Uuid;
@@ -22,7 +14,6 @@ use uuid::Uuid;
 use std:: {
   cell::RefCell,
   fmt::Debug,
-  num::NonZeroU32,
   rc::Rc,
 };
 use crate::button::Button;
@@ -31,23 +22,29 @@ use crate::child_window::{
   LayoutArgs,
   Orientation,
 };
+use crate::event_proxy::EventProxy;
 use crate::row_layout::RowLayout;
+use crate::theme::Theme;
 use crate::UserEvent;
+use crate::window_surface::{Damage, WindowSurface};
 use crate::WindowUtils;
 
 /// A pop-up context menu
 pub struct ContextMenu {
   uuid: Uuid,
   main_win_uuid: Uuid,
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
-  window: Window,
+  event_loop_proxy: Rc<EventProxy>,
+  window: Rc<Window>,
+  surface: Option<WindowSurface>, // created when the window is first displayed
   pixmap: Pixmap,
+  damage: Damage,                 // regions of the pixmap that haven't been displayed
   width: f64,
   height: f64,
   layout: Box<dyn Layout>,
   initial_draw_performed: bool,
   cursor_x: f64,                  // Current mouse location within the window
   cursor_y: f64,
+  modifiers: ModifiersState,      // Modifier keys that are currently pressed
 }
 
 impl ContextMenu {
@@ -56,7 +53,7 @@ impl ContextMenu {
         uuid: Uuid,
         main_win_uuid: Uuid,
         window: Window,
-        event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+        event_loop_proxy: Rc<EventProxy>,
         width: f64,
         height: f64
   ) -> ContextMenu {
@@ -76,14 +73,17 @@ impl ContextMenu {
       uuid: uuid,
       main_win_uuid: main_win_uuid,
       event_loop_proxy: event_loop_proxy.clone(),
-      window: window,
-      pixmap: Pixmap::new(width as u32, height as u32).unwrap(),
+      window: Rc::new(window),
+      surface: None,
+      pixmap: Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32).unwrap(),
+      damage: Damage::default(),
       width: width,
       height: height,
       layout: layout,
       initial_draw_performed: false,
       cursor_x: 0.0,
       cursor_y: 0.0,
+      modifiers: ModifiersState::empty(),
     }
   }
 
@@ -106,30 +106,25 @@ impl ContextMenu {
     self.layout.clear();
   }
 
-  // Displays the Pixmap onto the screen
+  // Displays the regions of the Pixmap that have changed onto the screen
   fn display_pixmap(&mut self) {
 
-    // Get the size of the window
-    let width = self.pixmap.width();
-    let height = self.pixmap.height();
-
-    // Create the drawing surface
-    let context = (Context::new(&self.window)).unwrap();
-    let mut surface = (Surface::new(&context, &self.window)).unwrap();
-    surface
-        .resize(NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap())
-        .unwrap();
-
-    // Copy the pixmap onto the drawing surface
-    let mut buffer = surface.buffer_mut().unwrap();
-    for index in 0..(width * height) as usize {
-      buffer[index] =
-          (self.pixmap.data()[index * 4 + 2] as u32) |
-          ((self.pixmap.data()[index * 4 + 1] as u32) << 8) |
-          ((self.pixmap.data()[index * 4] as u32) << 16);
+    // Create the drawing surface, which is kept until the window closes
+    if self.surface.is_none() {
+      match WindowSurface::new(self.window.clone()) {
+        Ok(surface) => self.surface = Some(surface),
+        Err(err) => {
+          println!("In ContextMenu::display_pixmap(), {err}");
+          return;
+        },
+      }
     }
 
-    buffer.present().unwrap();
+    if let Some(surface) = &mut self.surface {
+      if let Err(err) = surface.present(&self.pixmap, self.damage.take()) {
+        println!("In ContextMenu::display_pixmap(), {err}");
+      }
+    }
   }
 
   pub fn get_size(&self) -> (f64, f64) {
@@ -140,9 +135,9 @@ impl ContextMenu {
     self.uuid
   }
 
-  /// Moves the window
+  /// Moves the window, in logical units
   pub fn move_window(&self, x: f64, y: f64) {
-    self.window.set_outer_position(PhysicalPosition::new(x, y));
+    self.window.set_outer_position(LogicalPosition::new(x, y));
   }
 
   /// Called by the main window when there is an event for the context menu.
@@ -159,9 +154,13 @@ impl ContextMenu {
 
       WindowEvent::CursorMoved{position, ..} => {
 
-        // Save the new position, which is relative to the main window
-        self.cursor_x = position.x;
-        self.cursor_y = position.y;
+        // Save the new position, which is relative to the main window, in logical units
+        self.cursor_x = WindowUtils::to_logical(position.x);
+        self.cursor_y = WindowUtils::to_logical(position.y);
+      },
+
+      WindowEvent::ModifiersChanged(modifiers) => {
+        self.modifiers = modifiers.state();
       },
 
       WindowEvent::MouseInput{state, button, ..} => {
@@ -183,7 +182,7 @@ impl ContextMenu {
 
                 // Pass the event to the button
                 let mut child = child_rc.borrow_mut();
-                child.handle_mouse_pressed(button, self.cursor_x, self.cursor_y);
+                child.handle_mouse_pressed(button, self.cursor_x, self.cursor_y, self.modifiers);
               },
 
               None => {},
@@ -208,14 +207,14 @@ impl ContextMenu {
       WindowEvent::Resized(size) => {
 
         // If the size of the window has changed, create a new Pixmap for drawing.
-        if size.width as f64 != self.width || size.height as f64 != self.height {
+        if size.width != self.pixmap.width() || size.height != self.pixmap.height() {
 
-          // Save the new size
-          self.width = size.width as f64;
-          self.height = size.height as f64;
+          // Save the new size, which is in physical pixels
+          self.width = WindowUtils::to_logical(size.width as f64);
+          self.height = WindowUtils::to_logical(size.height as f64);
 
           // Create the pixmap into which we will draw
-          self.pixmap = Pixmap::new(self.width as u32, self.height as u32).unwrap();
+          self.pixmap = Pixmap::new(size.width, size.height).unwrap();
 
 
           // On intial start-up, the main window will receive this event
@@ -237,23 +236,21 @@ impl ContextMenu {
     // Get the size of the window
     let (width, height) = self.get_size();
 
+    // If the scale factor has changed, create a pixmap of the new size
+    let (pixmap_width, pixmap_height) = (WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32);
+    if pixmap_width != self.pixmap.width() || pixmap_height != self.pixmap.height() {
+      self.pixmap = Pixmap::new(pixmap_width, pixmap_height).unwrap();
+    }
+
     // Fill the pixmap with the background color
-    self.pixmap.fill(Color::WHITE);
+    self.pixmap.fill(Theme::current().palette.background);
 
     // Get the layout's pixmap
     let layout_pixmap = self.layout.layout(0.0, 0.0, width as f64, height as f64);
 
-    let paint = PixmapPaint::default();
-
     // Copy the layout's pixmap image onto the full pixmap
-    self.pixmap.draw_pixmap(
-        0,
-        0,
-        layout_pixmap.as_ref(),
-        &paint,
-        Transform::identity(),
-        None,
-    );
+    WindowUtils::draw_child_pixmap(&mut self.pixmap, 0.0, 0.0, &layout_pixmap);
+    self.damage.add_pixmap(&self.pixmap);
 
     // Display the pixmap
     self.display_pixmap();
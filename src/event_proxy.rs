@@ -0,0 +1,269 @@
+use winit::event_loop::EventLoopProxy;
+
+use tiny_skia::{IntRect, Pixmap};
+
+use uuid::Uuid;
+
+use crate::{ChildType, ChildWindow};
+use crate::MainApp;
+use crate::registry::Registry;
+use crate::signal::Signals;
+use crate::task::{TaskId, Tasks};
+use crate::timer::Timers;
+use crate::ui_handle::UiHandle;
+use crate::UserEvent;
+use crate::WindowUtils;
+
+use std::{
+  cell::RefCell,
+  collections::VecDeque,
+  fmt,
+  future::Future,
+  rc::Rc,
+  sync::{Arc, Mutex},
+};
+
+/// Delivers UserEvents to the MainApp
+///
+/// When the application is running inside a winit event loop, the events are
+/// sent through the event loop's proxy. A headless EventProxy stores the events
+/// in a queue instead, which the MainApp empties when
+/// MainApp::process_events() is called.
+///
+/// Requests to redraw a region of a top-level window are not sent as events.
+/// They are kept by the EventProxy until the MainApp draws the next frame, and
+/// a request replaces the earlier ones whose regions it covers, so a window
+/// that changes many times between frames is only copied once. The MainApp's
+/// timers and background tasks, the listeners that are connected to the
+/// windows, and the registry of the windows that have been added to the
+/// layouts, are also kept here, so that the windows and layouts can add them.
+///
+/// An EventProxy can only be used on the UI thread. Other threads send events
+/// with a UiHandle, which is created with get_ui_handle().
+#[derive(Clone)]
+pub struct EventProxy {
+  sender: EventSender,
+  pending_redraws: Rc<RefCell<Vec<PendingRedraw>>>,
+  timers: Rc<RefCell<Timers>>,
+  tasks: Rc<RefCell<Tasks>>,
+  signals: Rc<RefCell<Signals>>,
+  registry: Rc<RefCell<Registry>>,
+}
+
+// Where the events are sent
+#[derive(Clone)]
+enum EventSender {
+  EventLoop(EventLoopProxy<UserEvent>),
+  Headless(Arc<Mutex<VecDeque<UserEvent>>>),   // shared with the UiHandles
+}
+
+// A request to draw a pixmap at a location within a top-level window
+struct PendingRedraw {
+  main_win_uuid: Uuid,
+  x: f64,
+  y: f64,
+  pixmap: Pixmap,
+}
+
+impl PendingRedraw {
+
+  // Gets the region that the pixmap covers, in physical pixels
+  fn get_region(&self) -> Option<IntRect> {
+    IntRect::from_xywh(
+          WindowUtils::to_physical(self.x) as i32,
+          WindowUtils::to_physical(self.y) as i32,
+          self.pixmap.width(),
+          self.pixmap.height()
+    )
+  }
+}
+
+impl EventProxy {
+
+  fn new(sender: EventSender) -> Self {
+    Self {
+      sender,
+      pending_redraws: Rc::new(RefCell::new(Vec::new())),
+      timers: Rc::new(RefCell::new(Timers::default())),
+      tasks: Rc::new(RefCell::new(Tasks::default())),
+      signals: Rc::new(RefCell::new(Signals::default())),
+      registry: Rc::new(RefCell::new(Registry::default())),
+    }
+  }
+
+  /// Creates an EventProxy that queues events for a headless MainApp
+  pub fn new_headless() -> Self {
+    EventProxy::new(EventSender::Headless(Arc::new(Mutex::new(VecDeque::new()))))
+  }
+
+  /// Returns true if the events are queued, instead of being sent to an event loop
+  pub fn is_headless(&self) -> bool {
+    match self.sender {
+      EventSender::EventLoop(_) => false,
+      EventSender::Headless(_) => true,
+    }
+  }
+
+  /// Removes the oldest queued event
+  ///
+  /// This always returns None if the events are sent to an event loop.
+  pub fn next_queued_event(&self) -> Option<UserEvent> {
+    match &self.sender {
+      EventSender::EventLoop(_) => None,
+      EventSender::Headless(queue) => match queue.lock() {
+        Ok(mut queue) => queue.pop_front(),
+        Err(_err) => None,
+      },
+    }
+  }
+
+  /// Gets the registry of the windows that have been added to the layouts
+  pub(crate) fn get_registry(&self) -> &RefCell<Registry> {
+    &self.registry
+  }
+
+  /// Records a window or layout that has been added to a parent in the registry
+  pub(crate) fn register_child(&self, parent: Uuid, child: &ChildType) {
+    let dropped = self.registry.borrow_mut().add_child(parent, child);
+    self.signals.borrow_mut().disconnect_sources(&dropped);
+  }
+
+  /// Records a window that has been added to a parent in the registry
+  pub(crate) fn register_window(&self, parent: Uuid, window: &Rc<RefCell<dyn ChildWindow>>) {
+    let dropped = self.registry.borrow_mut().add_window(parent, window);
+    self.signals.borrow_mut().disconnect_sources(&dropped);
+  }
+
+  /// Forgets a window, layout, tab, or pop-up that has been removed, along
+  /// with everything that it contains, and disconnects the windows' listeners
+  pub(crate) fn unregister(&self, uuid: Uuid) {
+    let removed = self.registry.borrow_mut().remove(uuid);
+    self.signals.borrow_mut().disconnect_sources(&removed);
+  }
+
+  /// Forgets the windows and layouts that a parent contains, and disconnects the windows' listeners
+  pub(crate) fn unregister_children(&self, parent: Uuid) {
+    let removed = self.registry.borrow_mut().remove_children(parent);
+    self.signals.borrow_mut().disconnect_sources(&removed);
+  }
+
+  /// Gets the listeners that are connected to the windows
+  pub(crate) fn get_signals(&self) -> &RefCell<Signals> {
+    &self.signals
+  }
+
+  /// Gets the tasks that are running in the background
+  pub(crate) fn get_tasks(&self) -> &RefCell<Tasks> {
+    &self.tasks
+  }
+
+  /// Gets the timers that the MainApp runs
+  pub(crate) fn get_timers(&self) -> &RefCell<Timers> {
+    &self.timers
+  }
+
+  /// Creates a handle for sending events to the MainApp from other threads
+  pub fn get_ui_handle(&self) -> UiHandle {
+    match &self.sender {
+      EventSender::EventLoop(proxy) => UiHandle::new_event_loop(proxy.clone()),
+      EventSender::Headless(queue) => UiHandle::new_headless(queue.clone()),
+    }
+  }
+
+  /// Gets the number of redraw requests that are waiting for the next frame
+  pub fn get_pending_redraw_count(&self) -> usize {
+    self.pending_redraws.borrow().len()
+  }
+
+  /// Keeps a request to draw a pixmap within a top-level window until the next frame
+  ///
+  /// x and y are the location of the pixmap within the window, in logical units.
+  pub(crate) fn queue_redraw(&self, main_win_uuid: Uuid, x: f64, y: f64, pixmap: Pixmap) {
+
+    let redraw = PendingRedraw { main_win_uuid, x, y, pixmap };
+    let mut pending_redraws = self.pending_redraws.borrow_mut();
+
+    // Drop the earlier requests that this one draws over
+    if let Some(region) = redraw.get_region() {
+      pending_redraws.retain(|pending| {
+        pending.main_win_uuid != main_win_uuid ||
+              !pending.get_region().is_some_and(|pending_region| region.contains(&pending_region))
+      });
+    }
+
+    pending_redraws.push(redraw);
+  }
+
+  /// Removes the redraw requests for a top-level window, which are no longer
+  /// needed when the whole window is drawn again
+  pub(crate) fn discard_redraws(&self, main_win_uuid: Uuid) {
+    self.pending_redraws.borrow_mut().retain(|pending| pending.main_win_uuid != main_win_uuid);
+  }
+
+  /// Removes all of the redraw requests, as Redraw events in the order they were made
+  pub(crate) fn take_redraws(&self) -> Vec<UserEvent> {
+    self.pending_redraws.borrow_mut()
+      .drain(..)
+      .map(|pending| UserEvent::Redraw(pending.main_win_uuid, pending.x, pending.y, pending.pixmap))
+      .collect()
+  }
+
+  /// Runs a future on a background thread, and then calls on_complete with its output on the UI thread
+  ///
+  /// This is the same as MainApp::spawn(), for use where the MainApp isn't available.
+  pub fn spawn<T, F, C>(&self, future: F, on_complete: C) -> TaskId
+  where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    C: FnOnce(&mut MainApp, T) + 'static,
+  {
+    self.tasks.borrow_mut().spawn(self.get_ui_handle(), None, future, on_complete)
+  }
+
+  /// Runs a future on a background thread for a pop-up or a tab
+  ///
+  /// This is the same as MainApp::spawn_for(), for use where the MainApp isn't available.
+  pub fn spawn_for<T, F, C>(&self, owner: Uuid, future: F, on_complete: C) -> TaskId
+  where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    C: FnOnce(&mut MainApp, T) + 'static,
+  {
+    self.tasks.borrow_mut().spawn(self.get_ui_handle(), Some(owner), future, on_complete)
+  }
+
+  /// Sends an event to the MainApp
+  pub fn send_event(&self, event: UserEvent) -> Result<(), String> {
+    match &self.sender {
+      EventSender::EventLoop(proxy) => match proxy.send_event(event) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("{err}")),
+      },
+      EventSender::Headless(queue) => match queue.lock() {
+        Ok(mut queue) => {
+          queue.push_back(event);
+          Ok(())
+        },
+        Err(err) => Err(format!("{err}")),
+      },
+    }
+  }
+}
+
+impl From<EventLoopProxy<UserEvent>> for EventProxy {
+  fn from(proxy: EventLoopProxy<UserEvent>) -> Self {
+    EventProxy::new(EventSender::EventLoop(proxy))
+  }
+}
+
+impl fmt::Debug for EventProxy {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.sender {
+      EventSender::EventLoop(_) => write!(f, "EventProxy::EventLoop"),
+      EventSender::Headless(queue) => match queue.lock() {
+        Ok(queue) => write!(f, "EventProxy::Headless({} queued)", queue.len()),
+        Err(_err) => write!(f, "EventProxy::Headless"),
+      },
+    }
+  }
+}
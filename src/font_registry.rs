@@ -0,0 +1,337 @@
+use crate::text_font::{TextFont, DEFAULT_FONT_SIZE};
+
+use ttf_parser::{name_id, Face, Language};
+
+use std::{
+  collections::HashSet,
+  env,
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// Weight of a regular font
+pub const FONT_WEIGHT_NORMAL: u16 = 400;
+
+/// Weight of a bold font
+pub const FONT_WEIGHT_BOLD: u16 = 700;
+
+/// Slant of a font
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontStyle {
+  Normal,
+  Italic,
+  Oblique,
+}
+
+/// Description of an installed font
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontInfo {
+  pub family: String,        // family name, such as "DejaVu Sans"
+  pub weight: u16,           // 100 (thin) to 900 (black); 400 is normal and 700 is bold
+  pub style: FontStyle,
+  pub monospaced: bool,      // true if all of the characters have the same width
+  pub path: PathBuf,         // file containing the font
+  pub index: u32,            // index of the font within the file, for collections
+}
+
+/// List of the fonts that are installed on the system
+///
+/// The directories used by fontconfig are scanned for TrueType and OpenType
+/// fonts, so that applications can find them by family, weight, and style
+/// instead of by path.
+#[derive(Debug)]
+pub struct FontRegistry {
+  directories: Vec<PathBuf>,
+  fonts: Vec<FontInfo>,
+}
+
+impl FontRegistry {
+
+  /// Creates a registry containing the fonts in the system's font directories
+  ///
+  /// The directories are read from the fontconfig configuration
+  /// (/etc/fonts/fonts.conf, or the file named by FONTCONFIG_FILE). If there
+  /// is none, the usual font directories for the platform are used.
+  pub fn new() -> Self {
+    FontRegistry::with_directories(FontRegistry::get_system_directories())
+  }
+
+  /// Creates a registry containing the fonts in the specified directories and their sub-directories
+  ///
+  /// Symbolic links are followed, but each directory and file is only scanned
+  /// once, even if it is reached through several paths.
+  pub fn with_directories(directories: Vec<PathBuf>) -> Self {
+
+    let mut fonts = Vec::new();
+    let mut scanned = HashSet::new();
+    for directory in &directories {
+      FontRegistry::scan_directory(directory, &mut scanned, &mut fonts);
+    }
+
+    Self {
+      directories,
+      fonts,
+    }
+  }
+
+  /// Gets the font in a family that is the closest match for a weight and style
+  ///
+  /// The family name is not case sensitive. A font with the requested style
+  /// is preferred over one with the requested weight.
+  pub fn find(&self, family: &str, weight: u16, style: FontStyle) -> Option<&FontInfo> {
+
+    let family = family.to_lowercase();
+    self.fonts.iter()
+      .filter(|font| font.family.to_lowercase() == family)
+      .min_by_key(|font| {
+        let style_difference = match (font.style, style) {
+          (found, wanted) if found == wanted => 0,
+          (FontStyle::Normal, _) | (_, FontStyle::Normal) => 2,
+          _ => 1,
+        };
+        (style_difference, font.weight.abs_diff(weight))
+      })
+  }
+
+  /// Gets the directories that were scanned for fonts
+  pub fn get_directories(&self) -> &Vec<PathBuf> {
+    &self.directories
+  }
+
+  /// Gets the names of the font families, in alphabetical order
+  pub fn get_families(&self) -> Vec<String> {
+
+    let mut families: Vec<String> = self.fonts.iter().map(|font| font.family.clone()).collect();
+    families.sort();
+    families.dedup();
+
+    families
+  }
+
+  /// Gets all of the fonts that were found
+  pub fn get_fonts(&self) -> &Vec<FontInfo> {
+    &self.fonts
+  }
+
+  /// Loads the font in a family that is the closest match for a weight and style
+  ///
+  /// If the family is not installed or the font cannot be loaded, the
+  /// returned Result contains a string explaining why.
+  pub fn load(&self, family: &str, weight: u16, style: FontStyle, font_size: f32) -> Result<TextFont, String> {
+
+    let info = match self.find(family, weight, style) {
+      Some(info) => info,
+      None => return Err(format!("The font family {family} is not installed")),
+    };
+
+    match info.path.to_str() {
+      Some(path) => TextFont::from_file(path, info.index, font_size),
+      None => Err(format!("Cannot load the font {}", info.path.display())),
+    }
+  }
+
+  /// Makes the font in a family that is the closest match for a weight and style the default font
+  ///
+  /// It is used by the windows that are created after this is called.
+  pub fn set_default_font(&self, family: &str, weight: u16, style: FontStyle) -> Result<(), String> {
+
+    let font = self.load(family, weight, style, DEFAULT_FONT_SIZE)?;
+    TextFont::set_default_font(&font);
+
+    Ok(())
+  }
+
+  // Gets a font's family name, preferring the typographic family and the English name
+  fn get_family_name(face: &Face) -> Option<String> {
+
+    for id in [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY] {
+      let names: Vec<_> = face.names().into_iter()
+        .filter(|name| name.name_id == id && name.is_unicode())
+        .collect();
+      let english = names.iter().find(|name| name.language() == Language::English_UnitedStates);
+      if let Some(family) = english.or(names.first()).and_then(|name| name.to_string()) {
+        return Some(family);
+      }
+    }
+
+    None
+  }
+
+  // Gets the font directories used by fontconfig or, if it is not installed, the platform's usual ones
+  fn get_system_directories() -> Vec<PathBuf> {
+
+    let home = env::var("HOME").map(PathBuf::from).ok();
+
+    let config_file = match env::var("FONTCONFIG_FILE") {
+      Ok(file) => PathBuf::from(file),
+      Err(_) => PathBuf::from("/etc/fonts/fonts.conf"),
+    };
+    let mut directories = Vec::new();
+    if let Ok(config) = fs::read_to_string(&config_file) {
+      FontRegistry::parse_fontconfig(&config, home.as_deref(), &mut directories);
+
+      // The files in conf.d can add more directories
+      if let Some(config_dir) = config_file.parent() {
+        if let Ok(entries) = fs::read_dir(config_dir.join("conf.d")) {
+          let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+          paths.sort();
+          for path in paths {
+            if let Ok(config) = fs::read_to_string(&path) {
+              FontRegistry::parse_fontconfig(&config, home.as_deref(), &mut directories);
+            }
+          }
+        }
+      }
+    }
+
+    if directories.is_empty() {
+      directories.push(PathBuf::from("/usr/share/fonts"));
+      directories.push(PathBuf::from("/usr/local/share/fonts"));
+      directories.push(PathBuf::from("/System/Library/Fonts"));
+      directories.push(PathBuf::from("/Library/Fonts"));
+      if let Ok(windows_dir) = env::var("WINDIR") {
+        directories.push(Path::new(&windows_dir).join("Fonts"));
+      }
+      if let Some(home) = &home {
+        directories.push(home.join(".fonts"));
+        directories.push(home.join(".local/share/fonts"));
+        directories.push(home.join("Library/Fonts"));
+      }
+    }
+
+    directories.retain(|directory| directory.is_dir());
+    directories.dedup();
+
+    directories
+  }
+
+  // Adds the directories in the <dir> elements of a fontconfig file
+  fn parse_fontconfig(config: &str, home: Option<&Path>, directories: &mut Vec<PathBuf>) {
+
+    let mut remaining = config;
+    while let Some(start) = remaining.find("<dir") {
+      remaining = &remaining[start + 4..];
+
+      // Skip elements such as <dirname>
+      let (attributes, after) = match remaining.split_once('>') {
+        Some(parts) => parts,
+        None => break,
+      };
+      if !attributes.is_empty() && !attributes.starts_with(char::is_whitespace) {
+        continue;
+      }
+      let (value, after) = match after.split_once("</dir>") {
+        Some(parts) => parts,
+        None => break,
+      };
+      remaining = after;
+
+      let value = value.trim();
+      let directory = if attributes.contains("prefix=\"xdg\"") {
+        let data_home = match env::var("XDG_DATA_HOME") {
+          Ok(data_home) => PathBuf::from(data_home),
+          Err(_) => match home {
+            Some(home) => home.join(".local/share"),
+            None => continue,
+          },
+        };
+        data_home.join(value)
+      } else if let Some(relative) = value.strip_prefix('~') {
+        match home {
+          Some(home) => home.join(relative.trim_start_matches('/')),
+          None => continue,
+        }
+      } else {
+        PathBuf::from(value)
+      };
+
+      if !directories.contains(&directory) {
+        directories.push(directory);
+      }
+    }
+  }
+
+  // Adds the fonts in a directory and its sub-directories
+  //
+  // scanned holds the canonical paths of the directories and files that have
+  // already been scanned, so that a symbolic link back to a parent directory
+  // doesn't loop, and nested directories don't add the same fonts twice.
+  fn scan_directory(directory: &Path, scanned: &mut HashSet<PathBuf>, fonts: &mut Vec<FontInfo>) {
+
+    if !fs::canonicalize(directory).is_ok_and(|canonical| scanned.insert(canonical)) {
+      return;
+    }
+
+    let entries = match fs::read_dir(directory) {
+      Ok(entries) => entries,
+      Err(_) => return,
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+
+    for path in paths {
+
+      // Follow symbolic links to find what they point to
+      let is_dir = match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::metadata(&path).is_ok_and(|target| target.is_dir()),
+        Ok(metadata) => metadata.is_dir(),
+        Err(_) => continue,
+      };
+      if is_dir {
+        FontRegistry::scan_directory(&path, scanned, fonts);
+        continue;
+      }
+
+      let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+      match extension.as_deref() {
+        Some("ttf") | Some("otf") | Some("ttc") | Some("otc")
+          if fs::canonicalize(&path).is_ok_and(|canonical| scanned.insert(canonical)) => FontRegistry::scan_file(&path, fonts),
+        _ => {},
+      }
+    }
+  }
+
+  // Adds the fonts in a TrueType or OpenType file or collection
+  fn scan_file(path: &Path, fonts: &mut Vec<FontInfo>) {
+
+    let data = match fs::read(path) {
+      Ok(data) => data,
+      Err(_) => return,
+    };
+
+    let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+    for index in 0..count {
+      let face = match Face::parse(&data, index) {
+        Ok(face) => face,
+        Err(_) => continue,
+      };
+      let family = match FontRegistry::get_family_name(&face) {
+        Some(family) => family,
+        None => continue,
+      };
+
+      let style = match face.style() {
+        ttf_parser::Style::Normal => FontStyle::Normal,
+        ttf_parser::Style::Italic => FontStyle::Italic,
+        ttf_parser::Style::Oblique => FontStyle::Oblique,
+      };
+
+      fonts.push(FontInfo {
+        family,
+        weight: face.weight().to_number(),
+        style,
+        monospaced: face.is_monospaced(),
+        path: path.to_path_buf(),
+        index,
+      });
+    }
+  }
+}
+
+impl Default for FontRegistry {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,191 @@
+use winit::{
+  event::{
+    ElementState,
+    MouseButton,
+    MouseScrollDelta,
+    TouchPhase,
+  },
+  keyboard::{Key, ModifiersState},
+};
+
+use tiny_skia::{IntRect, Pixmap};
+
+use crate::child_window::KeyboardEvent;
+use crate::clipboard::{Clipboard, InProcessClipboard};
+use crate::MainApp;
+use crate::WindowUtils;
+
+use std::{
+  thread,
+  time::{Duration, Instant},
+};
+
+/// Functions for running a MainApp without a display
+///
+/// A headless MainApp is created by passing EventProxy::new_headless() to
+/// MainApp::new(). Instead of being displayed in a window, its contents are
+/// drawn into an offscreen Pixmap, which can be read with get_pixmap().
+/// Input is simulated with the send_*() functions, each of which processes
+/// the UserEvents that result from it before returning.
+impl MainApp {
+
+  /// Starts a headless MainApp
+  ///
+  /// This is used instead of run_event_loop(). The contents are laid out and
+  /// drawn, and the startup callback is called. The clipboard only shares
+  /// text within the application.
+  pub fn run_headless(&mut self) {
+
+    if !self.event_loop_proxy.is_headless() {
+      println!("MainApp::run_headless() requires a headless EventProxy");
+      return;
+    }
+
+    // Stop ignoring the draw events from the children
+    WindowUtils::set_ignore_events(false);
+
+    // Keep the system clipboard out of headless runs
+    Clipboard::set_backend(Box::new(InProcessClipboard::new()));
+
+    // Calculate the location and size of the window
+    self.calculate_location_and_size();
+
+    // Create the pixmap into which we will draw
+    self.pixmap = Pixmap::new(
+          WindowUtils::to_physical(self.width) as u32,
+          WindowUtils::to_physical(self.height) as u32
+    ).unwrap();
+
+    // Perform the initial draw
+    self.process_redraw_requested();
+    self.process_events();
+  }
+
+  /// Returns the pixmap containing the main window's current contents
+  pub fn get_pixmap(&self) -> &Pixmap {
+    &self.pixmap
+  }
+
+  /// Returns the regions of the pixmap that have been redrawn since the previous call
+  ///
+  /// The regions are in physical pixels. Without a display they are kept
+  /// instead of being presented, which shows how much of the main window a
+  /// change redraws.
+  pub fn take_damage(&mut self) -> Vec<IntRect> {
+    self.damage.take()
+  }
+
+  /// Processes the queued UserEvents
+  ///
+  /// This only needs to be called after changing a window directly, such as
+  /// with Label::set_text(). The windows that requested to be redrawn are then
+  /// drawn into the pixmap, as they are before each frame when there is a
+  /// display. It returns the number of events and redraw requests processed.
+  pub fn process_events(&mut self) -> usize {
+
+    let mut count = 0;
+    while let Some(event) = self.event_loop_proxy.next_queued_event() {
+      self.process_user_event(None, event);
+      count += 1;
+    }
+
+    count + self.draw_pending_redraws()
+  }
+
+  /// Calls the callbacks of the timers that have expired, and then processes
+  /// the events that they caused
+  ///
+  /// Without an event loop, the timers are only run when this is called. It
+  /// returns the number of callbacks that were called.
+  pub fn process_timers(&mut self) -> usize {
+
+    let count = self.run_timers();
+    self.process_events();
+
+    count
+  }
+
+  /// Processes events until the background tasks have finished, which calls
+  /// their completion callbacks
+  ///
+  /// Returns false if some of the tasks are still running after timeout.
+  pub fn wait_for_tasks(&mut self, timeout: Duration) -> bool {
+
+    let deadline = Instant::now() + timeout;
+    loop {
+
+      self.process_events();
+      if self.event_loop_proxy.get_tasks().borrow().get_count() == 0 {
+        return true;
+      }
+      if Instant::now() >= deadline {
+        return false;
+      }
+
+      thread::sleep(Duration::from_millis(1));
+    }
+  }
+
+  /// Moves the mouse cursor and clicks a button
+  ///
+  /// x and y are relative to the main window
+  pub fn send_click(&mut self, button: MouseButton, x: f64, y: f64) {
+    self.send_cursor_moved(x, y);
+    self.send_mouse_input(ElementState::Pressed, button);
+    self.send_mouse_input(ElementState::Released, button);
+  }
+
+  /// Moves the mouse cursor
+  ///
+  /// x and y are relative to the main window
+  pub fn send_cursor_moved(&mut self, x: f64, y: f64) {
+    self.process_cursor_moved(x, y);
+    self.process_events();
+  }
+
+  /// Presses and releases a key
+  pub fn send_key(&mut self, key: Key, text: Option<&str>) {
+    self.send_keyboard_input(KeyboardEvent::pressed(key.clone(), text));
+    self.send_keyboard_input(KeyboardEvent::released(key));
+  }
+
+  /// Sends a key press or release
+  pub fn send_keyboard_input(&mut self, event: KeyboardEvent) {
+    self.process_keyboard_input(event);
+    self.process_events();
+  }
+
+  /// Sets the modifier keys that are currently pressed, such as Shift
+  pub fn send_modifiers(&mut self, modifiers: ModifiersState) {
+    self.modifiers = modifiers;
+  }
+
+  /// Presses or releases a mouse button at the cursor's current location
+  pub fn send_mouse_input(&mut self, state: ElementState, button: MouseButton) {
+    self.process_mouse_input(state, button);
+    self.process_events();
+  }
+
+  /// Turns the mouse wheel at the cursor's current location
+  pub fn send_mouse_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
+    self.process_mouse_wheel(delta, phase);
+    self.process_events();
+  }
+
+  /// Changes the size of the main window
+  ///
+  /// width and height are in logical units
+  pub fn send_resized(&mut self, width: f64, height: f64) {
+    self.process_resized(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32);
+    self.process_events();
+  }
+
+  /// Types text, one character at a time
+  pub fn send_text(&mut self, text: &str) {
+
+    for character in text.chars() {
+      let text = character.to_string();
+      self.send_key(Key::Character(text.clone().into()), Some(&text));
+    }
+  }
+}
@@ -1,12 +1,11 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     MouseScrollDelta::{LineDelta, PixelDelta},
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -34,8 +33,9 @@ use std::{
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, LayoutType};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, LayoutType};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
@@ -62,7 +62,7 @@ pub struct ImageView {
 impl ImageView {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     display_type: DisplayType,
   ) -> Self {
@@ -134,13 +134,15 @@ impl ImageView {
 
     let mut pixmap = self.window_base.get_pixmap();
 
-    // If the window's size has changed, create a new pixmap
+    // If the window's size has changed, create a new pixmap. The image is
+    // measured in logical units, and the pixmap in physical pixels.
     let (drawing_width, drawing_height) = self.window_base.get_drawing_size();
-    if drawing_width != pixmap.width() as f64 || drawing_height != pixmap.height() as f64 {
-      pixmap = match Pixmap::new(drawing_width as u32, drawing_height as u32) {
+    let (pixmap_width, pixmap_height) = self.window_base.get_physical_drawing_size();
+    if pixmap_width != pixmap.width() as f64 || pixmap_height != pixmap.height() as f64 {
+      pixmap = match Pixmap::new(pixmap_width as u32, pixmap_height as u32) {
         Some(pixmap) => pixmap,
         None => {
-          println!("Cannot create a pixmap of size {drawing_width} x {drawing_height}");
+          println!("Cannot create a pixmap of size {pixmap_width} x {pixmap_height}");
           Pixmap::new(1, 1).unwrap()
         },
       };
@@ -159,7 +161,7 @@ impl ImageView {
       DisplayType::Actual => {
 
         // Copy the visible portion of the image into the Pixmap
-        self.copy_image_section(x, y, drawing_width, drawing_height)
+        WindowUtils::to_physical_pixmap(&self.copy_image_section(x, y, drawing_width, drawing_height))
       },
 
       DisplayType::ScaleLarger => {
@@ -173,8 +175,8 @@ impl ImageView {
           match self.scale_by_size(
                 x as f32,
                 y as f32,
-                drawing_width as u32,
-                drawing_height as u32
+                pixmap_width as u32,
+                pixmap_height as u32
           ) {
 
             Some(pixmap) => pixmap,
@@ -185,7 +187,7 @@ impl ImageView {
             },
           }
         } else {
-          self.copy_image_section(x, y, drawing_width, drawing_height)
+          WindowUtils::to_physical_pixmap(&self.copy_image_section(x, y, drawing_width, drawing_height))
         }
       },
 
@@ -200,8 +202,8 @@ impl ImageView {
           match self.scale_by_size(
                 x as f32,
                 y as f32,
-                drawing_width as u32,
-                drawing_height as u32
+                pixmap_width as u32,
+                pixmap_height as u32
           ) {
 
             Some(pixmap) => pixmap,
@@ -212,7 +214,7 @@ impl ImageView {
             },
           }
         } else {
-          self.copy_image_section(x, y, drawing_width, drawing_height)
+          WindowUtils::to_physical_pixmap(&self.copy_image_section(x, y, drawing_width, drawing_height))
         }
       },
 
@@ -222,8 +224,8 @@ impl ImageView {
         match self.scale_by_size(
               x as f32,
               y as f32,
-              drawing_width as u32,
-              drawing_height as u32
+              pixmap_width as u32,
+              pixmap_height as u32
         ) {
 
           Some(pixmap) => pixmap,
@@ -598,6 +600,13 @@ impl ChildWindow for ImageView {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -612,10 +621,10 @@ impl ChildWindow for ImageView {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -633,6 +642,20 @@ impl ChildWindow for ImageView {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -701,6 +724,10 @@ impl ChildWindow for ImageView {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -782,13 +809,16 @@ impl ChildWindow for ImageView {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-        mouse_x: f64, mouse_y: f64) {
+        mouse_x: f64, mouse_y: f64, _modifiers: ModifiersState) {
 
     // If the user clicked the left button, call the callback. If the user clicked
     // the right button, display the tooltip, if there is one.
@@ -808,11 +838,15 @@ impl ChildWindow for ImageView {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _x: f64, _y: f64) {
@@ -884,7 +918,7 @@ impl ChildWindow for ImageView {
     self.window_base.set_location(x, y);
 
     // Has the size changed?
-    if force || width != self.window_base.get_width() || height != self.window_base.get_height() {
+    if force || self.window_base.is_theme_changed() || width != self.window_base.get_width() || height != self.window_base.get_height() {
 
       // Save the new size
       self.window_base.set_size(width, height);
@@ -1,11 +1,10 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -14,40 +13,47 @@ use tiny_skia::{
   Pixmap,
 };
 
+use unicode_segmentation::UnicodeSegmentation;
+
 use uuid::Uuid;
 
 use std::{
   cell::RefCell,
   fmt::Debug,
+  ops::Range,
   rc::Rc,
 };
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::ContextMenuItem;
+use crate::child_window::{Alignment, ContextMenuItem, EditAction, KeyboardEvent};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::rich_text::RichText;
 use crate::text_font::TextFont;
-use crate::UserEvent;
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
 
-// Used for internal margins
-const INTERNAL_MARGIN: f64 = 4.0;
-const DEFAULT_FONT_SIZE: f32 = 14.0;
-
-/// A child window which contains a non-editable, single line of text
+/// A child window which contains non-editable text
+///
+/// The text is broken into lines at newlines. When word wrapping is on, it is
+/// also broken between words to fit the width that the layout gives the label.
+/// The text can be rich text, whose runs have their own fonts and colors.
 pub struct Label {
   window_base: WindowBase,
   font: Option<TextFont>,
   text_color: Color,
   bg_color: Color,
   internal_padding: f64,
+  alignment: Alignment,
+  word_wrap: bool,
+  rich_text: Option<RichText>,
 }
 
 impl Label {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     text: String,
     text_color: Color,
@@ -56,9 +62,10 @@ impl Label {
 
     // Load the font
     let (text_width, text_height);
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", DEFAULT_FONT_SIZE) {
+    let font = match TextFont::new_default() {
       Ok(font) => {
-        let (bound_width, bound_height) = font.get_bounds(text.as_str(), None);
+        let lines = Label::split_lines(&text).into_iter().map(|line| RichText::from(&text[line])).collect::<Vec<_>>();
+        let (bound_width, bound_height) = Label::get_text_size(&font, &lines);
         text_width = bound_width;
         text_height = bound_height;
         Some(font)
@@ -71,8 +78,8 @@ impl Label {
     };
 
     // Calculate the size of the window
-    let width: f64 = text_width as f64 + 2.0;
-    let height: f64 = text_height as f64 + 4.0;
+    let width: f64 = WindowUtils::to_logical(text_width as f64) + 2.0;
+    let height: f64 = WindowUtils::to_logical(text_height as f64) + 4.0;
 
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("Label".to_string());
@@ -82,13 +89,18 @@ impl Label {
     window_base.set_max_size(width, height);
     window_base.set_min_size(width, height);
 
+    let internal_padding = window_base.get_theme().metrics.padding;
+
     // Create the instance
     let mut inst = Self {
       window_base: window_base,
       font: font,
       text_color: text_color,
       bg_color: bg_color,
-      internal_padding: INTERNAL_MARGIN,
+      internal_padding,
+      alignment: Alignment::CENTER,
+      word_wrap: false,
+      rich_text: None,
     };
 
     inst.draw();
@@ -98,7 +110,7 @@ impl Label {
 
   fn draw(&mut self) {
 
-    let (width, height) = self.window_base.get_drawing_size();
+    let (width, height) = self.window_base.get_physical_drawing_size();
 
     // Create the pixmap into which we will draw
     let mut pixmap = Pixmap::new(width as u32, height as u32).unwrap();
@@ -110,24 +122,34 @@ impl Label {
     match &self.font {
       Some(font) => {
 
-        match self.window_base.get_text() {
+        match self.get_content() {
 
           Some(text) => {
 
-            let (bounds_width, bounds_height) = font.get_bounds(&text, None);
-
-            // Center the text
-            font.draw_text(
-              text.as_str(),
-              &mut pixmap,
-              ((width - bounds_width as f64) / 2.0) as i32,
-              ((height - bounds_height as f64) / 2.0) as i32,
-              self.text_color,
-              self.bg_color,
-              -1,
-              Color::BLACK,
-              None
-            );
+            // Center the lines vertically
+            let lines = self.get_lines(font, &text, width);
+            let (_text_width, text_height) = Label::get_text_size(font, &lines);
+            let mut y = ((height - text_height as f64) / 2.0) as i32;
+
+            for line in lines {
+
+              let (bounds_width, _bounds_height) = font.get_rich_bounds(&line);
+              let x = match self.alignment {
+                Alignment::LEFT => 1.0,
+                Alignment::RIGHT => width - bounds_width as f64 - 1.0,
+                _ => (width - bounds_width as f64) / 2.0,
+              };
+
+              let (_line_width, line_height) = font.draw_rich_text(
+                &line,
+                &mut pixmap,
+                x as i32,
+                y,
+                self.text_color,
+                self.bg_color,
+              );
+              y += line_height as i32;
+            }
           },
 
           None => {},
@@ -138,6 +160,158 @@ impl Label {
 
     self.window_base.set_pixmap(pixmap)
   }
+
+  // Draws the window and requests that it be redrawn within the main window
+  fn draw_and_request_redraw(&mut self) {
+
+    self.draw();
+
+    let (x, y) = self.window_base.get_location();
+    WindowUtils::request_redraw(
+          self.window_base.get_event_loop().clone(),
+          self.window_base.get_main_win_uuid(),
+          x,
+          y,
+          self.window_base.get_pixmap()
+    );
+  }
+
+  /// Gets how the lines of text are aligned
+  pub fn get_alignment(&self) -> Alignment {
+    self.alignment
+  }
+
+  // Gets the text that is drawn, which is the rich text if there is any
+  fn get_content(&self) -> Option<RichText> {
+
+    match &self.rich_text {
+      Some(rich_text) => Some(rich_text.clone()),
+      None => self.window_base.get_text().map(|text| RichText::from(text.as_str())),
+    }
+  }
+
+  // Gets the lines of text that are drawn when the label is width physical pixels wide
+  //
+  // The lines are wrapped using the label's font.
+  fn get_lines(&self, font: &TextFont, text: &RichText, width: f64) -> Vec<RichText> {
+
+    let plain_text = text.get_text();
+    let ranges = if self.word_wrap {
+      font.get_wrap_ranges(&plain_text, (width - 2.0).max(1.0) as u32)
+    } else {
+      Label::split_lines(&plain_text)
+    };
+
+    ranges.into_iter().map(|range| text.slice(range)).collect()
+  }
+
+  /// Gets the rich text, if the label's text was set with set_rich_text()
+  pub fn get_rich_text(&self) -> Option<RichText> {
+    self.rich_text.clone()
+  }
+
+  // Gets the size of the widest line and the total height of the lines
+  fn get_text_size(font: &TextFont, lines: &[RichText]) -> (u32, u32) {
+
+    lines.iter()
+      .map(|line| font.get_rich_bounds(line))
+      .fold((0, 0), |(width, height), (line_width, line_height)| (width.max(line_width), height + line_height))
+  }
+
+  /// Returns true if the text is wrapped to fit the label's width
+  pub fn get_word_wrap(&self) -> bool {
+    self.word_wrap
+  }
+
+  /// Sets how the lines of text are aligned
+  ///
+  /// Alignment::LEFT, CENTER, and RIGHT align each line horizontally. The
+  /// text is always centered vertically, so TOP and BOTTOM are the same as CENTER.
+  pub fn set_alignment(&mut self, alignment: Alignment) {
+    self.alignment = alignment;
+    self.draw_and_request_redraw();
+  }
+
+  /// Sets the colors of the text and the background
+  pub fn set_colors(&mut self, text_color: Color, bg_color: Color) {
+    self.text_color = text_color;
+    self.bg_color = bg_color;
+    self.draw_and_request_redraw();
+  }
+
+  /// Sets text whose runs have their own fonts, colors, and decorations
+  ///
+  /// get_text() returns the text without its styles. Calling set_text()
+  /// replaces the rich text with plain text.
+  pub fn set_rich_text(&mut self, text: RichText) {
+
+    self.window_base.set_text(text.get_text());
+    self.rich_text = Some(text);
+
+    self.update_size();
+    self.draw_and_request_redraw();
+  }
+
+  /// Turns word wrapping on or off
+  ///
+  /// When it is on, the text is broken between words to fit the width that the
+  /// label is given, and get_height_for_width() gives the height it needs.
+  /// The label can be made as narrow as its longest word. When it is off, the
+  /// label's size is the size of its text.
+  pub fn set_word_wrap(&mut self, word_wrap: bool) {
+    self.word_wrap = word_wrap;
+    self.update_size();
+    self.draw_and_request_redraw();
+  }
+
+  // Splits text into lines at its newlines, returning the byte range of each line
+  fn split_lines(text: &str) -> Vec<Range<usize>> {
+
+    let mut start = 0;
+    text.split('\n').map(|line| {
+      let range = start..start + line.trim_end_matches('\r').len();
+      start += line.len() + 1;
+      range
+    }).collect()
+  }
+
+  // Sets the size of the window to fit the text
+  fn update_size(&mut self) {
+
+    let font = match &self.font {
+      Some(font) => font,
+      None => return,
+    };
+    let text = self.get_content().unwrap_or_default();
+
+    if self.word_wrap {
+
+      // Keep the width, which is set by the layout, and fit the height to it
+      let plain_text = text.get_text();
+      let longest_word = plain_text.split_word_bound_indices()
+        .filter(|(_start, word)| !word.trim().is_empty())
+        .map(|(start, word)| font.get_rich_bounds(&text.slice(start..start + word.len())).0)
+        .max()
+        .unwrap_or(0);
+      let (_line_width, line_height) = font.get_bounds("Wy", None);
+      let line_height = WindowUtils::to_logical(line_height as f64);
+      let height = self.get_height_for_width(self.window_base.get_width()).unwrap_or(line_height + 4.0);
+
+      self.window_base.set_height(height);
+      self.window_base.set_min_size(WindowUtils::to_logical(longest_word as f64) + 2.0, line_height + 4.0);
+      self.window_base.set_max_size(f64::MAX, f64::MAX);
+    } else {
+
+      let (text_width, text_height) = Label::get_text_size(font, &self.get_lines(font, &text, 0.0));
+      let width: f64 = WindowUtils::to_logical(text_width as f64) + 2.0;
+      let height: f64 = WindowUtils::to_logical(text_height as f64) + 4.0;
+
+      self.window_base.set_width(width);
+      self.window_base.set_height(height);
+      self.window_base.set_max_size(width, height);
+      self.window_base.set_min_size(width, height);
+    }
+  }
 }
 
 impl Debug for Label {
@@ -172,6 +346,13 @@ impl ChildWindow for Label {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -186,10 +367,10 @@ impl ChildWindow for Label {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -207,6 +388,20 @@ impl ChildWindow for Label {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -243,6 +438,10 @@ impl ChildWindow for Label {
 
   fn get_max_size(&self) -> Option<(f64, f64)> {
 
+    if self.word_wrap {
+      return self.window_base.get_max_size();
+    }
+
     match self.window_base.get_max_size() {
 
       Some((width, mut height)) => {
@@ -251,15 +450,15 @@ impl ChildWindow for Label {
 
           Some(font) => {
 
-            match self.window_base.get_text() {
+            match self.get_content() {
 
               Some(text) => {
 
                 // Find the size of the text
-                let (_, text_height) = font.get_bounds(text.as_str(), None);
+                let (_, text_height) = Label::get_text_size(font, &self.get_lines(font, &text, 0.0));
 
                 // Add internal padding
-                text_height as f64 + self.internal_padding
+                WindowUtils::to_logical(text_height as f64) + self.internal_padding
               },
 
               None => 1.0,
@@ -282,6 +481,23 @@ impl ChildWindow for Label {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, width: f64) -> Option<f64> {
+
+    if !self.word_wrap {
+      return None;
+    }
+
+    match &self.font {
+      Some(font) => {
+        let text = self.get_content().unwrap_or_default();
+        let lines = self.get_lines(font, &text, WindowUtils::to_physical(width));
+        let (_text_width, text_height) = Label::get_text_size(font, &lines);
+        Some(WindowUtils::to_logical(text_height as f64) + 4.0)
+      },
+      None => None,
+    }
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -332,57 +548,39 @@ impl ChildWindow for Label {
   }
   fn set_text(&mut self, text: String) {
 
-    self.window_base.set_text(text.clone());
+    self.window_base.set_text(text);
+    self.rich_text = None;
 
     // Adjust the size to accomodate the new text
-    match &self.font {
-
-      Some(font) => {
-
-        let (bound_width, bound_height) = font.get_bounds(text.as_str(), None);
-
-        let width: f64 = bound_width as f64 + 2.0;
-        let height: f64 = bound_height as f64 + 4.0;
-
-        self.set_width(width);
-        self.set_height(height);
-        self.set_max_size(width, height);
-        self.set_min_size(width, height);
-      },
-
-      None => {},
-    }
+    self.update_size();
 
-    self.draw();
+    self.draw_and_request_redraw();
+  }
 
-    // Request a redraw
-    let (x, y) = self.window_base.get_location();
-    WindowUtils::request_redraw(
-          self.window_base.get_event_loop().clone(),
-          self.window_base.get_main_win_uuid(),
-          x,
-          y,
-          self.window_base.get_pixmap()
-    );
+  fn handle_edit_action(&mut self, _action: EditAction) {
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, _button: MouseButton,
-        _mouse_x: f64, _mouse_y: f64) {
+        _mouse_x: f64, _mouse_y: f64, _modifiers: ModifiersState) {
   }
   fn handle_mouse_released(&mut self, _button: MouseButton,
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _x: f64, _y: f64) {
@@ -402,7 +600,7 @@ impl ChildWindow for Label {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || height != win_height {
 
       // Save the size
       self.window_base.set_size(width, height);
@@ -1,8 +1,7 @@
-use crate::UserEvent;
+use crate::event_proxy::EventProxy;
+use crate::theme::Theme;
 use crate::WindowUtils;
 
-use winit::event_loop::EventLoopProxy;
-
 use tiny_skia::Pixmap;
 
 use uuid::Uuid;
@@ -15,7 +14,7 @@ use std:: {
 #[derive(Clone, Debug)]
 pub struct LayoutBase {
     uuid: Uuid,                                 // layout's unique ID
-    event_loop: Rc<EventLoopProxy<UserEvent>>,  // main window's event loop
+    event_loop: Rc<EventProxy>,                 // main window's event loop
     main_win_uuid: Uuid,                        // ID of the outermost parent window
     pixmap: Pixmap,                             // layout's contents
     main_win_x: f64,                            // layout's location within the main window
@@ -23,11 +22,14 @@ pub struct LayoutBase {
     width: f64,                                 // size of the layout
     height: f64,
     name: String,                               // only used in Debug
+    laid_out_area: Option<(f64, f64, f64, f64)>, // location and size that the pixmap was laid out for
+    theme_generation: u64,                      // generation of the theme that the pixmap was drawn with
+    dirty: bool,                                // whether the layout has changed since it was laid out
 }
 
 impl LayoutBase {
 
-  pub fn new(event_loop: Rc<EventLoopProxy<UserEvent>>, main_win_uuid: Uuid) -> Self {
+  pub fn new(event_loop: Rc<EventProxy>, main_win_uuid: Uuid) -> Self {
 
     // Set all of the default values
     Self {
@@ -40,6 +42,9 @@ impl LayoutBase {
       width: 1.0,
       height: 1.0,
       name: "<unspecified>".to_string(),
+      laid_out_area: None,
+      theme_generation: Theme::get_generation(),
+      dirty: true,
     }
   }
 
@@ -47,13 +52,14 @@ impl LayoutBase {
     self.uuid
   }
   pub fn set_uuid(&mut self, uuid: Uuid) {
+    self.event_loop.get_registry().borrow_mut().set_uuid(self.uuid, uuid);
     self.uuid = uuid;
   }
 
-  pub fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  pub fn get_event_loop(&self) -> Rc<EventProxy> {
     self.event_loop.clone()
   }
-  pub fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  pub fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.event_loop = event_loop;
   }
 
@@ -64,6 +70,11 @@ impl LayoutBase {
     self.main_win_uuid = main_win_uuid;
   }
 
+  /// Gets the theme that the layout is drawn with
+  pub fn get_theme(&self) -> Rc<Theme> {
+    Theme::current()
+  }
+
   pub fn get_pixmap(&self) -> Pixmap {
     self.pixmap.clone()
   }
@@ -71,6 +82,32 @@ impl LayoutBase {
     self.pixmap = pixmap;
   }
 
+  /// Whether the layout must be drawn again, because it or the theme has changed
+  ///
+  /// The layout's children are checked separately, by Layout::is_dirty().
+  pub fn is_dirty(&self) -> bool {
+    self.dirty || self.theme_generation != Theme::get_generation()
+  }
+  pub fn set_dirty(&mut self, dirty: bool) {
+    self.dirty = dirty;
+  }
+
+  /// Whether the pixmap was laid out for the specified location and size
+  pub fn is_laid_out_at(&self, main_win_x: f64, main_win_y: f64, width: f64, height: f64) -> bool {
+    self.laid_out_area == Some((main_win_x, main_win_y, width, height))
+  }
+
+  /// Saves the pixmap that the layout was drawn into, and the area that it was laid out for
+  ///
+  /// Until the layout or one of its children changes, the pixmap is reused
+  /// when the layout is laid out again in the same area.
+  pub fn set_laid_out(&mut self, main_win_x: f64, main_win_y: f64, width: f64, height: f64, pixmap: Pixmap) {
+    self.pixmap = pixmap;
+    self.laid_out_area = Some((main_win_x, main_win_y, width, height));
+    self.theme_generation = Theme::get_generation();
+    self.dirty = false;
+  }
+
   pub fn get_main_win_x(&self) -> f64 {
     self.main_win_x
   }
@@ -116,7 +153,7 @@ impl LayoutBase {
 
   pub fn layout(&mut self, _main_win_x: f64, _main_win_y: f64, width: f64, height: f64) -> Pixmap {
 
-    let pixmap = match Pixmap::new(width as u32, height as u32) {
+    let pixmap = match Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32) {
 
       Some(pixmap) => pixmap,
 
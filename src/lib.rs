@@ -5,42 +5,44 @@ use winit::{
   dpi::{
     LogicalPosition,
     LogicalSize,
-    PhysicalSize,
     Position,
   },
   event::{
     ElementState,
-    KeyEvent,
     MouseButton,
+    MouseScrollDelta,
+    TouchPhase,
     WindowEvent,
   },
   event_loop::{
     ActiveEventLoop,
     ControlFlow,
     EventLoop,
-    EventLoopProxy,
   },
+  keyboard::ModifiersState,
   monitor::MonitorHandle,
   window::{ Window, WindowAttributes, WindowId },
 };
-use softbuffer::{ Context, Surface };
-use tiny_skia::{ Color, Pixmap, PixmapPaint, Transform };
+use tiny_skia::Pixmap;
 
 use std::{
+  any::{Any, TypeId},
   cell::RefCell,
   collections::HashMap,
-  num::NonZeroU32,
+  future::Future,
   rc::Rc,
   time::{Duration, Instant},
 };
 
 use uuid::Uuid;
 
+pub mod app_event;
 pub mod child_window;
 use crate::child_window::{
   BorderLocation,
   ChildType,
   ChildWindow,
+  KeyboardEvent,
   Layout,
   LayoutArgs,
   LayoutType,
@@ -48,7 +50,11 @@ use crate::child_window::{
 };
 pub mod border_layout;
 pub mod button;
+pub mod clipboard;
 pub mod context_menu;
+pub mod event_proxy;
+pub mod font_registry;
+pub mod headless;
 pub mod image_view;
 pub mod label;
 pub mod layout_base;
@@ -57,27 +63,51 @@ pub mod list;
 pub mod menubar;
 pub mod multi_line_edit;
 pub mod popup;
+pub mod registry;
+pub mod rich_text;
 pub mod row_layout;
 pub mod scroll_bar;
 pub mod scroll_layout;
+pub mod shortcut;
+pub mod signal;
 pub mod slider;
+pub mod snapshot;
 pub mod status_bar;
 pub mod tab_layout;
 pub mod table;
+pub mod task;
 pub mod text_font;
+pub mod theme;
+pub mod timer;
 pub mod tooltip;
+pub mod ui_handle;
+pub mod undo;
 pub mod window_base;
+pub mod window_surface;
 pub mod window_utils;
+use crate::app_event::{AppEvent, AppEventHandler};
 use crate::border_layout::BorderLayout;
 use crate::child_window::Orientation;
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::menubar::MenuBar;
 use crate::popup::PopUp;
+use crate::registry::WindowRef;
+use crate::shortcut::{Shortcut, ShortcutAction};
 use crate::status_bar::StatusBar;
+use crate::task::TaskId;
+use crate::text_font::TextFont;
+use crate::theme::Theme;
+use crate::timer::TimerId;
 use crate::tooltip::ToolTip;
+use crate::ui_handle::UiHandle;
+use crate::window_surface::{Damage, WindowSurface};
 use crate::window_utils::WindowUtils;
 
-const DOUBLE_CLICK_TIME: u64 = 500;
+const DOUBLE_CLICK_TIME: u64 = 500;      // milliseconds
+const DOUBLE_CLICK_DISTANCE: f64 = 4.0;  // maximum mouse movement between the clicks
+const DEFAULT_REFRESH_RATE: u32 = 60_000;  // millihertz, used when the monitor's refresh rate is unknown
+const TOOLTIP_TIMEOUT: u64 = 5000;       // milliseconds that a tooltip is displayed
 
 pub enum MainAppSize {
   Actual(f64, f64, f64, f64),   // Actual location and size (x, y, width, height)
@@ -88,25 +118,31 @@ pub enum MainAppSize {
 pub struct MainApp {
   id: Uuid,
   window_id: Option<WindowId>,
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+  event_loop_proxy: Rc<EventProxy>,
   startup_callback: Box<dyn Fn()>,
   monitor: Option<MonitorHandle>,
   location_and_size: MainAppSize,
   title: String,
-  window: Option<Window>,
+  window: Option<Rc<Window>>,
+  surface: Option<WindowSurface>,  // created when the window is first displayed
   layout: Box<dyn Layout>,
   menubar: Option<Rc<RefCell<MenuBar>>>,
   statusbar: Option<Rc<RefCell<StatusBar>>>,
   focus_window: Option<Rc<RefCell<dyn ChildWindow>>>,
+  modifiers: ModifiersState,      // Modifier keys that are currently pressed
   modal_window: Option<Rc<RefCell<PopUp>>>,
   context_menu: Option<Rc<RefCell<ContextMenu>>>,   // there is only one context menu for the entire application
   context_menu_id: WindowId,
   tooltip_popup: Option<Rc<RefCell<ToolTip>>>,    // there is only one tooltip pop-up for the entire application
   tooltip_popup_id: WindowId,
+  tooltip_timer: Option<TimerId>, // hides the tooltip when it expires
   cursor_x: f64,                  // Current mouse location within MainApp
   cursor_y: f64,
   mouse_left_button_down: bool,
   pixmap: Pixmap,
+  damage: Damage,                 // regions of the pixmap that haven't been displayed
+  frame_interval: Duration,       // shortest time between two frames, which is the monitor's refresh period
+  last_frame: Option<Instant>,    // when the pixmap was last displayed
   x: f64,
   y: f64,
   width: f64,
@@ -116,8 +152,9 @@ pub struct MainApp {
   drag_start_win_y: f64,
   popups: HashMap<WindowId, Rc<RefCell<PopUp>>>,
   log_unhandled_events: bool,
+  shortcuts: Vec<(Shortcut, ShortcutAction)>,     // application-wide keyboard shortcuts
   initial_draw_performed: bool,
-  last_mouse_left_click: Instant,
+  last_mouse_left_click: Option<(Instant, f64, f64)>,   // time and location of the previous left click
   caret_moved_event_callback: Option<Box<dyn Fn(Uuid, usize, usize)>>,
   close_tab_event_callback: Option<Box<dyn Fn(Uuid, Uuid)>>,
   create_context_menu_event_callback: Option<Box<dyn Fn(Uuid, f64, f64)>>,
@@ -127,10 +164,12 @@ pub struct MainApp {
   redraw_all_event_callback: Option<Box<dyn Fn()>>,
   selection_changed_event_callback: Option<Box<dyn Fn(Uuid)>>,
   set_list_event_callback: Option<Box<dyn Fn(Uuid, Vec<String>)>>,
-  slider_value_changed_event_callback: Option<Box<dyn Fn(f64)>>,
+  slider_value_changed_event_callback: Option<Box<dyn Fn(Uuid, f64)>>,
   user_defined_event_callback: Option<Box<dyn Fn(u64, Vec<String>)>>,
+  app_event_handlers: HashMap<TypeId, Vec<AppEventHandler>>,  // handlers for each type of App event data
   window_created_callback: Option<Box<dyn Fn(Rc<RefCell<PopUp>>)>>,
   end_program_handler: Option<Box<dyn Fn() -> bool>>,
+  theme: Rc<Theme>,
 }
 
 impl MainApp {
@@ -142,7 +181,7 @@ impl MainApp {
     id: Uuid,
     title: &str,
     location_and_size: MainAppSize,
-    event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+    event_loop_proxy: Rc<EventProxy>,
     startup_callback: F,
   ) -> RefCell<MainApp> {
 
@@ -153,6 +192,10 @@ impl MainApp {
     // Ignore the drawing events from the children until the event loop is started
     WindowUtils::set_ignore_events(true);
 
+    // Windows are drawn with the light theme until set_theme() is called
+    let theme = Rc::new(Theme::light());
+    Theme::set_current(theme.clone());
+
     let inst = MainApp {
       id: id,
       event_loop_proxy: event_loop_proxy,
@@ -162,19 +205,25 @@ impl MainApp {
       location_and_size: location_and_size,
       title: title.to_string(),
       window: None,
+      surface: None,
       layout: layout,
       menubar: None,
       statusbar: None,
       focus_window: None,
+      modifiers: ModifiersState::empty(),
       modal_window: None,
       context_menu: None,   // This will be created when the window is created
       context_menu_id: WindowId::dummy(),
       tooltip_popup: None,  // This will be created when the window is created
       tooltip_popup_id: WindowId::dummy(),
+      tooltip_timer: None,
       cursor_x: 0.0,
       cursor_y: 0.0,
       mouse_left_button_down: false,
       pixmap: Pixmap::new(800, 600).unwrap(),
+      damage: Damage::default(),
+      frame_interval: Duration::from_secs_f64(1000.0 / DEFAULT_REFRESH_RATE as f64),
+      last_frame: None,
       x: 0.0,
       y: 0.0,
       width: 800.0,
@@ -184,8 +233,9 @@ impl MainApp {
       drag_start_win_y: 0.0,
       popups: HashMap::new(),
       log_unhandled_events: true,
+      shortcuts: Vec::new(),
       initial_draw_performed: false,
-      last_mouse_left_click: Instant::now(),
+      last_mouse_left_click: None,
       caret_moved_event_callback: None,
       close_tab_event_callback: None,
       create_context_menu_event_callback: None,
@@ -197,13 +247,27 @@ impl MainApp {
       set_list_event_callback: None,
       slider_value_changed_event_callback: None,
       user_defined_event_callback: None,
+      app_event_handlers: HashMap::new(),
       window_created_callback: None,
       end_program_handler: None,
+      theme,
     };
 
     RefCell::new(inst)
   }
 
+  /// Adds a handler for the App events whose data is of type T
+  ///
+  /// Any number of handlers can be added for each type, and they are called in
+  /// the order they were added.
+  pub fn add_app_event_handler<T: Any, F: Fn(&T) + 'static>(&mut self, handler: F) {
+    self.app_event_handlers.entry(TypeId::of::<T>()).or_default().push(Box::new(move |event: &AppEvent| {
+      if let Some(data) = event.downcast_ref::<T>() {
+        handler(data);
+      }
+    }));
+  }
+
   /// Adds an item to the menubar
   pub fn add_menu_item<F: Fn() + 'static>(&self, label: String, callback: F) {
 
@@ -216,6 +280,109 @@ impl MainApp {
     };
   }
 
+  /// Adds an item to the menu bar, along with a keyboard shortcut that performs the same action
+  ///
+  /// The item displays the shortcut's accelerator text, such as "Save (Ctrl+S)".
+  pub fn add_menu_item_with_shortcut<F: Fn() + 'static>(
+        &mut self,
+        label: String,
+        shortcut: Shortcut,
+        callback: F
+  ) {
+
+    let callback = Rc::new(callback);
+
+    match &self.menubar {
+      Some(menubar) => {
+        let mut menubar_ref = menubar.borrow_mut();
+        let callback_clone = callback.clone();
+        menubar_ref.add_item_with_shortcut(label, &shortcut, move || callback_clone());
+      },
+      None => println!("Menu bar has not been enabled"),
+    };
+
+    self.add_shortcut(shortcut, ShortcutAction::Callback(Box::new(move || callback())));
+  }
+
+  /// Registers an application-wide keyboard shortcut
+  ///
+  /// Shortcuts are checked before key presses are passed to the window with focus.
+  /// If the shortcut is already registered, its action is replaced.
+  pub fn add_shortcut(&mut self, shortcut: Shortcut, action: ShortcutAction) {
+
+    self.remove_shortcut(&shortcut);
+    self.shortcuts.push((shortcut, action));
+  }
+
+  /// Adds a timer, whose callback is called on the UI thread when it expires
+  ///
+  /// The timer expires after interval, and then every interval until it is
+  /// cancelled if repeat is true. The callback is given the MainApp, so that
+  /// it can change the windows or add and cancel timers, including its own.
+  pub fn add_timer<F: FnMut(&mut MainApp) + 'static>(
+        &mut self,
+        interval: Duration,
+        repeat: bool,
+        callback: F
+  ) -> TimerId {
+    self.event_loop_proxy.get_timers().borrow_mut().add(interval, repeat, Box::new(callback))
+  }
+
+  // Calculates the location and size of the main window
+  fn calculate_location_and_size(&mut self) {
+
+    match self.location_and_size {
+      MainAppSize::Actual(actual_x, actual_y, actual_width, actual_height) => {
+        self.x = actual_x;
+        self.y = actual_y;
+        self.width = actual_width;
+        self.height = actual_height;
+      },
+
+      MainAppSize::Relative(percent_x, percent_y, percent_width, percent_height) => {
+
+        // The main window is laid out in logical units
+        let monitor_size: LogicalSize<f64> = match &self.monitor {
+          Some(monitor) => monitor.size().to_logical(monitor.scale_factor()),
+          None => LogicalSize::new(800.0, 600.0),
+        };
+
+        self.x = monitor_size.width * percent_x;
+        self.y = monitor_size.height * percent_y;
+        self.width = monitor_size.width * percent_width;
+        self.height = monitor_size.height * percent_height;
+      },
+    }
+  }
+
+  /// Cancels a task that was started with spawn() or spawn_for()
+  ///
+  /// The task's future is dropped the next time that it is waiting, and its
+  /// completion callback isn't called. Returns false if the task has already
+  /// finished or been cancelled.
+  pub fn cancel_task(&mut self, id: TaskId) -> bool {
+    self.event_loop_proxy.get_tasks().borrow_mut().cancel(id)
+  }
+
+  /// Cancels a timer that was added with add_timer()
+  ///
+  /// Returns false if the timer has already expired without repeating, or
+  /// has already been cancelled.
+  pub fn cancel_timer(&mut self, id: TimerId) -> bool {
+    self.event_loop_proxy.get_timers().borrow_mut().cancel(id)
+  }
+
+  // Gives the input focus to a window, removing it from the window that had it
+  fn change_focus(&mut self, window: Rc<RefCell<dyn ChildWindow>>) {
+
+    if let Some(focus_window) = &self.focus_window {
+      focus_window.borrow_mut().set_focused(false);
+    }
+
+    window.borrow_mut().set_focused(true);
+    self.focus_window = Some(window);
+  }
+
   // Creates a pop-up context menu that is initally hidden.
   fn create_context_menu(
         &mut self,
@@ -361,44 +528,100 @@ impl MainApp {
     }
   }
 
-  // Displays the Pixmap onto the screen
+  // Displays the regions of the Pixmap that have changed onto the screen
   fn display_pixmap(&mut self) {
 
     match &self.window {
 
       Some(window) => {
 
-        // Get the size of the window
-        let width = self.pixmap.width();
-        let height = self.pixmap.height();
-
-        // Create the drawing surface
-        let context = (Context::new(&window)).unwrap();
-        let mut surface = (Surface::new(&context, &window)).unwrap();
-        surface
-            .resize(NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap())
-            .unwrap();
-
-        // Copy the pixmap onto the drawing surface
-        let mut buffer = surface.buffer_mut().unwrap();
-        for index in 0..(width * height) as usize {
-          buffer[index] =
-              (self.pixmap.data()[index * 4 + 2] as u32) |
-              ((self.pixmap.data()[index * 4 + 1] as u32) << 8) |
-              ((self.pixmap.data()[index * 4] as u32) << 16);
+        // Create the drawing surface, which is kept until the window closes
+        if self.surface.is_none() {
+          match WindowSurface::new(window.clone()) {
+            Ok(surface) => self.surface = Some(surface),
+            Err(err) => {
+              println!("In MainApp::display_pixmap(), {err}");
+              return;
+            },
+          }
         }
 
-        buffer.present().unwrap();
+        if let Some(surface) = &mut self.surface {
+          if let Err(err) = surface.present(&self.pixmap, self.damage.take()) {
+            println!("In MainApp::display_pixmap(), {err}");
+          }
+        }
       },
 
       None => {},
     }
   }
 
+  // Draws the next frame, if any of the top-level windows have changed
+  //
+  // The windows that have requested to be redrawn are drawn into the pixmaps,
+  // and then the regions that have changed are displayed. Frames are displayed
+  // at most once per refresh of the monitor; if the previous one was displayed
+  // too recently, this returns when the next one is due.
+  fn draw_frame(&mut self) -> Option<Instant> {
+
+    self.draw_pending_redraws();
+
+    let damaged = !self.damage.is_empty() ||
+          self.popups.values().any(|popup| popup.borrow().is_damaged());
+    if !damaged {
+      return None;
+    }
+
+    let now = Instant::now();
+    if let Some(last_frame) = self.last_frame {
+      let next_frame = last_frame + self.frame_interval;
+      if now < next_frame {
+        return Some(next_frame);
+      }
+    }
+
+    self.display_pixmap();
+    for popup in self.popups.values() {
+      popup.borrow_mut().display_pixmap();
+    }
+
+    self.last_frame = Some(now);
+    None
+  }
+
+  // Draws the windows that have requested to be redrawn since the previous
+  // frame into their top-level windows' pixmaps, returning how many there were
+  fn draw_pending_redraws(&mut self) -> usize {
+
+    let redraws = self.event_loop_proxy.take_redraws();
+    let count = redraws.len();
+    for redraw in redraws {
+      self.process_user_event(None, redraw);
+    }
+
+    count
+  }
+
+  // Passes an event that a window fired to the listeners that are connected to it
+  //
+  // Returns true if one of them listens for the event.
+  fn emit_signal(&self, source: Uuid, event: &UserEvent) -> bool {
+
+    let listeners = self.event_loop_proxy.get_signals().borrow().get_listeners(source);
+
+    let mut handled = false;
+    for listener in listeners {
+      handled |= listener(event);
+    }
+
+    handled
+  }
+
   /// Adds a menu bar to the main window
   pub fn enable_menubar(
         &mut self,
-        event_loop: Rc<EventLoopProxy<UserEvent>>
+        event_loop: Rc<EventProxy>
   ) {
 
     let menubar = MenuBar::new(event_loop, self.id);
@@ -412,14 +635,11 @@ impl MainApp {
   /// Adds a status bar to the main window
   pub fn enable_statusbar(
         &mut self,
-        event_loop: Rc<EventLoopProxy<UserEvent>>
+        event_loop: Rc<EventProxy>
   ) {
 
-    let statusbar = StatusBar::new(
-          event_loop,
-          self.id,
-          Color::from_rgba8(191, 191, 191, 255)
-    );
+    // The background is the theme's control color
+    let statusbar = StatusBar::new(event_loop, self.id, None);
     let statusbar_rc = Rc::new(RefCell::new(statusbar));
     match self.layout.add_child(
           statusbar_rc.clone(),
@@ -430,6 +650,41 @@ impl MainApp {
     }
   }
 
+  /// Finds a window of type T with the specified name, such as a LineEdit
+  ///
+  /// Any window that has been added to a layout can be found, including the
+  /// windows in pop-ups and in tabs that aren't active. If several windows of
+  /// type T have the name, the first one that was added is returned.
+  pub fn find<T: ChildWindow>(&self, name: &str) -> Option<WindowRef<T>> {
+    self.event_loop_proxy.get_registry().borrow().get_windows_with_name(name)
+      .into_iter()
+      .find_map(WindowRef::new)
+  }
+
+  /// Finds the window with the specified ID, if it is of type T
+  pub fn find_by_id<T: ChildWindow>(&self, uuid: Uuid) -> Option<WindowRef<T>> {
+    self.event_loop_proxy.get_registry().borrow().get_window_with_id(uuid)
+      .and_then(WindowRef::new)
+  }
+
+  // Calls a task's completion callback with its output, unless it has been cancelled
+  pub(crate) fn finish_task(&mut self, id: TaskId, output: Box<dyn Any + Send>) {
+
+    let on_complete = self.event_loop_proxy.get_tasks().borrow_mut().take_completion(id);
+    if let Some(on_complete) = on_complete {
+      on_complete(self, output);
+    }
+  }
+
+  // Removes a task whose future panicked, without calling its completion callback
+  pub(crate) fn fail_task(&mut self, id: TaskId, message: String) {
+
+    let on_complete = self.event_loop_proxy.get_tasks().borrow_mut().take_completion(id);
+    if on_complete.is_some() {
+      println!("Task {id:?} failed: {message}");
+    }
+  }
+
   pub fn get_location(&self) -> (f64, f64) {
     (self.x, self.y)
   }
@@ -438,15 +693,20 @@ impl MainApp {
     self.monitor.clone()
   }
 
+  /// Gets the number of physical pixels in each logical unit
+  pub fn get_scale_factor(&self) -> f64 {
+    WindowUtils::get_scale_factor()
+  }
+
   pub fn get_size(&self) -> (f64, f64) {
 
     match &self.window {
 
       Some(window) => {
 
-        // Get the size of the window
+        // Get the size of the window in logical units
         let size = window.inner_size();
-        (size.width as f64, size.height as f64)
+        (WindowUtils::to_logical(size.width as f64), WindowUtils::to_logical(size.height as f64))
       },
 
       None => (self.width, self.height),
@@ -460,6 +720,17 @@ impl MainApp {
     }
   }
 
+  /// Gets the theme that the windows are drawn with
+  pub fn get_theme(&self) -> Rc<Theme> {
+    self.theme.clone()
+  }
+
+  /// Creates a handle that other threads use to send events to the MainApp
+  /// or to have functions called on the UI thread
+  pub fn get_ui_handle(&self) -> UiHandle {
+    self.event_loop_proxy.get_ui_handle()
+  }
+
   pub fn get_uuid(&self) -> Uuid {
     self.id
   }
@@ -468,9 +739,9 @@ impl MainApp {
     self.window_id
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, _button: MouseButton) {
@@ -483,31 +754,57 @@ impl MainApp {
     self.log_unhandled_events = flag;
   }
 
+  // Moves the input focus to the next window in the focus chain, or the
+  // previous one if backwards is true
+  fn move_focus(&mut self, backwards: bool) {
+
+    let window = WindowUtils::get_next_focus_window(
+          self.layout.get_focus_chain(),
+          &self.focus_window,
+          backwards
+    );
+    if let Some(window) = window {
+      self.change_focus(window);
+    }
+  }
+
   pub fn redraw(&mut self) {
 
+    // The windows' pending redraws are included in the new pixmap
+    self.event_loop_proxy.discard_redraws(self.id);
+
     // Get the size of the window
     let (width, height) = self.get_size();
 
     // Fill the pixmap with the background color
-    self.pixmap.fill(Color::WHITE);
+    self.pixmap.fill(self.theme.palette.background);
 
     // Get the layout's pixmap
     let layout_pixmap = self.layout.layout(0.0, 0.0, width as f64, height as f64);
 
-    let paint = PixmapPaint::default();
-
     // Copy the layout's pixmap image onto the full pixmap
-    self.pixmap.draw_pixmap(
-        0,
-        0,
-        layout_pixmap.as_ref(),
-        &paint,
-        Transform::identity(),
-        None,
-    );
+    WindowUtils::draw_child_pixmap(&mut self.pixmap, 0.0, 0.0, &layout_pixmap);
+    self.damage.add_pixmap(&self.pixmap);
+  }
 
-    // Display the pixmap
-    self.display_pixmap();
+  /// Unregisters an application-wide keyboard shortcut
+  pub fn remove_shortcut(&mut self, shortcut: &Shortcut) {
+    self.shortcuts.retain(|(registered, _action)| registered != shortcut);
+  }
+
+  // Calls the callbacks of the timers that have expired, returning how many there were
+  fn run_timers(&mut self) -> usize {
+
+    let now = Instant::now();
+    let expired = self.event_loop_proxy.get_timers().borrow_mut().take_expired(now);
+    let count = expired.len();
+
+    for (id, mut callback) in expired {
+      callback(self);
+      self.event_loop_proxy.get_timers().borrow_mut().restore(id, callback, now);
+    }
+
+    count
   }
 
   pub fn run_event_loop(&mut self, event_loop: EventLoop<UserEvent>) {
@@ -581,6 +878,20 @@ impl MainApp {
     self.end_program_handler = Some(handler);
   }
 
+  /// Gives the input focus to the window with the specified ID
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  pub fn set_focus(&mut self, uuid: Uuid) -> Result<(), String> {
+
+    match self.layout.get_child_with_id(uuid) {
+      Some(window) => {
+        self.change_focus(window);
+        Ok(())
+      },
+      None => Err(format!("There is no window with the ID {uuid}")),
+    }
+  }
+
   pub fn set_modal(&mut self, child: Option<Rc<RefCell<PopUp>>>) {
     self.modal_window = child;
   }
@@ -589,6 +900,29 @@ impl MainApp {
     self.process_selected_items_event_callback = Some(callback);
   }
 
+  /// Sets the number of physical pixels in each logical unit
+  ///
+  /// The windows are laid out in logical units and drawn at the scale factor,
+  /// so that they are the same size on high and low resolution displays. It
+  /// is set from the display when the main window is created or moved to
+  /// another display, and can be set on a headless MainApp to simulate one.
+  pub fn set_scale_factor(&mut self, scale_factor: f64) {
+
+    WindowUtils::set_scale_factor(scale_factor);
+
+    // Create a pixmap of the new size and redraw the windows into it
+    self.pixmap = Pixmap::new(
+          WindowUtils::to_physical(self.width) as u32,
+          WindowUtils::to_physical(self.height) as u32
+    ).unwrap();
+    if self.initial_draw_performed {
+      self.redraw();
+      for (_popup_window_id, popup_rc) in self.popups.clone().into_iter() {
+        popup_rc.borrow_mut().redraw();
+      }
+    }
+  }
+
   pub fn set_selection_changed_event_callback(&mut self, callback: Box<dyn Fn(Uuid)>) {
     self.selection_changed_event_callback = Some(callback);
   }
@@ -597,7 +931,7 @@ impl MainApp {
     self.set_list_event_callback = Some(callback);
   }
 
-  pub fn set_slider_value_changed_event_callback(&mut self, callback: Box<dyn Fn(f64)>) {
+  pub fn set_slider_value_changed_event_callback(&mut self, callback: Box<dyn Fn(Uuid, f64)>) {
     self.slider_value_changed_event_callback = Some(callback);
   }
 
@@ -636,6 +970,28 @@ impl MainApp {
     self.redraw_all_event_callback = Some(callback);
   }
 
+  /// Replaces the theme that the windows are drawn with, and redraws all of them
+  ///
+  /// If the theme has a font, it and the theme's font size are used by the
+  /// windows that are created afterwards. Existing windows keep their fonts,
+  /// so set the theme before creating the windows to use its font everywhere.
+  pub fn set_theme(&mut self, theme: Theme) {
+
+    if let Some(font) = &theme.font {
+      TextFont::set_default_font(font);
+    }
+    TextFont::set_default_font_size(theme.font_size);
+
+    self.theme = Rc::new(theme);
+    Theme::set_current(self.theme.clone());
+
+    // Redraw the main window and the pop-ups
+    self.redraw();
+    for (_popup_window_id, popup_rc) in self.popups.clone().into_iter() {
+      popup_rc.borrow_mut().redraw();
+    }
+  }
+
   pub fn set_user_defined_event_callback(&mut self, callback: Box<dyn Fn(u64, Vec<String>)>) {
     self.user_defined_event_callback = Some(callback);
   }
@@ -702,8 +1058,18 @@ impl MainApp {
           tooltip_ref.set_text(text);
         }
 
-        // Start a timer thread to hide the tooltip
-        // debug
+        // Hide the tooltip after a while, restarting the timer if it is already shown
+        if let Some(timer) = self.tooltip_timer.take() {
+          self.cancel_timer(timer);
+        }
+        self.tooltip_timer = Some(self.add_timer(
+              Duration::from_millis(TOOLTIP_TIMEOUT),
+              false,
+              |app| {
+                app.tooltip_timer = None;
+                app.set_tooltip_visible(false);
+              }
+        ));
 
         // Set the tooltip's visibility
         self.set_tooltip_visible(true);
@@ -713,6 +1079,36 @@ impl MainApp {
       },
     }
   }
+
+  /// Runs a future on a background thread, and then calls on_complete with its output on the UI thread
+  ///
+  /// The futures run on a small pool of threads, each of which runs one
+  /// future at a time. on_complete is given the MainApp and can use the
+  /// windows, so it is the place to display the output. The returned ID
+  /// can be passed to cancel_task(). If the future panics, the task is
+  /// removed without calling on_complete.
+  pub fn spawn<T, F, C>(&mut self, future: F, on_complete: C) -> TaskId
+  where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    C: FnOnce(&mut MainApp, T) + 'static,
+  {
+    self.event_loop_proxy.spawn(future, on_complete)
+  }
+
+  /// Runs a future on a background thread for a pop-up or a tab
+  ///
+  /// This is the same as spawn(), except that the task is cancelled when the
+  /// pop-up whose UUID is owner closes, or when a CloseTab event is received
+  /// for the tab whose UUID is owner.
+  pub fn spawn_for<T, F, C>(&mut self, owner: Uuid, future: F, on_complete: C) -> TaskId
+  where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    C: FnOnce(&mut MainApp, T) + 'static,
+  {
+    self.event_loop_proxy.spawn_for(owner, future, on_complete)
+  }
 }
 
 impl ApplicationHandler<UserEvent> for MainApp {
@@ -722,29 +1118,7 @@ impl ApplicationHandler<UserEvent> for MainApp {
     self.monitor = event_loop.primary_monitor();
 
     // Calculate the location and size of the window
-    match self.location_and_size {
-      MainAppSize::Actual(actual_x, actual_y, actual_width, actual_height) => {
-        self.x = actual_x;
-        self.y = actual_y;
-        self.width = actual_width;
-        self.height = actual_height;
-      },
-
-      MainAppSize::Relative(percent_x, percent_y, percent_width, percent_height) => {
-
-        let monitor_size = match &self.monitor {
-          Some(monitor) => monitor.size(),
-          None => PhysicalSize::new(800, 600),
-        };
-
-        self.x = monitor_size.width as f64 * percent_x;
-        self.y = monitor_size.height as f64 * percent_y;
-        self.width = monitor_size.width as f64 * percent_width;
-        self.height = monitor_size.height as f64 * percent_height;
-      },
-    }
-
-    self.pixmap = Pixmap::new(self.width as u32, self.height as u32).unwrap();
+    self.calculate_location_and_size();
 
     let window_attributes = WindowAttributes::default()
             .with_title(self.title.clone())
@@ -754,7 +1128,15 @@ impl ApplicationHandler<UserEvent> for MainApp {
     self.window = match event_loop.create_window(window_attributes) {
       Ok(window) => {
         self.window_id = Some(window.id());
-        Some(window)
+        WindowUtils::set_scale_factor(window.scale_factor());
+
+        // Frames are drawn at most as often as the monitor refreshes
+        let refresh_rate = window.current_monitor()
+              .or(self.monitor.clone())
+              .and_then(|monitor| monitor.refresh_rate_millihertz())
+              .unwrap_or(DEFAULT_REFRESH_RATE);
+        self.frame_interval = Duration::from_secs_f64(1000.0 / refresh_rate as f64);
+        Some(Rc::new(window))
       },
       Err(err) => {
         println!("In MainApp::resume(), cannot create main window: {err}");
@@ -762,6 +1144,12 @@ impl ApplicationHandler<UserEvent> for MainApp {
       },
     };
 
+    // Create the pixmap into which we will draw
+    self.pixmap = Pixmap::new(
+          WindowUtils::to_physical(self.width) as u32,
+          WindowUtils::to_physical(self.height) as u32
+    ).unwrap();
+
     // Create the initially hidden context menu
     self.create_context_menu(event_loop, self.x, self.y);
 
@@ -769,13 +1157,166 @@ impl ApplicationHandler<UserEvent> for MainApp {
     self.create_tooltip(event_loop, self.x, self.y);
   }
 
+  // Called when the event loop has processed the waiting events, before it waits for more
+  fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+
+    self.run_timers();
+    let next_frame = self.draw_frame();
+
+    // Wake up when the next timer expires or the next frame is due
+    let next_timer = self.event_loop_proxy.get_timers().borrow().get_next_deadline();
+    match next_timer.into_iter().chain(next_frame).min() {
+      Some(deadline) => event_loop.set_control_flow(ControlFlow::WaitUntil(deadline)),
+      None => event_loop.set_control_flow(ControlFlow::Wait),
+    }
+  }
+
   // Called when a UserEvent is processed by the event loop
   fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
+    self.process_user_event(Some(event_loop), event);
+  }
+
+  // Called when a WindowEvent is processed by the event loop
+  fn window_event(&mut self,
+        event_loop: &ActiveEventLoop,
+        id: WindowId,
+        event: WindowEvent
+  ) {
+
+    // If this event is for one of the popups or a context menu, pass it on.
+    match self.window_id {
+
+      Some(window_id) => {
+
+        if window_id != id {
 
+          match self.popups.get(&id) {
+
+            Some(popup_rc) => {
+              let mut popup = popup_rc.borrow_mut();
+              popup.process_event(event);
+              return;
+            },
+
+            None => {},
+          }
+
+          if id == self.context_menu_id {
+
+            match &self.context_menu {
+
+              Some(context_menu_rc) => {
+                let mut context_menu = context_menu_rc.borrow_mut();
+                context_menu.process_event(event);
+              },
+
+              None => {},
+            }
+          }
+
+          // Don't do any further processing of this event
+          return;
+        }
+      },
+
+      None => {},
+    }
+
+    // This event is for this window
     match event {
+        WindowEvent::CloseRequested => {
+
+          let do_continue = match &self.end_program_handler {
+
+            Some(end_program_handler) => end_program_handler(),
+
+            None => true,
+          };
+
+          // Exit the event loop, which will end the program
+          if do_continue {
+            event_loop.exit();
+          }
+        },
+
+        WindowEvent::CursorMoved{position, ..} => {
+          self.process_cursor_moved(WindowUtils::to_logical(position.x), WindowUtils::to_logical(position.y));
+        },
+
+        WindowEvent::KeyboardInput{device_id: _, event, is_synthetic: _} => {
+          self.process_keyboard_input(KeyboardEvent::from(event));
+        },
+
+        WindowEvent::ModifiersChanged(modifiers) => {
+          self.modifiers = modifiers.state();
+        },
+
+        WindowEvent::MouseInput{state, button, ..} => {
+          self.process_mouse_input(state, button);
+        },
+
+        WindowEvent::MouseWheel{device_id: _, delta, phase} => {
+          self.process_mouse_wheel(delta, phase);
+        },
+
+        WindowEvent::Moved(position) => {
+
+          // Save the new location, which is laid out in logical units
+          self.x = WindowUtils::to_logical(position.x as f64);
+          self.y = WindowUtils::to_logical(position.y as f64);
+        },
+
+        WindowEvent::RedrawRequested => {
+          self.process_redraw_requested();
+        },
+
+        WindowEvent::Resized(size) => {
+          self.process_resized(size.width, size.height);
+        },
+
+        WindowEvent::ScaleFactorChanged{scale_factor, ..} => {
+          self.set_scale_factor(scale_factor);
+        },
+
+        _ => {},
+    }
+  }
+}
+
+// Event processing shared by the event loop and headless mode
+impl MainApp {
+
+  // Processes a UserEvent
+  //
+  // event_loop is None when running headless, in which case pop-up windows
+  // cannot be created.
+  fn process_user_event(&mut self, event_loop: Option<&ActiveEventLoop>, event: UserEvent) {
+
+    match event {
+
+      UserEvent::App(_main_win_uuid, app_event) => {
+
+        match self.app_event_handlers.get(&app_event.get_type_id()) {
+
+          Some(handlers) => {
+            for handler in handlers {
+              handler(&app_event);
+            }
+          },
+
+          None => {
+
+            if self.log_unhandled_events {
+              println!("Received an unhandled App event: {:?}", app_event);
+            }
+          },
+        }
+      },
 
       UserEvent::CaretMoved(source_uuid, line_index, char_index) => {
 
+        self.emit_signal(source_uuid, &event);
+
         match &self.caret_moved_event_callback {
 
           Some(callback) => callback(source_uuid, line_index, char_index),
@@ -786,8 +1327,17 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
       UserEvent::ClosePopUp(_main_win_uuid, window_id) => {
 
-        // Remove the popup from the map
-        self.popups.remove(&window_id);
+        // Remove the popup from the map, along with the redraws that it requested
+        if let Some(popup) = self.popups.remove(&window_id) {
+          let popup_uuid = popup.borrow().get_uuid();
+          self.event_loop_proxy.discard_redraws(popup_uuid);
+
+          // Cancel the tasks that were started for the popup
+          self.event_loop_proxy.get_tasks().borrow_mut().cancel_owned_by(popup_uuid);
+
+          // Forget the windows that it contained, and their listeners
+          self.event_loop_proxy.unregister(popup_uuid);
+        }
 
         // If this PopUp is the modal window, clear the modal window.
         let mut matches = false;
@@ -806,6 +1356,9 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
       UserEvent::CloseTab(_main_win_uuid, source_uuid, tab_uuid) => {
 
+        // Cancel the tasks that were started for the tab
+        self.event_loop_proxy.get_tasks().borrow_mut().cancel_owned_by(tab_uuid);
+
         match &self.close_tab_event_callback {
 
           Some(callback) => callback(source_uuid, tab_uuid),
@@ -829,7 +1382,12 @@ impl ApplicationHandler<UserEvent> for MainApp {
         modal
       ) => {
 
-        self.create_popup(uuid, event_loop, x, y, width, height, modal);
+        match event_loop {
+
+          Some(event_loop) => self.create_popup(uuid, event_loop, x, y, width, height, modal),
+
+          None => println!("Cannot create a pop-up window without an event loop"),
+        }
       },
 
       UserEvent::DeleteItems(main_win_id, source_id) => {
@@ -842,19 +1400,41 @@ impl ApplicationHandler<UserEvent> for MainApp {
         }
       },
 
+      UserEvent::Edit(main_win_uuid, window_uuid, action) => {
+
+        // If the window is in one of the pop-ups, pass the event to it.
+        for (_popup_window_id, popup_rc) in self.popups.clone().into_iter() {
+
+          let mut popup_ref = popup_rc.borrow_mut();
+          if popup_ref.get_uuid() == main_win_uuid {
+            popup_ref.process_user_event(event);
+            return;
+          }
+        }
+
+        match self.layout.get_child_with_id(window_uuid) {
+
+          Some(window) => window.borrow_mut().handle_edit_action(action),
+
+          None => println!("Cannot perform {action:?}; there is no window with the ID {window_uuid}"),
+        }
+      },
+
       UserEvent::HideContextMenu(_main_win_uuid) => {
         self.set_context_menu_visible(false);
       },
 
       UserEvent::ProcessSelectedItems(_main_win_uuid, source) => {
 
+        let handled = self.emit_signal(source, &event);
+
         match &self.process_selected_items_event_callback {
 
           Some(callback) => callback(source),
 
           None => {
 
-            if self.log_unhandled_events {
+            if !handled && self.log_unhandled_events {
               println!("Received an unhandled ProcessSelectedItems event: source = {source}");
             }
           },
@@ -869,7 +1449,7 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
           let mut popup_ref = popup_rc.borrow_mut();
           if popup_ref.get_uuid() == main_win_uuid {
-            popup_ref.process_user_event(event_loop, event);
+            popup_ref.process_user_event(event);
             return;
           }
         }
@@ -880,18 +1460,10 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
           None => {
 
-            // Update the MainApp's pixmap
-            self.pixmap.draw_pixmap(
-                x as i32,
-                y as i32,
-                pixmap.as_ref(),
-                &PixmapPaint::default(),
-                Transform::identity(),
-                None,
-            );
-
-            // Display the updated pixmap
-            self.display_pixmap();
+            // Update the MainApp's pixmap. The region is displayed with the
+            // others that change before the next frame.
+            WindowUtils::draw_child_pixmap(&mut self.pixmap, x, y, pixmap);
+            self.damage.add_child_pixmap(x, y, pixmap);
           },
         }
       },
@@ -902,10 +1474,19 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
           Some(callback) => callback(),
 
-          None => self.redraw(),
+          None => {
+
+            // Only the layouts that have changed are drawn again, so
+            // there is nothing to do if none of them have
+            if self.layout.is_dirty() {
+              self.redraw();
+            }
+          },
         }
       },
 
+      UserEvent::Run(task) => task.call(self),
+
       UserEvent::ScrollValueChanged(
             main_win_uuid,
             scroll_bar_uuid,
@@ -976,13 +1557,15 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
       UserEvent::SelectionChanged(_main_win_uuid, source) => {
 
+        let handled = self.emit_signal(source, &event);
+
         match &self.selection_changed_event_callback {
 
           Some(callback) => callback(source),
 
           None => {
 
-            if self.log_unhandled_events {
+            if !handled && self.log_unhandled_events {
               println!("Received an unhandled SelectionChanged event: source = {source}");
             }
           },
@@ -1038,16 +1621,18 @@ impl ApplicationHandler<UserEvent> for MainApp {
         }
       },
 
-      UserEvent::SliderValueChange(value) => {
+      UserEvent::SliderValueChange(_main_win_uuid, source, value) => {
+
+        let handled = self.emit_signal(source, &event);
 
         match &self.slider_value_changed_event_callback {
 
-          Some(callback) => callback(value),
+          Some(callback) => callback(source, value),
 
           None => {
 
-            if self.log_unhandled_events {
-              println!("Received an unhandled SliderValueChanged event: value = {value}");
+            if !handled && self.log_unhandled_events {
+              println!("Received an unhandled SliderValueChanged event: source = {source}, value = {value}");
             }
           },
         }
@@ -1079,7 +1664,7 @@ impl ApplicationHandler<UserEvent> for MainApp {
 
           let mut popup_ref = popup_rc.borrow_mut();
           if popup_ref.get_uuid() == main_win_uuid {
-            popup_ref.process_user_event(event_loop, event);
+            popup_ref.process_user_event(event);
             return;
           }
         }
@@ -1092,7 +1677,13 @@ impl ApplicationHandler<UserEvent> for MainApp {
             let mut layout_ref = layout.borrow_mut();
             let (x, y) = layout_ref.get_location();
             let (width, height) = layout_ref.get_size();
-            layout_ref.layout(x, y, width, height);
+            layout_ref.set_dirty(true);
+            let pixmap = layout_ref.layout(x, y, width, height);
+
+            // Draw the layout with the other redraw requests, after the ones
+            // that its windows made before it was scrolled, so that they
+            // don't draw over it at their old locations
+            self.event_loop_proxy.queue_redraw(main_win_uuid, x, y, pixmap);
           },
 
           None => {},
@@ -1116,324 +1707,302 @@ impl ApplicationHandler<UserEvent> for MainApp {
     }
   }
 
-  // Called when a WindowEvent is processed by the event loop
-  fn window_event(&mut self,
-        event_loop: &ActiveEventLoop,
-        id: WindowId,
-        event: WindowEvent
-  ) {
-
-    // If this event is for one of the popups or a context menu, pass it on.
-    match self.window_id {
+  // Processes a cursor movement within the main window
+  fn process_cursor_moved(&mut self, x: f64, y: f64) {
 
-      Some(window_id) => {
+    // Save the new position, which is relative to the main window
+    self.cursor_x = x;
+    self.cursor_y = y;
 
-        if window_id != id {
+    match &self.modal_window {
 
-          match self.popups.get(&id) {
-
-            Some(popup_rc) => {
-              let mut popup = popup_rc.borrow_mut();
-              popup.process_event(event);
-              return;
-            },
-
-            None => {},
-          }
-
-          if id == self.context_menu_id {
-
-            match &self.context_menu {
-
-              Some(context_menu_rc) => {
-                let mut context_menu = context_menu_rc.borrow_mut();
-                context_menu.process_event(event);
-              },
-
-              None => {},
-            }
-          }
+      Some(window) => {
 
-          // Don't do any further processing of this event
-          return;
+        // Pass the event to the modal window
+        let mut child_ref = window.borrow_mut();
+        if self.mouse_left_button_down {
+          child_ref.handle_mouse_drag(x, y, self.modifiers);
+        } else {
+          child_ref.handle_mouse_movement(x, y);
         }
       },
+      None => {
 
-      None => {},
-    }
-
-    // This event is for this window
-    match event {
-        WindowEvent::CloseRequested => {
-
-          let do_continue = match &self.end_program_handler {
-
-            Some(end_program_handler) => end_program_handler(),
-
-            None => true,
-          };
-
-          // Exit the event loop, which will end the program
-          if do_continue {
-            event_loop.exit();
-          }
-        },
-
-        WindowEvent::CursorMoved{position, ..} => {
-
-          // Save the new position, which is relative to the main window
-          self.cursor_x = position.x;
-          self.cursor_y = position.y;
-
-          match &self.modal_window {
-
+        // Pass the event to the window under the mouse
+        match &self.layout.get_child_at(x, y) {
             Some(window) => {
-
-              // Pass the event to the modal window
               let mut child_ref = window.borrow_mut();
               if self.mouse_left_button_down {
-                child_ref.handle_mouse_drag(position.x, position.y);
+                child_ref.handle_mouse_drag(x, y, self.modifiers);
               } else {
-                child_ref.handle_mouse_movement(position.x, position.y);
+                child_ref.handle_mouse_movement(x, y);
               }
             },
-            None => {
+            None => {},
+          };
+      },
+    }
+  }
 
-              // Pass the event to the window under the mouse
-              match &self.layout.get_child_at(position.x, position.y) {
-                  Some(window) => {
-                    let mut child_ref = window.borrow_mut();
-                    if self.mouse_left_button_down {
-                      child_ref.handle_mouse_drag(position.x, position.y);
-                    } else {
-                      child_ref.handle_mouse_movement(position.x, position.y);
-                    }
-                  },
-                  None => {},
-                };
-            },
-          }
-        },
+  // Processes a key press or release
+  fn process_keyboard_input(&mut self, event: KeyboardEvent) {
 
-        WindowEvent::KeyboardInput{device_id: _, event, is_synthetic: _} => {
+    let key_pressed = event.state == ElementState::Pressed;
 
-          let key_pressed = event.state == ElementState::Pressed;
+    // Shortcuts take precedence over the window with focus
+    if key_pressed && self.modal_window.is_none() && self.process_shortcut(&event) {
+      return;
+    }
 
-          match &self.modal_window {
+    // Tab and Shift+Tab move the focus between the windows
+    if self.modal_window.is_none() &&
+          WindowUtils::is_focus_traversal(&event, self.modifiers, &self.focus_window) {
+      if key_pressed {
+        self.move_focus(self.modifiers.shift_key());
+      }
+      return;
+    }
+
+    match &self.modal_window {
 
+      Some(window) => {
+        let mut child_ref = window.borrow_mut();
+        if key_pressed {
+          child_ref.handle_keyboard_pressed_event(event, self.modifiers);
+        } else {
+          child_ref.handle_keyboard_released_event(event);
+        }
+      },
+      None => {
+        match &self.focus_window {
             Some(window) => {
               let mut child_ref = window.borrow_mut();
+              child_ref.set_focused(true);
               if key_pressed {
-                child_ref.handle_keyboard_pressed_event(event);
+                child_ref.handle_keyboard_pressed_event(event, self.modifiers);
               } else {
                 child_ref.handle_keyboard_released_event(event);
               }
             },
             None => {
-              match &self.focus_window {
-                  Some(window) => {
-                    let mut child_ref = window.borrow_mut();
-                    child_ref.set_focused(true);
-                    if key_pressed {
-                      child_ref.handle_keyboard_pressed_event(event);
-                    } else {
-                      child_ref.handle_keyboard_released_event(event);
-                    }
-                  },
-                  None => {
-                    if key_pressed {
-                      self.handle_keyboard_pressed_event(event);
-                    } else {
-                      self.handle_keyboard_released_event(event);
-                    }
-                  },
-                };
+              if key_pressed {
+                self.handle_keyboard_pressed_event(event);
+              } else {
+                self.handle_keyboard_released_event(event);
+              }
             },
-          }
-        },
+          };
+      },
+    }
+  }
 
-        WindowEvent::MouseInput{state, button, ..} => {
+  // Processes a mouse button press or release
+  fn process_mouse_input(&mut self, state: ElementState, button: MouseButton) {
+
+    match state {
+
+      ElementState::Pressed => {
 
-          match state {
+        self.mouse_left_button_down = true;
 
-            ElementState::Pressed => {
+        let mut double_click = false;
+        if MouseButton::Left == button {
+          self.dragging = true;
+          self.drag_start_win_x = self.cursor_x;
+          self.drag_start_win_y = self.cursor_y;
 
-              self.mouse_left_button_down = true;
+          // Is this the second part of a double click?
+          double_click = WindowUtils::is_double_click(
+                self.last_mouse_left_click,
+                self.cursor_x,
+                self.cursor_y
+          );
+          if double_click {
 
-              if MouseButton::Left == button {
-                self.dragging = true;
-                self.drag_start_win_x = self.cursor_x;
-                self.drag_start_win_y = self.cursor_y;
+            // A third click starts a new double click
+            self.last_mouse_left_click = None;
+          } else {
+            self.last_mouse_left_click = Some((Instant::now(), self.cursor_x, self.cursor_y));
+          }
+        }
 
-                // Is this the second part of a double click?
-                let timenow = Instant::now();
-                if timenow.duration_since(self.last_mouse_left_click) <
-                      Duration::from_millis(DOUBLE_CLICK_TIME) {
+        match &self.modal_window {
 
-                  // Pass a double-click event
-                } else {
+          Some(window) => {
+            let mut child_ref = window.borrow_mut();
+            child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y, self.modifiers);
+            if double_click {
+              child_ref.handle_mouse_double_click(button, self.cursor_x, self.cursor_y);
+            }
+          },
+          None => {
 
-                  // Start timer to pass the event
+            // Remove focus from the child that currently has it
+            match &self.focus_window {
+              Some(window) => {
+                let mut child_ref = window.borrow_mut();
+                child_ref.set_focused(false);
+              },
+              None => {},
+            };
+
+            // Give focus to the window that the cursor is on top of
+            self.focus_window = self.layout.get_child_at(self.cursor_x as f64, self.cursor_y as f64);
+            match &self.focus_window {
+              Some(window) => {
+                let mut child_ref = window.borrow_mut();
+                child_ref.set_focused(true);
+                child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y, self.modifiers);
+                child_ref.handle_mouse_drag_start(self.cursor_x, self.cursor_y, self.modifiers);
+                if double_click {
+                  child_ref.handle_mouse_double_click(button, self.cursor_x, self.cursor_y);
                 }
-              }
+              },
+              None => {
+                self.handle_mouse_pressed(button);
+              },
+            };
+          },
+        }
+      },
 
-              match &self.modal_window {
-
-                Some(window) => {
-                  let mut child_ref = window.borrow_mut();
-                  child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y);
-                },
-                None => {
-
-                  // Remove focus from the child that currently has it
-                  match &self.focus_window {
-                    Some(window) => {
-                      let mut child_ref = window.borrow_mut();
-                      child_ref.set_focused(false);
-                    },
-                    None => {},
-                  };
-
-                  // Give focus to the window that the cursor is on top of
-                  self.focus_window = self.layout.get_child_at(self.cursor_x as f64, self.cursor_y as f64);
-                  match &self.focus_window {
-                    Some(window) => {
-                      let mut child_ref = window.borrow_mut();
-                      child_ref.set_focused(true);
-                      child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y);
-                      child_ref.handle_mouse_drag_start(self.cursor_x, self.cursor_y);
-                    },
-                    None => {
-                      self.handle_mouse_pressed(button);
-                    },
-                  };
-                },
-              }
-            },
+      ElementState::Released => {
 
-            ElementState::Released => {
+        self.mouse_left_button_down = false;
 
-              self.mouse_left_button_down = false;
+        // Was dragging being performed?
+        if MouseButton::Left == button &&
+              (self.cursor_x != self.drag_start_win_x ||
+              self.cursor_y != self.drag_start_win_y) {
 
-              // Was dragging being performed?
-              if MouseButton::Left == button &&
-                    (self.cursor_x != self.drag_start_win_x ||
-                    self.cursor_y != self.drag_start_win_y) {
+          match &self.focus_window {
+            Some(window) => {
 
-                match &self.focus_window {
-                  Some(window) => {
+              // Pass the events to the window with focus
+              let mut child_ref = window.borrow_mut();
+              child_ref.handle_mouse_drag(self.cursor_x, self.cursor_y, self.modifiers);
+              child_ref.handle_mouse_drag_end(self.cursor_x, self.cursor_y, self.modifiers);
 
-                    // Pass the events to the window with focus
-                    let mut child_ref = window.borrow_mut();
-                    child_ref.handle_mouse_drag(self.cursor_x, self.cursor_y);
-                    child_ref.handle_mouse_drag_end(self.cursor_x, self.cursor_y);
+              return;
+            },
 
-                    return;
-                  },
+            None => {},
+          }
+        }
+        self.dragging = false;
 
-                  None => {},
-                }
-              }
-              self.dragging = false;
+        match &self.modal_window {
 
-              match &self.modal_window {
+          Some(window) => {
 
-                Some(window) => {
+            // Pass the event to the modal window
+            let mut child_ref = window.borrow_mut();
+            child_ref.handle_mouse_released(button, self.cursor_x, self.cursor_y);
+          },
+          None => {
 
-                  // Pass the event to the modal window
-                  let mut child_ref = window.borrow_mut();
-                  child_ref.handle_mouse_released(button, self.cursor_x, self.cursor_y);
-                },
-                None => {
+            match &self.focus_window {
+              Some(window) => {
 
-                  match &self.focus_window {
-                    Some(window) => {
+                // Pass the event to the window with focus
+                let mut child_ref = window.borrow_mut();
+                child_ref.handle_mouse_released(button, self.cursor_x, self.cursor_y);
+              },
 
-                      // Pass the event to the window with focus
-                      let mut child_ref = window.borrow_mut();
-                      child_ref.handle_mouse_released(button, self.cursor_x, self.cursor_y);
-                    },
+              None => self.handle_mouse_released(button),
+            }
+          },
+        }
+      },
+    };
+  }
 
-                    None => self.handle_mouse_released(button),
-                  }
-                },
-              }
-            },
-          };
-        },
+  // Processes a mouse wheel movement
+  fn process_mouse_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
 
-        WindowEvent::MouseWheel{device_id: _, delta, phase} => {
+    match &self.modal_window {
 
-          match &self.modal_window {
+      Some(window) => {
+        let mut child_ref = window.borrow_mut();
+        child_ref.handle_mouse_wheel(delta, phase);
+      },
+      None => {
+        // Give focus to the window that the cursor is on top of
+        self.focus_window = self.layout.get_child_at(self.cursor_x as f64, self.cursor_y as f64);
+        match &self.focus_window {
+          Some(window) => {
 
-            Some(window) => {
-              let mut child_ref = window.borrow_mut();
-              child_ref.handle_mouse_wheel(delta, phase);
-            },
-            None => {
-              // Give focus to the window that the cursor is on top of
-              self.focus_window = self.layout.get_child_at(self.cursor_x as f64, self.cursor_y as f64);
-              match &self.focus_window {
-                Some(window) => {
-
-                  // Give the window focus
-                  let mut child_ref = window.borrow_mut();
-                  child_ref.set_focused(true);
-
-                  // Pass the event to the window
-                  child_ref.handle_mouse_wheel(delta, phase);
-                },
-                None => {},
-              };
-            },
-          }
-        },
+            // Give the window focus
+            let mut child_ref = window.borrow_mut();
+            child_ref.set_focused(true);
 
-        WindowEvent::Moved(position) => {
+            // Pass the event to the window
+            child_ref.handle_mouse_wheel(delta, phase);
+          },
+          None => {},
+        };
+      },
+    }
+  }
 
-          // Save the new location
-          self.x = position.x as f64;
-          self.y = position.y as f64;
-        },
+  // Draws the main window, calling the startup callback the first time
+  fn process_redraw_requested(&mut self) {
 
-        WindowEvent::RedrawRequested => {
+    // Set the initially drawn flag
+    if !self.initial_draw_performed {
+      self.initial_draw_performed = true;
 
-          // Set the initially drawn flag
-          if !self.initial_draw_performed {
-            self.initial_draw_performed = true;
+      // Call the startup callback
+      (self.startup_callback)();
+    }
 
-            // Call the startup callback
-            (self.startup_callback)();
-          }
+    self.redraw();
+  }
 
-          self.redraw();
-        },
+  // Processes a change in the size of the main window, which is in physical pixels
+  fn process_resized(&mut self, width: u32, height: u32) {
 
-        WindowEvent::Resized(size) => {
+    // If the size of the window has changed, create a new Pixmap for drawing.
+    if width != self.pixmap.width() || height != self.pixmap.height() {
 
-          // If the size of the window has changed, create a new Pixmap for drawing.
-          if size.width as f64 != self.width || size.height as f64 != self.height {
+      // Save the new size
+      self.width = WindowUtils::to_logical(width as f64);
+      self.height = WindowUtils::to_logical(height as f64);
 
-            // Save the new size
-            self.width = size.width as f64;
-            self.height = size.height as f64;
+      // Create the pixmap into which we will draw
+      self.pixmap = Pixmap::new(width, height).unwrap();
 
-            // Create the pixmap into which we will draw
-            self.pixmap = Pixmap::new(self.width as u32, self.height as u32).unwrap();
 
+      // On intial start-up, the main window will receive this event
+      // followed by the RedrawRequested event. We only need to redraw
+      // on the second event.
+      if self.initial_draw_performed {
 
-            // On intial start-up, the main window will receive this event
-            // followed by the RedrawRequested event. We only need to redraw
-            // on the second event.
-            if self.initial_draw_performed {
+        // Redraw the window
+        self.redraw();
+      }
+    }
+  }
 
-              // Redraw the window
-              self.redraw();
-            }
-          }
-        },
+  // Performs the action of the shortcut that matches a key press
+  //
+  // Returns true if a shortcut matched
+  fn process_shortcut(&self, event: &KeyboardEvent) -> bool {
 
-        _ => {},
+    for (shortcut, action) in &self.shortcuts {
+
+      if shortcut.matches(event, self.modifiers) {
+        match action {
+          ShortcutAction::Callback(callback) => callback(),
+          ShortcutAction::Event(user_event) => {
+            WindowUtils::fire_user_event(self.event_loop_proxy.clone(), user_event.clone());
+          },
+        }
+
+        return true;
+      }
     }
+
+    false
   }
 }
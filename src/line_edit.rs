@@ -1,13 +1,11 @@
 use winit::{
   event::{
     ElementState,
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
-  keyboard::{Key, NamedKey},
+  keyboard::{Key, ModifiersState, NamedKey},
   window::Window,
 };
 
@@ -27,10 +25,12 @@ use std::{
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::ContextMenuItem;
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent};
+use crate::clipboard::Clipboard;
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::text_font::TextFont;
-use crate::UserEvent;
+use crate::undo::{TextEdit, UndoStack};
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
 
@@ -38,28 +38,24 @@ use crate::window_utils::WindowUtils;
 pub struct LineEdit {
   window_base: WindowBase,
   font: Option<TextFont>,
-  char_width: u32,
   insertion_point: usize,
-  caret_color: Color,
   internal_padding: f64,
   modified: bool,
+  undo_stack: UndoStack<usize>,    // locations are byte indexes
 }
 
 impl LineEdit {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     text: String,
   ) -> Self {
 
     // Load the font
-    let mut char_width = 20;
     let mut char_height = 18;
-    let font = match TextFont::new("../resources/FreeMonoBold.ttf", 14.0) {
+    let font = match TextFont::new_default() {
       Ok(font) => {
-        let (bounds_width, _bounds_height) = font.get_bounds("W", None);
-        char_width = bounds_width;
         let (_bounds_width, bounds_height) = font.get_bounds("Wy", None);
         char_height = bounds_height;
         Some(font)
@@ -68,10 +64,11 @@ impl LineEdit {
     };
 
     // Calculate the size of the window
-    let height: f64 = char_height as f64 + 4.0;
+    let height: f64 = WindowUtils::to_logical(char_height as f64) + 4.0;
 
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("LineEdit".to_string());
+    window_base.set_accepts_focus(true);
     window_base.set_size(200.0, height);
     window_base.set_min_size(30.0, height);
     window_base.set_max_size(-1.0, height);    // max width is never used within LineEdit
@@ -80,22 +77,48 @@ impl LineEdit {
     // Set the insert point to be at the end of the string
     let insertion_point = text.len();
 
+    let internal_padding = window_base.get_theme().metrics.padding;
+
     // Create the instance
-    Self {
+    let mut inst = Self {
       window_base: window_base,
       font: font,
-      char_width: char_width,
       insertion_point: insertion_point,
-      caret_color: Color::from_rgba8(64, 64, 255, 255),
-      internal_padding: 4.0,
+      internal_padding,
       modified: false,
+      undo_stack: UndoStack::new(),
+    };
+
+    // Build the context menu
+    for (label, action) in [("Cut", EditAction::Cut), ("Copy", EditAction::Copy), ("Paste", EditAction::Paste)] {
+      let item = WindowUtils::create_edit_menu_item(
+            label,
+            action,
+            event_loop.clone(),
+            main_win_uuid,
+            inst.get_uuid()
+      );
+      inst.add_context_menu_item(Box::new(item));
     }
+
+    inst
+  }
+
+  /// Returns true if there is a change that can be redone
+  pub fn can_redo(&self) -> bool {
+    self.undo_stack.can_redo()
+  }
+
+  /// Returns true if there is a change that can be undone
+  pub fn can_undo(&self) -> bool {
+    self.undo_stack.can_undo()
   }
 
   fn draw(&mut self) {
 
     let (_unused_width, height) = self.window_base.get_drawing_size();
-    let width = self.window_base.get_width();
+    let width = WindowUtils::to_physical(self.window_base.get_width());
+    let height = WindowUtils::to_physical(height);
 
     // Create the pixmap into which we will draw
     let mut pixmap = match Pixmap::new(width as u32, height as u32) {
@@ -107,37 +130,42 @@ impl LineEdit {
     };
 
     // Fill the pixmap with the background color
-    pixmap.fill(self.window_base.get_background_color());
+    let bg_color = self.window_base.get_background_color();
+    pixmap.fill(bg_color);
 
     // Set the border's color
+    let theme = self.window_base.get_theme();
     let mut paint = Paint::default();
-    paint.set_color_rgba8(200, 0, 0, 220);
+    paint.set_color(theme.palette.edit_border);
     paint.anti_alias = true;
 
     // Draw the border
-    WindowUtils::draw_border(&mut pixmap, width, height, &paint);
+    WindowUtils::draw_border(&mut pixmap, width, height, &paint, theme.metrics.border_width);
 
     // Display the text
     match self.window_base.get_text() {
 
       Some(text) => {
 
+        // The scroll amount and the caret index are in grapheme clusters
         let x_scroll = self.window_base.get_x_scroll();
+        let visible_text = &text[WindowUtils::get_grapheme_byte_index(&text, x_scroll as usize)..];
+        let caret_index = WindowUtils::get_grapheme_index(&text, self.insertion_point);
 
         if self.window_base.get_focused() {
           match &self.font {
             // Indicate focus by highlighting the insertion point
             Some(font) => {
               font.draw_text(
-                &text[(x_scroll as usize)..],
+                visible_text,
                 &mut pixmap,
                 2,
                 2,
-                Color::BLACK,
-                Color::WHITE,
-                (self.insertion_point as i64) - (x_scroll as i64),
-                self.caret_color,
-                Some(self.char_width)
+                theme.palette.text,
+                bg_color,
+                (caret_index as i64) - (x_scroll as i64),
+                theme.palette.accent,
+                None
               );
             },
             None => {},
@@ -147,15 +175,15 @@ impl LineEdit {
             // Indicate lack of focus by not highlighting the insertion point
             Some(font) => {
               font.draw_text(
-                &text[(x_scroll as usize)..],
+                visible_text,
                 &mut pixmap,
                 2,
                 2,
-                Color::BLACK,
-                Color::WHITE,
+                theme.palette.text,
+                bg_color,
                 -1,             // tells TextFont to not draw the caret
-                Color::BLACK,
-                Some(self.char_width)
+                theme.palette.text,
+                None
               );
             },
             None => {},
@@ -169,14 +197,107 @@ impl LineEdit {
     self.window_base.set_pixmap(pixmap);
   }
   
+  // Draws the window and requests that it be redrawn within the main window
+  fn draw_and_request_redraw(&mut self) {
+
+    let x = self.window_base.get_x();
+    let y = self.window_base.get_y();
+    self.draw();
+    WindowUtils::request_redraw(
+          self.window_base.get_event_loop().clone(),
+          self.window_base.get_main_win_uuid(),
+          x,
+          y,
+          self.window_base.get_pixmap()
+    );
+  }
+
+  // Replaces the characters between start and end, records the change so
+  // that it can be undone, and moves the caret to the end of the new text
+  //
+  // typing is true if the text was typed, so that it can be undone along with
+  // the text typed just before it.
+  fn edit_text(&mut self, text: &mut String, start: usize, end: usize, inserted: &str, typing: bool) {
+
+    let edit = TextEdit {
+      start,
+      removed: text[start..end].to_string(),
+      inserted: inserted.to_string(),
+      caret_before: self.insertion_point,
+      caret_after: start + inserted.len(),
+    };
+
+    text.replace_range(start..end, inserted);
+    self.insertion_point = edit.caret_after;
+    self.undo_stack.record(edit, typing);
+
+    self.modified = true;
+  }
+
   /// Returns true if the text has been modified
   pub fn is_modified(&self) -> bool {
     self.modified
   }
 
+  /// Redoes the last change that was undone
+  pub fn redo(&mut self) {
+
+    if let Some(edit) = self.undo_stack.redo() {
+      let mut text = self.window_base.get_text().unwrap_or_default();
+      text.replace_range(edit.start..edit.start + edit.removed.len(), &edit.inserted);
+      self.restore_caret(text, edit.caret_after);
+    }
+  }
+
+  // Saves the text after a change was undone or redone, moves the caret, and
+  // redraws the window
+  fn restore_caret(&mut self, text: String, caret: usize) {
+
+    self.insertion_point = caret;
+
+    // The text is unmodified if it is back to where it was saved
+    self.modified = !self.undo_stack.is_saved();
+
+    self.update_text(text);
+    self.draw_and_request_redraw();
+  }
+
   /// Sets the modified flag
+  ///
+  /// Clearing the flag marks the current text as saved, so undoing or redoing
+  /// back to it also clears the flag.
   pub fn set_modified(&mut self, modified: bool) {
     self.modified = modified;
+    if !modified {
+      self.undo_stack.set_saved();
+    }
+  }
+
+  /// Undoes the last change
+  pub fn undo(&mut self) {
+
+    if let Some(edit) = self.undo_stack.undo() {
+      let mut text = self.window_base.get_text().unwrap_or_default();
+      text.replace_range(edit.start..edit.start + edit.inserted.len(), &edit.removed);
+      self.restore_caret(text, edit.caret_before);
+    }
+  }
+
+  // Saves the text and scrolls, if necessary, so that the caret is visible
+  fn update_text(&mut self, text: String) {
+
+    // Get the number of grapheme clusters before the caret
+    let caret_index = WindowUtils::get_grapheme_index(&text, self.insertion_point);
+
+    // If the caret is no longer visible, scroll the window so that it is.
+    if let Some(font) = &self.font {
+      let width = WindowUtils::to_physical(self.window_base.get_width() - self.internal_padding);
+      let x_scroll = self.window_base.get_x_scroll() as usize;
+      let first_visible = font.layout(&text, None).get_scroll_for_caret(caret_index, x_scroll, width as f32);
+      self.window_base.set_x_scroll(first_visible as f64);
+    }
+
+    self.window_base.set_text(text);
   }
 }
 
@@ -215,6 +336,13 @@ impl ChildWindow for LineEdit {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -229,10 +357,10 @@ impl ChildWindow for LineEdit {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -273,6 +401,20 @@ impl ChildWindow for LineEdit {
     );
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -318,12 +460,12 @@ impl ChildWindow for LineEdit {
           Some(font) => {
             // Find the size of the text
             let (_, text_height) = match self.window_base.get_text() {
-              Some(text) => font.get_bounds(&text, Some(self.char_width)),
+              Some(text) => font.get_bounds(&text, None),
               None => (0, 0),
             };
 
             // Add internal padding
-            text_height as f64 + self.internal_padding
+            WindowUtils::to_logical(text_height as f64) + self.internal_padding
           },
           None => 1.0
         };
@@ -344,6 +486,10 @@ impl ChildWindow for LineEdit {
     (self.window_base.get_width(), height)    // Use the saved width, which is set in redraw()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -395,6 +541,7 @@ impl ChildWindow for LineEdit {
   fn set_text(&mut self, text: String) {
     
     self.modified = false;
+    self.undo_stack.clear();
     
     self.window_base.set_text(text);
 
@@ -410,7 +557,57 @@ impl ChildWindow for LineEdit {
     );
   }
 
-  fn handle_keyboard_pressed_event(&mut self, event: KeyEvent) {
+  fn handle_edit_action(&mut self, action: EditAction) {
+
+    // The clipboard operations act on all of the text
+    let mut text = match self.window_base.get_text() {
+      Some(text) => text,
+      None => "".to_string(),
+    };
+
+    // A failed copy leaves the text as it was, and a failed paste does nothing
+    match action {
+      EditAction::Copy => {
+        let _ = Clipboard::set_text(&text);
+        return;
+      },
+      EditAction::Cut => {
+        if Clipboard::set_text(&text).is_err() {
+          return;
+        }
+        let end = text.len();
+        self.edit_text(&mut text, 0, end, "", false);
+      },
+      EditAction::Paste => {
+
+        // Line breaks cannot be displayed, so they are replaced with spaces
+        let pasted = match Clipboard::get_text() {
+          Ok(Some(pasted)) => pasted.replace("\r\n", " ").replace(['\r', '\n'], " "),
+          Ok(None) | Err(_) => return,
+        };
+        self.edit_text(&mut text, self.insertion_point, self.insertion_point, &pasted, false);
+      },
+      EditAction::Undo => {
+        self.undo();
+        return;
+      },
+      EditAction::Redo => {
+        self.redo();
+        return;
+      },
+    }
+
+    self.update_text(text);
+    self.draw_and_request_redraw();
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, event: KeyboardEvent, modifiers: ModifiersState) {
+
+    // Ctrl+C, Ctrl+X, and Ctrl+V use the clipboard, and Ctrl+Z and Ctrl+Y undo and redo
+    if let Some(action) = WindowUtils::get_edit_action(&event, modifiers) {
+      self.handle_edit_action(action);
+      return;
+    }
 
     match self.window_base.get_text() {
 
@@ -428,9 +625,7 @@ impl ChildWindow for LineEdit {
 
           // Caret movement
           Key::Named(NamedKey::ArrowLeft) => {
-            if self.insertion_point > 0 {
-              self.insertion_point -= 1;
-            }
+            self.insertion_point = WindowUtils::get_previous_grapheme_boundary(&text, self.insertion_point);
 
             // Set the flag in order to scroll, if necessary.
             text_modified = true;
@@ -438,9 +633,7 @@ impl ChildWindow for LineEdit {
             processed = true;
           },
           Key::Named(NamedKey::ArrowRight) => {
-            if self.insertion_point < text.len() {
-              self.insertion_point += 1;
-            }
+            self.insertion_point = WindowUtils::get_next_grapheme_boundary(&text, self.insertion_point);
 
             // Set the flag in order to scroll, if necessary.
             text_modified = true;
@@ -472,13 +665,9 @@ impl ChildWindow for LineEdit {
             // If the insertion point is already at the beginning, there is nothing to do.
             if self.insertion_point > 0 {
 
-              // Move the insertion point back one character
-              self.insertion_point -= 1;
-
-              // Delete this character
-              let mut part1 = text[0..self.insertion_point].to_string();
-              part1.push_str(&text[self.insertion_point + 1..]);
-              text = part1;
+              // Delete the character before the insertion point
+              let start = WindowUtils::get_previous_grapheme_boundary(&text, self.insertion_point);
+              self.edit_text(&mut text, start, self.insertion_point, "", false);
 
               text_modified = true;
             }
@@ -491,9 +680,8 @@ impl ChildWindow for LineEdit {
             if self.insertion_point < text.len() {
 
               // Delete this character
-              let mut part1 = text[0..self.insertion_point].to_string();
-              part1.push_str(&text[self.insertion_point + 1..]);
-              text = part1;
+              let end = WindowUtils::get_next_grapheme_boundary(&text, self.insertion_point);
+              self.edit_text(&mut text, self.insertion_point, end, "", false);
 
               text_modified = true;
             }
@@ -511,12 +699,12 @@ impl ChildWindow for LineEdit {
           match event.text {
             Some(event_text) => {
 
-              // Ignore carriage returns and linefeeds
-              if event_text != "\r" && event_text != "\n" {
+              // Ignore carriage returns, linefeeds, and the other control
+              // characters, such as those typed while Ctrl is held down
+              if !event_text.chars().any(|character| character.is_control()) {
 
                 // Insert the entered text at the insertion point
-                text.insert_str(self.insertion_point, event_text.as_str());
-                self.insertion_point += event_text.len();
+                self.edit_text(&mut text, self.insertion_point, self.insertion_point, &event_text, true);
 
                 text_modified = true;
               }
@@ -526,45 +714,27 @@ impl ChildWindow for LineEdit {
         }
 
         if text_modified {
-          
-          self.modified = true;
-          
-          self.window_base.set_text(text);
-
-          // Calculate the width of the window in characters
-          let width = self.window_base.get_width();
-          let win_char_width = width / self.char_width as f64;
-
-          // If the caret is no longer visible, scroll the window so that it is.
-          let x_scroll = self.window_base.get_x_scroll();
-          if (self.insertion_point as f64) < x_scroll {
-            self.window_base.set_x_scroll(self.insertion_point as f64);
-          } else if (self.insertion_point as f64) + x_scroll >= win_char_width {
-            self.window_base.set_x_scroll((self.insertion_point as f64) - (win_char_width - 1.0));
-          }
+          self.update_text(text);
         }
 
         // Redraw this window's contents within the main window
-        let x = self.window_base.get_x();
-        let y = self.window_base.get_y();
-        self.draw();
-        WindowUtils::request_redraw(
-              self.window_base.get_event_loop().clone(),
-              self.window_base.get_main_win_uuid(),
-              x,
-              y,
-              self.window_base.get_pixmap()
-        );
+        self.draw_and_request_redraw();
       },
 
       None => self.insertion_point = 0,
     }
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-      mouse_x: f64, _mouse_y: f64) {
+      mouse_x: f64, mouse_y: f64, _modifiers: ModifiersState) {
+
+    // Right mouse click shows the context menu
+    if MouseButton::Right == button {
+      self.window_base.handle_mouse_pressed(button, mouse_x, mouse_y);
+      return;
+    }
 
     if button == MouseButton::Left {
 
@@ -576,24 +746,15 @@ impl ChildWindow for LineEdit {
 
             Some(text) => {
 
-              let text_len = text.len();
-
-              // Find the character that starts closest to, but not after, the mouse location
-              let (text_width, _text_height) =
-                  font.get_bounds(text.as_str(), Some(self.char_width));
-              let char_width; 
-              if 0 < text_len {
-                char_width = text_width / text_len as u32;
-              } else {
-                char_width = text_width;
-              }
-              let mut insertion_point = ((mouse_x - self.window_base.get_x()) / char_width as f64) as usize;
-              if insertion_point > text_len {
-                insertion_point = text_len;
-              }
+              // Find the character boundary closest to the mouse location. The
+              // text is drawn 2 pixels from the left edge.
+              let x_scroll = self.window_base.get_x_scroll() as usize;
+              let visible_text = &text[WindowUtils::get_grapheme_byte_index(&text, x_scroll)..];
+              let mouse_x = WindowUtils::to_physical(mouse_x - self.window_base.get_x()) - 2.0;
+              let insertion_point = font.layout(visible_text, None).get_grapheme_at(mouse_x as f32) + x_scroll;
 
               // Set the insertion point and caret
-              self.insertion_point = insertion_point;
+              self.insertion_point = WindowUtils::get_grapheme_byte_index(&text, insertion_point);
 
               self.draw();
               WindowUtils::request_redraw(
@@ -617,11 +778,15 @@ impl ChildWindow for LineEdit {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _x: f64, _y: f64) {
@@ -659,7 +824,7 @@ impl ChildWindow for LineEdit {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || new_height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || new_height != win_height {
 
       // Save the new size
       self.window_base.set_size(width, new_height);
@@ -1,7 +1,6 @@
 use winit::{
-  event::{KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
-  event_loop::EventLoopProxy,
-  keyboard::{KeyCode, PhysicalKey},
+  event::{MouseButton, MouseScrollDelta, TouchPhase},
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -14,8 +13,11 @@ use uuid::Uuid;
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, LayoutType};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, LayoutType};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::rich_text::RichText;
+use crate::signal::ConnectionId;
 use crate::text_font::TextFont;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
@@ -38,32 +40,29 @@ pub enum SelectionMode {
 pub struct List {
   window_base: WindowBase,
   font: Option<TextFont>,
-  char_width: u32,        // Width of a wide character
-  char_height: u32,
+  char_width: f64,        // Width of a wide character, in logical units
+  char_height: f64,
   items: Vec<String>,
-  color_text: Color,
+  rich_items: Vec<Option<RichText>>,    // rich text of each item that has it
   selection_mode: SelectionMode,
   selected: Vec<usize>,
-  selected_color: Color,
-  ctrl_down: bool,
-  shift_down: bool,
   row_locations: HashMap<usize, f64>, // key is the row's zero-based index, value is the Y coordinate
 }
 
 impl List {
 
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
   ) -> Self {
 
     // Load the font
-    let mut char_width = 0;
-    let mut char_height = 0;
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", 14.0) {
+    let mut char_width = 0.0;
+    let mut char_height = 0.0;
+    let font = match TextFont::new_default() {
       Ok(mut font) => {
-        char_width = font.get_max_char_width();
-        char_height = font.get_max_char_height();
+        char_width = WindowUtils::to_logical(font.get_max_char_width() as f64);
+        char_height = WindowUtils::to_logical(font.get_max_char_height() as f64);
         Some(font)
       },
       Err(_err) => None,
@@ -71,6 +70,7 @@ impl List {
 
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("Template".to_string());
+    window_base.set_accepts_focus(true);
 
     // Create the instance
     let mut inst = Self {
@@ -79,12 +79,9 @@ impl List {
       char_width: char_width,
       char_height: char_height,
       items: Vec::new(),
-      color_text: Color::BLACK,
+      rich_items: Vec::new(),
       selection_mode: SelectionMode::Single,
       selected: Vec::new(),
-      selected_color: Color::from_rgba8(32, 32, 150, 255),
-      ctrl_down: false,
-      shift_down: false,
       row_locations: HashMap::new(),
     };
 
@@ -113,6 +110,7 @@ impl List {
   /// Appends an item to the list
   pub fn append(&mut self, item: String) {
     self.items.push(item);
+    self.rich_items.push(None);
 
     // Redraw the pixmap
     self.draw();
@@ -148,10 +146,16 @@ impl List {
     }
   }
 
+  /// Disconnects a listener that was connected with one of the on_ methods,
+  /// returning false if it isn't connected
+  pub fn disconnect(&self, id: ConnectionId) -> bool {
+    self.window_base.disconnect(id)
+  }
+
   fn draw(&mut self) {
 
     // Create the new pixmap
-    let (width, height) = self.window_base.get_drawing_size();
+    let (width, height) = self.window_base.get_physical_drawing_size();
     let mut pixmap = match Pixmap::new(width as u32, height as u32) {
       Some(pixmap) => pixmap,
       None => {
@@ -159,7 +163,9 @@ impl List {
       },
     };
 
-    pixmap.fill(Color::WHITE);
+    let theme = self.window_base.get_theme();
+    let bg_color = self.window_base.get_background_color();
+    pixmap.fill(bg_color);
 
     // Draw the items into the pixmap
     match &self.font {
@@ -178,30 +184,40 @@ impl List {
           let item = self.items.get(item_index).unwrap();
 
           // Set the drawing color based on whether this item is selected
-          let mut drawing_color = self.color_text;
+          let mut drawing_color = theme.palette.text;
           if self.selected.iter().any(|&i| i == item_index) {
-            drawing_color = self.selected_color;
+            drawing_color = theme.palette.selected_item;
           }
 
           // Draw the item's text
-          let (_drawn_width, drawn_height) = font.draw_text(
-            item.as_str(),
-            &mut pixmap,
-            0,
-            y as i32,
-            drawing_color,
-            Color::WHITE,
-            -1,
-            drawing_color,
-            None
-          );
+          let (_drawn_width, drawn_height) = match self.rich_items.get(item_index) {
+            Some(Some(rich_text)) => font.draw_rich_text(
+              rich_text,
+              &mut pixmap,
+              0,
+              y as i32,
+              drawing_color,
+              bg_color,
+            ),
+            _ => font.draw_text(
+              item.as_str(),
+              &mut pixmap,
+              0,
+              y as i32,
+              drawing_color,
+              bg_color,
+              -1,
+              drawing_color,
+              None
+            ),
+          };
 
           // Save this row's location (just the Y coordinate)
           self.row_locations.insert(item_index, y as f64);
 
           // Prepare for the next item
           y += drawn_height;
-          y += LINE_PADDING;
+          y += WindowUtils::to_physical(LINE_PADDING as f64) as u32;
 
           item_index += 1;
         }
@@ -214,6 +230,28 @@ impl List {
     self.window_base.set_pixmap(pixmap);
   }
 
+  // Returns the index of the row at a Y coordinate relative to the main window
+  fn get_row_index_at(&self, mouse_y: f64) -> usize {
+
+    // Convert the mouse coordinates the coordinates relative to the top of the window
+    let (_x, y) = self.window_base.get_location();
+    let win_y = WindowUtils::to_physical(mouse_y - y);
+
+    let mut row_index = self.window_base.get_y_scroll() as usize;
+    for _i in 0..self.row_locations.len() {
+      // Start search if this row's Y coordinate is greater than the mouse location
+      if self.row_locations[&row_index] > win_y {
+        break;
+      }
+      row_index += 1;
+    }
+    if row_index > 0 {
+      row_index -= 1;   // The mouse is on the previous row
+    }
+
+    row_index
+  }
+
   /// Gets the item with the specified zero-based index
   pub fn get_item_by_index(&self, index: usize) -> Option<&String> {
     self.items.get(index)
@@ -227,6 +265,7 @@ impl List {
   /// Inserts an item into the list
   pub fn insert(&mut self, index: usize, item: String) {
     self.items.insert(index, item);
+    self.rich_items.insert(index, None);
 
     // Redraw the pixmap
     self.draw();
@@ -242,6 +281,29 @@ impl List {
     );
   }
 
+  /// Calls a function each time the user chooses to process the selected
+  /// items, such as by double-clicking one
+  pub fn on_process_selected_items<F: Fn() + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      let matched = matches!(event, UserEvent::ProcessSelectedItems(..));
+      if matched {
+        listener();
+      }
+      matched
+    }))
+  }
+
+  /// Calls a function each time the selected items change
+  pub fn on_selection_changed<F: Fn() + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      let matched = matches!(event, UserEvent::SelectionChanged(..));
+      if matched {
+        listener();
+      }
+      matched
+    }))
+  }
+
   /// Removes an item from the list
   ///
   /// Returns an Option containing the removed item or None if the index is invalid.
@@ -252,6 +314,7 @@ impl List {
     }
 
     let item = self.items.remove(index);
+    self.rich_items.remove(index);
 
     // Request a redraw
     let (x, y) = self.window_base.get_location();
@@ -270,6 +333,7 @@ impl List {
   pub fn set_items(&mut self, items: Vec<String>) {
 
     self.items = items;
+    self.rich_items = vec![None; self.items.len()];
 
     self.selected.clear();
 
@@ -285,15 +349,15 @@ impl List {
     // Set the scroll ranges
     let (win_width, win_height) = self.window_base.get_drawing_size();
     self.set_x_scroll_min(0.0);
-    if longest as f64 > win_width / self.char_width as f64 {
-      self.set_x_scroll_max(longest as f64 - (win_width / self.char_width as f64));
+    if longest as f64 > win_width / self.char_width {
+      self.set_x_scroll_max(longest as f64 - (win_width / self.char_width));
     } else {
       self.set_x_scroll_max(0.0);
     }
     self.set_x_scroll(0.0);
     self.set_y_scroll_min(0.0);
-    if self.items.len() as f64 > win_height / self.char_height as f64 {
-      self.set_y_scroll_max(self.items.len() as f64 - (win_height / self.char_height as f64));
+    if self.items.len() as f64 > win_height / self.char_height {
+      self.set_y_scroll_max(self.items.len() as f64 - (win_height / self.char_height));
     } else {
       self.set_y_scroll_max(0.0);
     }
@@ -333,6 +397,38 @@ impl List {
     }
   }
 
+  /// Sets the text of an item to text whose runs have their own fonts, colors, and decorations
+  ///
+  /// This can be used to highlight the part of an item that matches a search.
+  /// The runs that don't have a color are drawn in the item's color, which
+  /// shows whether it is selected. get_item_by_index() returns the text
+  /// without its styles. If the index is invalid, the returned Result
+  /// contains a string explaining why.
+  pub fn set_item_rich_text(&mut self, index: usize, text: RichText) -> Result<(), String> {
+
+    if index >= self.items.len() {
+      return Err(format!("There is no item with index {index}"));
+    }
+
+    self.items[index] = text.get_text();
+    self.rich_items[index] = Some(text);
+
+    // Redraw the pixmap
+    self.draw();
+
+    // Request a redraw of this window
+    let (x, y) = self.window_base.get_location();
+    WindowUtils::request_redraw(
+          self.window_base.get_event_loop().clone(),
+          self.window_base.get_main_win_uuid(),
+          x,
+          y,
+          self.window_base.get_pixmap()
+    );
+
+    Ok(())
+  }
+
   /// Sets the selection mode
   pub fn set_selection_mode(&mut self, mode: SelectionMode) {
     self.selection_mode = mode;
@@ -371,6 +467,13 @@ impl ChildWindow for List {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -385,10 +488,10 @@ impl ChildWindow for List {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -406,6 +509,20 @@ impl ChildWindow for List {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -451,6 +568,10 @@ impl ChildWindow for List {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -508,13 +629,13 @@ impl ChildWindow for List {
 
     let (width, _height) = self.window_base.get_drawing_size();
 
-    width / self.char_width as f64
+    width / self.char_width
   }
   fn get_max_vertical_visible_items(&self) -> f64 {
 
     let (_width, height) = self.window_base.get_drawing_size();
 
-    height / self.char_height as f64
+    height / self.char_height
   }
 
   fn get_text(&self) -> Option<String> {
@@ -524,39 +645,16 @@ impl ChildWindow for List {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, event: KeyEvent) {
-
-    match event.physical_key {
-
-      PhysicalKey::Code(key_code) => {
-        if KeyCode::ControlLeft == key_code || KeyCode::ControlRight == key_code {
-          self.ctrl_down = true;
-        } else if KeyCode::ShiftLeft == key_code || KeyCode::ShiftRight == key_code {
-          self.shift_down = true;
-        }
-      },
-
-      PhysicalKey::Unidentified(_native_key_code) => {},
-    }
+  fn handle_edit_action(&mut self, _action: EditAction) {
   }
-  fn handle_keyboard_released_event(&mut self, event: KeyEvent) {
-
-    match event.physical_key {
 
-      PhysicalKey::Code(key_code) => {
-        if KeyCode::ControlLeft == key_code || KeyCode::ControlRight == key_code {
-          self.ctrl_down = false;
-        } else if KeyCode::ShiftLeft == key_code || KeyCode::ShiftRight == key_code {
-          self.shift_down = false;
-        }
-      },
-
-      PhysicalKey::Unidentified(_native_key_code) => {},
-    }
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
+  }
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-        mouse_x: f64, mouse_y: f64) {
+        mouse_x: f64, mouse_y: f64, modifiers: ModifiersState) {
 
     // Right mouse click shows the context menu
     if MouseButton::Right == button {
@@ -568,22 +666,10 @@ impl ChildWindow for List {
 
       Some(_font) => {
 
-        // Convert the mouse coordinates the coordinates relative to the top of the window
         let (x, y) = self.window_base.get_location();
-        let win_y = mouse_y - y;
 
         // Get the index of the item's row that was clicked
-        let mut row_index = self.window_base.get_y_scroll() as usize;
-        for _i in 0..self.row_locations.len() {
-          // Start search if this row's Y coordinate is greater than the mouse location
-          if self.row_locations[&row_index] > win_y {
-            break;
-          }
-          row_index += 1;
-        }
-        if row_index > 0 {
-          row_index -= 1;   // The mouse is on the previous row
-        }
+        let row_index = self.get_row_index_at(mouse_y);
 
         // If this item is already selected, deselect it; otherwise,
         // save the selection.
@@ -608,11 +694,11 @@ impl ChildWindow for List {
 
                 SelectionMode::Multiple => {
 
-                  if !self.ctrl_down && !self.shift_down {
+                  if !modifiers.control_key() && !modifiers.shift_key() {
                     self.selected.clear();
                   }
 
-                  if self.shift_down {
+                  if modifiers.shift_key() {
 
                     // Sort the vector of indices of selected items
                     self.selected.sort();
@@ -660,7 +746,7 @@ impl ChildWindow for List {
                         j += 1;
                       }
                     }
-                  } else if self.ctrl_down {
+                  } else if modifiers.control_key() {
 
                     // If this item is already selected, unselect it; otherwise,
                     // select it.
@@ -707,11 +793,54 @@ impl ChildWindow for List {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, button: MouseButton,
+        _mouse_x: f64, mouse_y: f64) {
+
+    if MouseButton::Left != button {
+      return;
+    }
+
+    let row_index = self.get_row_index_at(mouse_y);
+    if row_index >= self.items.len() {
+      return;
+    }
+
+    // The second click toggled the row's selection off, so select it again
+    if !self.selected.contains(&row_index) {
+
+      match self.selection_mode {
+        SelectionMode::Single => self.selected.clear(),
+        SelectionMode::Multiple => {},
+      }
+      self.selected.push(row_index);
+
+      // Redraw the list so that the highlighted items is updated
+      self.draw();
+      let (x, y) = self.window_base.get_location();
+      WindowUtils::request_redraw(
+            self.window_base.get_event_loop().clone(),
+            self.window_base.get_main_win_uuid(),
+            x,
+            y,
+            self.window_base.get_pixmap()
+      );
+    }
+
+    // Open the selected items
+    WindowUtils::fire_user_event(
+      self.window_base.get_event_loop().clone(),
+      UserEvent::ProcessSelectedItems(
+            self.window_base.get_main_win_uuid(),
+            self.window_base.get_uuid()
+      )
+    );
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _main_win_x: f64, _main_win_y: f64) {
@@ -767,7 +896,7 @@ impl ChildWindow for List {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || height != win_height {
 
       // Save the new size
       self.window_base.set_size(width, height);
@@ -1,28 +1,28 @@
 use winit::{
-  event::{KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
-  event_loop::EventLoopProxy,
+  event::{MouseButton, MouseScrollDelta, TouchPhase},
+  keyboard::ModifiersState,
   window::Window,
 };
 
 use tiny_skia::{
   Color,
   Pixmap,
-  PixmapPaint,
-  Transform,
 };
 
 use uuid::Uuid;
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, Layout, Orientation};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, Layout, Orientation};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::label::Label;
 use crate::LayoutArgs;
 use crate::row_layout::RowLayout;
+use crate::shortcut::Shortcut;
 use crate::text_font::TextFont;
-use crate::UserEvent;
 use crate::window_base::WindowBase;
+use crate::window_utils::WindowUtils;
 
 use std::{
   cell::RefCell,
@@ -36,7 +36,7 @@ pub struct MenuBar {
   font: Option<TextFont>,
   button_height: f64,
   layout: RowLayout,
-  bg_color: Color,
+  items: Vec<Rc<RefCell<Label>>>,
   action_map: HashMap<String, Box<dyn Fn()>>,  // Map of menu item actions, key is the label of the menu item
 }
 
@@ -46,29 +46,29 @@ impl MenuBar {
   ///
   /// event_loop: the application's event loop
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
   ) -> Self {
 
     // Load the font
     let font: Option<TextFont>;
     let win_height;
-    match TextFont::new("../resources/FreeMonoBold.ttf", 14.0) {
+    match TextFont::new_default() {
       Ok(text_font) => {
         let (_text_width, text_height) = text_font.get_bounds(&"Wy".to_string(), None);
-        win_height = text_height;
+        win_height = WindowUtils::to_logical(text_height as f64);
         font = Some(text_font);
       },
       Err(err) => {
         println!("Cannot load font for MenuBar: {err}");
-        win_height = 18;
+        win_height = 18.0;
         font = None;
       },
     };
 
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("Menu".to_string());
-    window_base.set_height((win_height * 2) as f64);    // This should be enough room for the buttons and some margins
+    window_base.set_height(win_height * 2.0);    // This should be enough room for the buttons and some margins
 
     let mut row_layout = RowLayout::new(
             event_loop.clone(),
@@ -81,9 +81,9 @@ impl MenuBar {
     Self {
       window_base: window_base,
       font: font,
-      button_height: (win_height + 4) as f64,
+      button_height: win_height + 4.0,
       layout: row_layout,
-      bg_color: Color::from_rgba8(64, 64, 255, 255),
+      items: Vec::new(),
       action_map: HashMap::new(),
     }
   }
@@ -99,12 +99,13 @@ impl MenuBar {
 
     // Create this item
     let event_loop_clone = self.window_base.get_event_loop().clone();
+    let palette = self.window_base.get_theme().palette.clone();
     let mut item = Label::new(
           event_loop_clone.clone(),
           self.window_base.get_main_win_uuid(),
           label.clone(),
-          Color::BLACK,
-          self.bg_color
+          palette.text,
+          palette.accent
     );
 
     // Adjust the menu's height, if necessary
@@ -112,7 +113,7 @@ impl MenuBar {
 
       Some(font) => {
         let (bounds_width, _bounds_height) = font.get_bounds(&label.clone(), None);
-        item.set_max_size((bounds_width + 4) as f64, self.button_height);
+        item.set_max_size(WindowUtils::to_logical(bounds_width as f64) + 4.0, self.button_height);
       },
 
       None => {},
@@ -124,6 +125,7 @@ impl MenuBar {
 
     // Add the item to the layout
     let item_ref = Rc::new(RefCell::new(item));
+    self.items.push(item_ref.clone());
     match self.layout.add_child(
           item_ref.clone(),
           LayoutArgs::None,
@@ -133,6 +135,18 @@ impl MenuBar {
     };
   }
 
+  /// Adds an item to the menu, displaying its shortcut's accelerator text
+  ///
+  /// The shortcut itself must be registered with MainApp::add_shortcut(), which
+  /// MainApp::add_menu_item_with_shortcut() does.
+  ///
+  /// label: menu item's display label
+  /// shortcut: key combination that performs the same action as the menu item
+  /// callback: closure that will be called when a menu item is clicked
+  pub fn add_item_with_shortcut<F: Fn() + 'static>(&mut self, label: String, shortcut: &Shortcut, callback: F) {
+    self.add_item(format!("{label} ({shortcut})"), callback);
+  }
+
   /// Adds a drop-down sub-menu to the menu
   pub fn add_submenu(&mut self) {
   }
@@ -140,14 +154,22 @@ impl MenuBar {
   fn draw(&mut self) {
 
     // Create the new pixmap
-    let (width, height) = self.window_base.get_drawing_size();
-    let mut pixmap = match Pixmap::new(width as u32, height as u32) {
+    let (width, _height) = self.window_base.get_drawing_size();
+    let (pixmap_width, pixmap_height) = self.window_base.get_physical_drawing_size();
+    let mut pixmap = match Pixmap::new(pixmap_width as u32, pixmap_height as u32) {
       Some(pixmap) => pixmap,
       None => {
         return;
       },
     };
-    pixmap.fill(self.bg_color);
+    // The menu bar and its items use the theme's accent color
+    let palette = self.window_base.get_theme().palette.clone();
+    if self.window_base.is_theme_changed() {
+      for item in &self.items {
+        item.borrow_mut().set_colors(palette.text, palette.accent);
+      }
+    }
+    pixmap.fill(palette.accent);
 
     // Tell the layout to draw its contents
     let layout_pixmap = self.layout.layout(
@@ -158,14 +180,7 @@ impl MenuBar {
     );
 
     // Copy the layout's pixmap image onto the full pixmap
-    pixmap.draw_pixmap(
-        0,
-        5,
-        layout_pixmap.as_ref(),
-        &PixmapPaint::default(),
-        Transform::identity(),
-        None,
-    );
+    WindowUtils::draw_child_pixmap(&mut pixmap, 0.0, 5.0, &layout_pixmap);
 
     // Save the new pixmap
     self.window_base.set_pixmap(pixmap);
@@ -204,6 +219,13 @@ impl ChildWindow for MenuBar {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -218,10 +240,10 @@ impl ChildWindow for MenuBar {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -239,6 +261,20 @@ impl ChildWindow for MenuBar {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -284,6 +320,10 @@ impl ChildWindow for MenuBar {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -336,13 +376,16 @@ impl ChildWindow for MenuBar {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, _button: MouseButton,
-        mouse_x: f64, mouse_y: f64) {
+        mouse_x: f64, mouse_y: f64, _modifiers: ModifiersState) {
 
     // Get the item that was clicked
     match self.layout.get_child_at(mouse_x, mouse_y) {
@@ -369,11 +412,15 @@ impl ChildWindow for MenuBar {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _main_win_x: f64, _main_win_y: f64) {
@@ -392,7 +439,7 @@ impl ChildWindow for MenuBar {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || height != win_height {
 
       // Save the new width. The height is set in new() and should never change.
       self.window_base.set_size(width, win_height);
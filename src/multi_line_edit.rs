@@ -1,19 +1,20 @@
 use winit::{
   event::{
     ElementState,
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
-  keyboard::{Key, NamedKey},
+  keyboard::{Key, ModifiersState, NamedKey},
   window::Window,
 };
 
 use tiny_skia::{
   Color,
+  Paint,
   Pixmap,
+  Rect,
+  Transform,
 };
 
 use uuid::Uuid;
@@ -27,9 +28,13 @@ use std::{
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, LayoutType};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, LayoutType};
+use crate::clipboard::Clipboard;
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::signal::ConnectionId;
 use crate::text_font::TextFont;
+use crate::undo::{TextEdit, UndoStack};
 use crate::UserEvent;
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
@@ -42,35 +47,36 @@ const LINE_PADDING: u32 = 2;
 pub struct MultiLineEdit {
   window_base: WindowBase,
   font: Option<TextFont>,
-  char_width: u32,
-  char_height: u32,
+  char_width: f64,        // size of a character, including the padding between lines, in logical units
+  char_height: f64,
   insertion_line: usize,  // Line that the caret is on
-  insertion_point: usize, // Character that the caret is on
-  caret_color: Color,
+  insertion_point: usize, // Byte index of the character that the caret is on
+  selection_anchor: Option<(usize, usize)>, // line and character where the selection starts
   lines: Vec<String>,
   top_line: usize,        // zero-based index of the first visible line
   tab_size: usize,        // Tab size, in characters
   line_locations: BTreeMap<u64, usize>,  // key is Y coordinate of line, value is line's index
   modified: bool,
+  undo_stack: UndoStack<(usize, usize)>, // locations are line indexes and byte indexes within the line
 }
 
 impl MultiLineEdit {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     tab_size: usize
   ) -> Self {
 
     // Load the font
-    let mut char_width = 20;
-    let mut char_height = 18;
-    let font = match TextFont::new("../resources/FreeMonoBold.ttf", 14.0) {
+    let mut char_width = 20.0;
+    let mut char_height = 18.0;
+    let font = match TextFont::new_default() {
       Ok(font) => {
         let (bounds_width, _bounds_height) = font.get_bounds("W", None);
-        char_width = bounds_width;
+        char_width = WindowUtils::to_logical(bounds_width as f64);
         let (_bounds_width, bounds_height) = font.get_bounds("Wy", None);
-        char_height = bounds_height + LINE_PADDING;  // add padding
+        char_height = WindowUtils::to_logical(bounds_height as f64) + LINE_PADDING as f64;  // add padding
         Some(font)
       },
       Err(_err) => None,
@@ -78,58 +84,109 @@ impl MultiLineEdit {
 
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("MultiLineEdit".to_string());
+    window_base.set_accepts_focus(true);
+    window_base.set_accepts_tab(true);     // Tab inserts a tab character
 
     // Create a single, empty line for a new file
     let mut lines: Vec<String> = Vec::new();
     lines.push("".to_string());
 
     // Create the instance
-    Self {
+    let mut inst = Self {
       window_base: window_base,
       font: font,
       char_width: char_width,
       char_height: char_height,
       insertion_line: 0,
       insertion_point: 0,
-      caret_color: Color::from_rgba8(64, 64, 255, 255),
+      selection_anchor: None,
       lines: lines,
       top_line: 0,
       tab_size: tab_size,
       line_locations: BTreeMap::new(),
       modified: false,
+      undo_stack: UndoStack::new(),
+    };
+
+    // Build the context menu
+    for (label, action) in [("Cut", EditAction::Cut), ("Copy", EditAction::Copy), ("Paste", EditAction::Paste)] {
+      let item = WindowUtils::create_edit_menu_item(
+            label,
+            action,
+            event_loop.clone(),
+            main_win_uuid,
+            inst.get_uuid()
+      );
+      inst.add_context_menu_item(Box::new(item));
     }
+
+    inst
   }
 
   fn calculate_visible_lines(&self) -> usize {
 
     let (_width, height) = self.window_base.get_drawing_size();
 
-    (height / self.char_height as f64) as usize
+    (height / self.char_height) as usize
+  }
+
+  /// Returns true if there is a change that can be redone
+  pub fn can_redo(&self) -> bool {
+    self.undo_stack.can_redo()
+  }
+
+  /// Returns true if there is a change that can be undone
+  pub fn can_undo(&self) -> bool {
+    self.undo_stack.can_undo()
   }
 
   fn delete_char_at_caret(&mut self) {
 
     // If the insertion point is past the end of the line,
     // append the next line to this one.
-    let mut current_line = self.lines[self.insertion_line].clone();
-    if self.insertion_point < current_line.len() {
-
-      // Delete this character
-      let mut part1 = current_line[0..self.insertion_point].to_string();
-      part1.push_str(&current_line[self.insertion_point + 1..]);
-      self.lines[self.insertion_line] = part1;
+    let caret = (self.insertion_line, self.insertion_point);
+    let line = &self.lines[self.insertion_line];
+    if self.insertion_point < line.len() {
+      let end = WindowUtils::get_next_grapheme_boundary(line, self.insertion_point);
+      self.edit_text(caret, (self.insertion_line, end), "", false);
     } else if self.insertion_line < self.lines.len() - 1 {
-
-      let text = self.lines[self.insertion_line + 1].clone();
-      current_line += text.as_str();
-      self.lines[self.insertion_line] = current_line;
-      self.lines.remove(self.insertion_line + 1);
+      self.edit_text(caret, (self.insertion_line + 1, 0), "", false);
     }
   }
 
+  // Deletes the selected text, if there is any, and moves the caret to where
+  // the selection started
+  //
+  // Returns true if text was deleted.
+  fn delete_selection(&mut self) -> bool {
+
+    let deleted = match self.get_selection_range() {
+
+      Some((start, end)) => {
+        self.selection_anchor = None;
+        self.edit_text(start, end, "", false);
+        true
+      },
+
+      None => false,
+    };
+
+    self.selection_anchor = None;
+
+    deleted
+  }
+
+  /// Disconnects a listener that was connected with one of the on_ methods,
+  /// returning false if it isn't connected
+  pub fn disconnect(&self, id: ConnectionId) -> bool {
+    self.window_base.disconnect(id)
+  }
+
   fn draw(&mut self) {
 
-    let (width, height) = self.window_base.get_drawing_size();
+    let (width, height) = self.window_base.get_physical_drawing_size();
+    let char_width = WindowUtils::to_physical(self.char_width) as f32;
+    let char_height = WindowUtils::to_physical(self.char_height) as f32;
 
     // Create the pixmap into which we will draw
     let mut pixmap = match Pixmap::new(width as u32, height as u32) {
@@ -141,7 +198,9 @@ impl MultiLineEdit {
     };
 
     // Fill the pixmap with the background color
-    pixmap.fill(self.window_base.get_background_color());
+    let theme = self.window_base.get_theme();
+    let bg_color = self.window_base.get_background_color();
+    pixmap.fill(bg_color);
 
     // Draw each visible line
     match &self.font {
@@ -150,11 +209,16 @@ impl MultiLineEdit {
 
         let visible_count = self.calculate_visible_lines();
 
+        // The scroll amount is in grapheme clusters
         let x_scroll = self.window_base.get_x_scroll();
 
         // Clear the Y coordinates map
         self.line_locations.clear();
 
+        let selection = self.get_selection_range();
+        let mut selection_paint = Paint::default();
+        selection_paint.set_color(theme.palette.selection);
+
         let mut y: i32 = 0;
         let mut line_index = self.top_line;
         let line_count = self.lines.len();
@@ -163,17 +227,13 @@ impl MultiLineEdit {
 
           // Get the size of this line's text
           let line = &self.lines[line_index];
-          let visible_line;
-          if line.len() > x_scroll as usize {
-            visible_line = &line[(x_scroll as usize)..];
-          } else {
-            visible_line = "";
-          }
+          let visible_line = &line[WindowUtils::get_grapheme_byte_index(line, x_scroll as usize)..];
 
           // Does this line contain the caret?
           let caret_index;
           if self.insertion_line == line_index {
-            caret_index = (self.insertion_point as i64) - (x_scroll as i64);
+            let caret_column = WindowUtils::get_grapheme_index(line, self.insertion_point);
+            caret_index = (caret_column as i64) - (x_scroll as i64);
 
             // Draw this line's text
             if 0 != visible_line.len() {    // Not a blank line
@@ -182,11 +242,11 @@ impl MultiLineEdit {
                 &mut pixmap,
                 0,
                 y,
-                Color::BLACK,
-                Color::WHITE,
+                theme.palette.text,
+                bg_color,
                 caret_index,
-                self.caret_color,
-                Some(self.char_width)
+                theme.palette.accent,
+                None
               );
             } else {
 
@@ -196,11 +256,11 @@ impl MultiLineEdit {
                 &mut pixmap,
                 0,
                 y,
-                Color::BLACK,
-                Color::WHITE,
+                theme.palette.text,
+                bg_color,
                 caret_index,
-                self.caret_color,
-                Some(self.char_width)
+                theme.palette.accent,
+                None
               );
             }
           } else {
@@ -212,19 +272,39 @@ impl MultiLineEdit {
               &mut pixmap,
               0,
               y,
-              Color::BLACK,
-              Color::WHITE,
+              theme.palette.text,
+              bg_color,
               caret_index,
-              self.caret_color,
-              Some(self.char_width)
+              theme.palette.accent,
+              None
             );
           }
 
+          // Highlight the selected part of the line. A line that the selection
+          // continues past includes one extra character for the line break.
+          if let Some(((start_line, start_col), (end_line, end_col))) = selection {
+            if start_line <= line_index && line_index <= end_line {
+
+              let layout = font.layout(visible_line, None);
+              let first_visible = x_scroll as usize;
+              let start = if line_index == start_line { WindowUtils::get_grapheme_index(line, start_col) } else { 0 };
+              let left = layout.get_caret_x(start.saturating_sub(first_visible));
+              let right = if line_index == end_line {
+                layout.get_caret_x(WindowUtils::get_grapheme_index(line, end_col).saturating_sub(first_visible))
+              } else {
+                layout.get_caret_x(layout.get_grapheme_count()) + char_width
+              };
+              if let Some(rect) = Rect::from_xywh(left, y as f32, right - left, char_height) {
+                pixmap.fill_rect(rect, &selection_paint, Transform::identity(), None);
+              }
+            }
+          }
+
           // Add this line's Y coordinate to the map
           self.line_locations.insert(y as u64, line_index);
 
           // Update the Y coordinate for the next line
-          y += self.char_height as i32;
+          y += char_height as i32;
           if y >= height as i32 {
             break;
           }
@@ -241,20 +321,155 @@ impl MultiLineEdit {
     self.window_base.set_pixmap(pixmap);
   }
 
+  // Draws the window and requests that it be redrawn within the main window
+  fn draw_and_request_redraw(&mut self) {
+
+    let x = self.window_base.get_x();
+    let y = self.window_base.get_y();
+    self.draw();
+    WindowUtils::request_redraw(
+          self.window_base.get_event_loop().clone(),
+          self.window_base.get_main_win_uuid(),
+          x,
+          y,
+          self.window_base.get_pixmap()
+    );
+  }
+
+  // Replaces the text between two locations, records the change so that it
+  // can be undone, and moves the caret to the end of the new text
+  //
+  // typing is true if the text was typed, so that it can be undone along with
+  // the text typed just before it.
+  fn edit_text(&mut self, start: (usize, usize), end: (usize, usize), text: &str, typing: bool) {
+
+    let edit = TextEdit {
+      start,
+      removed: self.get_text_range(start, end),
+      inserted: text.to_string(),
+      caret_before: (self.insertion_line, self.insertion_point),
+      caret_after: MultiLineEdit::get_end_location(start, text),
+    };
+
+    self.replace_text(start, end, text);
+    self.move_caret(edit.caret_after.0, edit.caret_after.1, false);
+    self.undo_stack.record(edit, typing);
+
+    self.modified = true;
+  }
+
   fn fire_caret_moved_event(&self) {
     WindowUtils::fire_user_event(
           self.window_base.get_event_loop(),
           UserEvent::CaretMoved(
                 self.window_base.get_uuid(),
                 self.insertion_line,
-                self.insertion_point
+                WindowUtils::get_grapheme_index(&self.lines[self.insertion_line], self.insertion_point)
           )
     );
   }
 
+  // Gets the byte index on another line that is in the same column as the caret
+  fn get_caret_column_on_line(&self, line_index: usize) -> usize {
+
+    let column = WindowUtils::get_grapheme_index(&self.lines[self.insertion_line], self.insertion_point);
+
+    WindowUtils::get_grapheme_byte_index(&self.lines[line_index], column)
+  }
+
+  // Gets the location of the end of text that starts at a location
+  fn get_end_location(start: (usize, usize), text: &str) -> (usize, usize) {
+
+    match text.rfind('\n') {
+      Some(index) => (start.0 + text.matches('\n').count(), text.len() - index - 1),
+      None => (start.0, start.1 + text.len()),
+    }
+  }
+
+  // Gets the line and character at a location in the main window
+  fn get_location_at(&self, mouse_x: f64, mouse_y: f64) -> (usize, usize) {
+
+    // Adjust the Y coordinate so that it is relative to the top
+    // of the window.
+    let adjusted_y = WindowUtils::to_physical(mouse_y - self.window_base.get_y()) as u64;
+
+    // Find the line index that is closest to the Y coordinate
+    let mut prev_index: usize = 0;
+    for (y_coord, line_index) in &self.line_locations {
+
+      // If this line's index is after the adjusted mouse location,
+      // stop looking.
+      if *y_coord > adjusted_y {
+        break;
+      }
+
+      // Save the index
+      prev_index = *line_index;
+    }
+
+    // Calculate the character that was clicked
+    let adjusted_x = WindowUtils::to_physical(mouse_x - self.window_base.get_x()).max(0.0);
+    let x_scroll = self.window_base.get_x_scroll() as usize;
+    let line = &self.lines[prev_index];
+    let char_index = match &self.font {
+      Some(font) => {
+        let visible_line = &line[WindowUtils::get_grapheme_byte_index(line, x_scroll)..];
+        font.layout(visible_line, None).get_grapheme_at(adjusted_x as f32) + x_scroll
+      },
+      None => x_scroll,
+    };
+
+    (prev_index, WindowUtils::get_grapheme_byte_index(line, char_index))
+  }
+
+  /// Gets the selected text, if there is any
+  ///
+  /// Lines are separated by a linefeed.
+  pub fn get_selected_text(&self) -> Option<String> {
+
+    self.get_selection_range().map(|(start, end)| self.get_text_range(start, end))
+  }
+
+  // Gets the start and end of the selection, in that order, as line and
+  // character indexes. Returns None if no text is selected.
+  fn get_selection_range(&self) -> Option<((usize, usize), (usize, usize))> {
+
+    let caret = (self.insertion_line, self.insertion_point);
+    match self.selection_anchor {
+      Some(anchor) if anchor < caret => Some((anchor, caret)),
+      Some(anchor) if anchor > caret => Some((caret, anchor)),
+      _ => None,
+    }
+  }
+
+  // Gets the text between two locations, with the lines separated by a linefeed
+  fn get_text_range(&self, start: (usize, usize), end: (usize, usize)) -> String {
+
+    if start.0 == end.0 {
+      return self.lines[start.0][start.1..end.1].to_string();
+    }
+
+    let mut text = self.lines[start.0][start.1..].to_string();
+    for line in &self.lines[start.0 + 1..end.0] {
+      text += "\n";
+      text += line;
+    }
+    text += "\n";
+    text += &self.lines[end.0][..end.1];
+
+    text
+  }
+
   // Inserts the appropriate number of spaces to represent a tab at the
-  // specified file location.
-  fn insert_tab(&mut self, line_index: usize, col_index: usize) {
+  // caret, replacing the selected text
+  fn insert_tab(&mut self) {
+
+    // The tab stop is calculated from where the selection starts
+    let (line_index, byte_index) = match self.get_selection_range() {
+      Some((start, _end)) => start,
+      None => (self.insertion_line, self.insertion_point),
+    };
+    let col_index = WindowUtils::get_grapheme_index(&self.lines[line_index], byte_index);
 
     // Determine the next tab stop after the specified location
     let tab_stop = ((col_index / self.tab_size) + 1) * self.tab_size;
@@ -263,30 +478,7 @@ impl MultiLineEdit {
     let spaces = " ".repeat(tab_stop - col_index);
 
     // Insert the spaces into the line
-    self.insert_text(line_index, col_index, &spaces, true, true);
-  }
-
-  fn insert_text(
-        &mut self,
-        line_index: usize,
-        col_index: usize,
-        text: &str,
-        move_caret: bool,
-        redraw: bool
-  ) {
-
-    // Insert the text at the specified file location
-    let mut line = self.lines[line_index].clone();
-    line.insert_str(col_index, text);
-    self.lines[line_index] = line;
-
-    if move_caret {
-      self.move_caret(
-            line_index,
-            col_index + text.len(),
-            redraw
-      );
-    }
+    self.replace_selection(&spaces, false);
   }
 
   /// Returns true if the text has been modified
@@ -313,23 +505,18 @@ impl MultiLineEdit {
     }
 
     // Save the character index. If the index is beyond the end of the
-    // line, adjust it.
-    if col > self.lines[self.insertion_line].len() {
-      self.insertion_point = self.lines[self.insertion_line].len();
-    } else {
-      self.insertion_point = col;
-    }
-
-    // Calculate the width of the window in characters
-    let width = self.window_base.get_width();
-    let win_char_width = width / self.char_width as f64;
+    // line, adjust it. If it is within a grapheme cluster, move it to the
+    // start of the next one.
+    let line = &self.lines[self.insertion_line];
+    let caret_column = WindowUtils::get_grapheme_index(line, col);
+    self.insertion_point = WindowUtils::get_grapheme_byte_index(line, caret_column);
 
     // If the caret is no longer visible, scroll the window so that it is.
-    let x_scroll = self.window_base.get_x_scroll();
-    if (self.insertion_point as f64) < x_scroll {
-      self.window_base.set_x_scroll(self.insertion_point as f64);
-    } else if (self.insertion_point as f64) + x_scroll > win_char_width {
-      self.window_base.set_x_scroll((self.insertion_point as f64) - win_char_width);
+    if let Some(font) = &self.font {
+      let width = WindowUtils::to_physical(self.window_base.get_width());
+      let x_scroll = self.window_base.get_x_scroll() as usize;
+      let first_visible = font.layout(line, None).get_scroll_for_caret(caret_column, x_scroll, width as f32);
+      self.window_base.set_x_scroll(first_visible as f64);
     }
     if scroll_vertically {
       self.set_y_scroll(self.top_line as f64);
@@ -389,9 +576,127 @@ impl MultiLineEdit {
     }
   }
 
+  /// Calls a function with the caret's line and character indexes each time the caret moves
+  pub fn on_caret_moved<F: Fn(usize, usize) + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      match event {
+        UserEvent::CaretMoved(_source, line_index, char_index) => {
+          listener(*line_index, *char_index);
+          true
+        },
+        _ => false,
+      }
+    }))
+  }
+
+  /// Redoes the last change that was undone
+  pub fn redo(&mut self) {
+
+    if let Some(edit) = self.undo_stack.redo() {
+      let end = MultiLineEdit::get_end_location(edit.start, &edit.removed);
+      self.replace_text(edit.start, end, &edit.inserted);
+      self.restore_caret(edit.caret_after);
+    }
+  }
+
+  // Replaces the selected text, or inserts at the caret if there isn't any
+  fn replace_selection(&mut self, text: &str, typing: bool) {
+
+    let caret = (self.insertion_line, self.insertion_point);
+    let (start, end) = self.get_selection_range().unwrap_or((caret, caret));
+    self.selection_anchor = None;
+    self.edit_text(start, end, text, typing);
+  }
+
+  // Replaces the text between two locations; text may contain several lines
+  fn replace_text(&mut self, start: (usize, usize), end: (usize, usize), text: &str) {
+
+    // Remove the text, saving what follows it
+    let remainder = self.lines[end.0][end.1..].to_string();
+    self.lines[start.0].truncate(start.1);
+    self.lines.drain(start.0 + 1..=end.0);
+
+    // The first line of the new text is appended to the start's line, and the
+    // others are inserted after it
+    let mut new_lines: Vec<String> = text.split('\n').map(|line| line.to_string()).collect();
+    let last_line = start.0 + new_lines.len() - 1;
+    let first = new_lines.remove(0);
+    self.lines[start.0].push_str(&first);
+    self.lines.splice(start.0 + 1..start.0 + 1, new_lines);
+
+    self.lines[last_line].push_str(&remainder);
+  }
+
+  // Moves the caret after a change was undone or redone, and redraws the window
+  fn restore_caret(&mut self, caret: (usize, usize)) {
+
+    self.selection_anchor = None;
+    self.move_caret(caret.0, caret.1, false);
+
+    // The text is unmodified if it is back to where it was saved
+    self.modified = !self.undo_stack.is_saved();
+
+    self.draw_and_request_redraw();
+  }
+
   /// Sets the modified flag
+  ///
+  /// Clearing the flag marks the current text as saved, so undoing or redoing
+  /// back to it also clears the flag.
   pub fn set_modified(&mut self, modified: bool) {
     self.modified = modified;
+    if !modified {
+      self.undo_stack.set_saved();
+    }
+  }
+
+  /// Selects the text between two locations, each of which is a line index and
+  /// the index of a character, or grapheme cluster, within that line
+  ///
+  /// The caret is moved to the end location. A character index beyond the end
+  /// of its line is moved to the end of the line.
+  ///
+  /// If a line index is not valid, the returned Result contains a string explaining why.
+  pub fn set_selection(
+        &mut self,
+        start_line: usize,
+        start_col: usize,
+        end_line: usize,
+        end_col: usize
+  ) -> Result<(), String> {
+
+    let line_count = self.lines.len();
+    if start_line >= line_count || end_line >= line_count {
+      return Err(format!("The text only has {line_count} lines"));
+    }
+
+    let start_line_text = &self.lines[start_line];
+    self.selection_anchor = Some((start_line, WindowUtils::get_grapheme_byte_index(start_line_text, start_col)));
+    let end_byte_index = WindowUtils::get_grapheme_byte_index(&self.lines[end_line], end_col);
+    self.move_caret(end_line, end_byte_index, true);
+
+    Ok(())
+  }
+
+  /// Undoes the last change
+  pub fn undo(&mut self) {
+
+    if let Some(edit) = self.undo_stack.undo() {
+      let end = MultiLineEdit::get_end_location(edit.start, &edit.inserted);
+      self.replace_text(edit.start, end, &edit.removed);
+      self.restore_caret(edit.caret_before);
+    }
+  }
+
+  // Starts a selection at the caret if extend is true and one has not been
+  // started yet; otherwise, removes the selection.
+  fn update_selection_anchor(&mut self, extend: bool) {
+
+    if !extend {
+      self.selection_anchor = None;
+    } else if self.selection_anchor.is_none() {
+      self.selection_anchor = Some((self.insertion_line, self.insertion_point));
+    }
   }
 }
 
@@ -428,6 +733,13 @@ impl ChildWindow for MultiLineEdit {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -442,10 +754,10 @@ impl ChildWindow for MultiLineEdit {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -486,6 +798,20 @@ impl ChildWindow for MultiLineEdit {
     );
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -534,6 +860,10 @@ impl ChildWindow for MultiLineEdit {
     (self.window_base.get_width(), height)    // Use the saved width, which is set in redraw()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -628,6 +958,8 @@ impl ChildWindow for MultiLineEdit {
   fn set_text(&mut self, text: String) {
 
     self.modified = false;
+    self.selection_anchor = None;
+    self.undo_stack.clear();
     
     let mut longest_line = 0;
 
@@ -637,8 +969,9 @@ impl ChildWindow for MultiLineEdit {
     // Save the lines
     for line in text.split('\n') {
       let line_str = line.to_string();
-      if line_str.len() > longest_line {
-        longest_line = line_str.len();
+      let line_length = WindowUtils::get_grapheme_count(&line_str);
+      if line_length > longest_line {
+        longest_line = line_length;
       }
       self.lines.push(line_str);
     }
@@ -647,8 +980,8 @@ impl ChildWindow for MultiLineEdit {
     let (pixel_width, pixel_height) = self.window_base.get_drawing_size();
 
     // Get the size of the window in characters
-    let char_width = (pixel_width / self.char_width as f64) as usize;
-    let char_height = (pixel_height / self.char_height as f64) as usize;
+    let char_width = (pixel_width / self.char_width) as usize;
+    let char_height = (pixel_height / self.char_height) as usize;
 
     // Use the length of the longest line and the width of the window
     // set the horizontal scroll range.
@@ -710,7 +1043,72 @@ impl ChildWindow for MultiLineEdit {
     }
   }
 
-  fn handle_keyboard_pressed_event(&mut self, event: KeyEvent) {
+  fn handle_edit_action(&mut self, action: EditAction) {
+
+    // Copy and Cut act on the selected text or, if there isn't any, on the
+    // line that contains the caret. A failed copy leaves the text as it was,
+    // and a failed paste does nothing.
+    let selected_text = self.get_selected_text();
+    match action {
+      EditAction::Copy => {
+        let _ = match selected_text {
+          Some(text) => Clipboard::set_text(&text),
+          None => Clipboard::set_text(&(self.lines[self.insertion_line].clone() + "\n")),
+        };
+        return;
+      },
+      EditAction::Cut => {
+        match selected_text {
+          Some(text) => {
+            if Clipboard::set_text(&text).is_err() {
+              return;
+            }
+            self.delete_selection();
+          },
+          None => {
+
+            // Remove the line along with one of the line breaks next to it
+            let line = self.insertion_line;
+            if Clipboard::set_text(&(self.lines[line].clone() + "\n")).is_err() {
+              return;
+            }
+            if line < self.lines.len() - 1 {
+              self.edit_text((line, 0), (line + 1, 0), "", false);
+            } else if line > 0 {
+              self.edit_text((line - 1, self.lines[line - 1].len()), (line, self.lines[line].len()), "", false);
+            } else {
+              self.edit_text((0, 0), (0, self.lines[0].len()), "", false);
+            }
+          },
+        }
+      },
+      EditAction::Paste => {
+        match Clipboard::get_text() {
+          Ok(Some(text)) => self.replace_selection(&text.replace('\r', ""), false),
+          Ok(None) | Err(_) => return,
+        }
+      },
+      EditAction::Undo => {
+        self.undo();
+        return;
+      },
+      EditAction::Redo => {
+        self.redo();
+        return;
+      },
+    }
+
+    // Redraw this window's contents within the main window
+    self.draw_and_request_redraw();
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, event: KeyboardEvent, modifiers: ModifiersState) {
+
+    // Ctrl+C, Ctrl+X, and Ctrl+V use the clipboard, and Ctrl+Z and Ctrl+Y undo and redo
+    if let Some(action) = WindowUtils::get_edit_action(&event, modifiers) {
+      self.handle_edit_action(action);
+      return;
+    }
 
     if self.lines.len() > 0 {
 
@@ -721,46 +1119,61 @@ impl ChildWindow for MultiLineEdit {
         return;
       }
 
+      // Holding Shift down while moving the caret selects text
+      let previous_selection = self.get_selection_range();
+      let extend_selection = modifiers.shift_key();
+
       let mut processed = false;
       match event.logical_key {
 
         // Caret movement
         Key::Named(NamedKey::ArrowLeft) => {
+          self.update_selection_anchor(extend_selection);
           if self.insertion_point > 0 {
-            self.move_caret(self.insertion_line, self.insertion_point - 1, true);
+            let line = &self.lines[self.insertion_line];
+            let previous = WindowUtils::get_previous_grapheme_boundary(line, self.insertion_point);
+            self.move_caret(self.insertion_line, previous, true);
           }
 
           processed = true;
         },
         Key::Named(NamedKey::ArrowRight) => {
+          self.update_selection_anchor(extend_selection);
           let line = &self.lines[self.insertion_line];
           if self.insertion_point <= line.len() {
-            self.move_caret(self.insertion_line, self.insertion_point + 1, true);
+            let next = WindowUtils::get_next_grapheme_boundary(line, self.insertion_point);
+            self.move_caret(self.insertion_line, next, true);
           }
 
           processed = true;
         },
         Key::Named(NamedKey::ArrowUp) => {
+          self.update_selection_anchor(extend_selection);
           if self.insertion_line > 0 {
 
-            // Move the caret to the previous line
-            self.move_caret(self.insertion_line - 1, self.insertion_point, true);
+            // Move the caret to the same column on the previous line
+            let col = self.get_caret_column_on_line(self.insertion_line - 1);
+            self.move_caret(self.insertion_line - 1, col, true);
           }
 
           processed = true;
         },
         Key::Named(NamedKey::ArrowDown) => {
 
+          self.update_selection_anchor(extend_selection);
           if self.insertion_line < self.lines.len() - 1 {
 
-            // Move the caret to the next line
-            self.move_caret(self.insertion_line + 1, self.insertion_point, true);
+            // Move the caret to the same column on the next line
+            let col = self.get_caret_column_on_line(self.insertion_line + 1);
+            self.move_caret(self.insertion_line + 1, col, true);
           }
 
           processed = true;
         },
         Key::Named(NamedKey::Home) => {
 
+          self.update_selection_anchor(extend_selection);
+
           // Move the insertion point to the beginnning of the text
           self.move_caret(self.insertion_line, 0, true);
 
@@ -768,6 +1181,8 @@ impl ChildWindow for MultiLineEdit {
         },
         Key::Named(NamedKey::End) => {
 
+          self.update_selection_anchor(extend_selection);
+
           // Move the insertion point to the end of the text
           let line = &self.lines[self.insertion_line];
           self.move_caret(self.insertion_line, line.len(), true);
@@ -778,28 +1193,28 @@ impl ChildWindow for MultiLineEdit {
         // Editing keys
         Key::Named(NamedKey::Backspace) => {
 
+          // If text is selected, only it is deleted
+          if self.delete_selection() {
+            text_modified = true;
+
           // If the insertion point is already at the beginning of the file,
           // there is nothing to do.
-          if self.insertion_line != 0 || self.insertion_point != 0 {
+          } else if self.insertion_line != 0 || self.insertion_point != 0 {
 
-            // Move the insertion point back one character
+            // Delete the character before the caret
+            let caret = (self.insertion_line, self.insertion_point);
             if self.insertion_point == 0 {  // Caret is at the beginning of the line
 
-              // Move to the end of the previous line
-              self.move_caret(
-                    self.insertion_line - 1,
-                    self.lines[self.insertion_line - 1].len(),
-                    false
-              );
+              // Join this line to the end of the previous line
+              let previous_end = (self.insertion_line - 1, self.lines[self.insertion_line - 1].len());
+              self.edit_text(previous_end, caret, "", false);
             } else {
-              self.move_caret(self.insertion_line, self.insertion_point - 1, false);
+              let line = &self.lines[self.insertion_line];
+              let previous = WindowUtils::get_previous_grapheme_boundary(line, self.insertion_point);
+              self.edit_text((self.insertion_line, previous), caret, "", false);
             }
 
-            // Delete this character
-            self.delete_char_at_caret();
-
-            // Text was modified after the caret was moved, so set the flag
-            // so that the text will be redrawn.
+            // Set the flag so that the text will be redrawn
             text_modified = true;
           }
 
@@ -807,7 +1222,10 @@ impl ChildWindow for MultiLineEdit {
         },
         Key::Named(NamedKey::Delete) => {
 
-          self.delete_char_at_caret();
+          // If text is selected, only it is deleted
+          if !self.delete_selection() {
+            self.delete_char_at_caret();
+          }
 
           // Text was modified but the caret was not moved, so set the flag
           // so that the text will be redrawn.
@@ -817,17 +1235,11 @@ impl ChildWindow for MultiLineEdit {
         },
         Key::Named(NamedKey::Enter) => {
 
-          // Move the text from the caret position on the current line to a new line
-          let mut current_line = self.lines[self.insertion_line].clone();
-          let newline = current_line[self.insertion_point..].to_string();
-          current_line = current_line[0..self.insertion_point].to_string();
-          self.lines[self.insertion_line] = current_line;
-
-          // Insert the new line after the current one
-          self.lines.insert(self.insertion_line + 1, newline);
+          // Move the text from the caret position on the current line to a new
+          // line, replacing the selected text
+          self.replace_selection("\n", false);
 
-          // Move the caret to the beginning of the new line
-          self.move_caret(self.insertion_line + 1, 0, true);
+          text_modified = true;
 
           processed = true;
         },
@@ -844,16 +1256,15 @@ impl ChildWindow for MultiLineEdit {
 
             if 0 < event_text.len() && '\t' == event_text.chars().nth(0).unwrap() {
 
-              self.insert_tab(self.insertion_line, self.insertion_point);
+              self.insert_tab();
+            } else if event_text.chars().any(|character| character.is_control()) {
+
+              // Ignore control characters, such as those typed while Ctrl is held down
+              return;
             } else {
 
-              self.insert_text(
-                    self.insertion_line,
-                    self.insertion_point,
-                    &event_text,
-                    true,
-                    true
-              );
+              // The typed text replaces the selected text
+              self.replace_selection(&event_text, true);
             }
             
             text_modified = true;
@@ -862,57 +1273,32 @@ impl ChildWindow for MultiLineEdit {
         }
       }
 
-      if text_modified {
+      if text_modified || previous_selection != self.get_selection_range() {
 
-        self.modified = true;
-        
         // Redraw this window's contents within the main window
-        let x = self.window_base.get_x();
-        let y = self.window_base.get_y();
-        self.draw();
-        WindowUtils::request_redraw(
-              self.window_base.get_event_loop().clone(),
-              self.window_base.get_main_win_uuid(),
-              x,
-              y,
-              self.window_base.get_pixmap()
-        );
+        self.draw_and_request_redraw();
       }
     }
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-      mouse_x: f64, mouse_y: f64) {
-
-    if button == MouseButton::Left {
-
-      // Adjust the Y coordinate so that it is relative to the top
-      // of the window.
-      let adjusted_y = (mouse_y - self.window_base.get_y()) as u64;
+      mouse_x: f64, mouse_y: f64, modifiers: ModifiersState) {
 
-      // Find the line index that is closest to the Y coordinate
-      let mut prev_index: usize = 0;
-      for (y_coord, line_index) in &self.line_locations {
-
-        // If this line's index is after the adjusted mouse location,
-        // stop looking.
-        if *y_coord > adjusted_y {
-          break;
-        }
+    // Right mouse click shows the context menu
+    if MouseButton::Right == button {
+      self.window_base.handle_mouse_pressed(button, mouse_x, mouse_y);
+      return;
+    }
 
-        // Save the index
-        prev_index = *line_index;
-      }
+    if button == MouseButton::Left {
 
-      // Calculate the character that was clicked
-      let mut char_index = (mouse_x / self.char_width as f64) + self.window_base.get_x_scroll();
-      if char_index > self.lines[prev_index].len() as f64 {
-        char_index = self.lines[prev_index].len() as f64;
-      }
+      // Shift+click selects the text between the caret and the click
+      self.update_selection_anchor(modifiers.shift_key());
 
-      self.move_caret(prev_index, char_index as usize, true);
+      let (line, col) = self.get_location_at(mouse_x, mouse_y);
+      self.move_caret(line, col, true);
 
       self.fire_caret_moved_event();
     }
@@ -922,11 +1308,24 @@ impl ChildWindow for MultiLineEdit {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, main_win_x: f64, main_win_y: f64, _modifiers: ModifiersState) {
+
+    // Dragging selects the text between where it started and the mouse
+    if self.window_base.get_focused() && self.selection_anchor.is_some() {
+      let (line, col) = self.get_location_at(main_win_x, main_win_y);
+      self.move_caret(line, col, true);
+    }
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
+
+    // The selection starts at the caret, which the mouse press moved
+    self.update_selection_anchor(true);
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _x: f64, _y: f64) {
@@ -963,7 +1362,7 @@ impl ChildWindow for MultiLineEdit {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || new_height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || new_height != win_height {
 
       // Save the new size
       self.window_base.set_size(width, new_height);
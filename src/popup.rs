@@ -4,50 +4,48 @@ use winit::{
     MouseButton,
     WindowEvent,
   },
-  event_loop::{ActiveEventLoop, EventLoopProxy},
+  keyboard::ModifiersState,
   window::{ Window, WindowId},
 };
 
-use softbuffer::{
-  Context,
-  Surface,
-};
-
 use tiny_skia::{
-  Color,
   Pixmap,
-  PixmapPaint,
-  Transform,
 };
 
 use uuid::Uuid;
 
 use std:: {
   cell::RefCell,
-  num::NonZeroU32,
   rc::Rc,
+  time::Instant,
 };
-use winit::event::{KeyEvent, MouseScrollDelta, TouchPhase};
+use winit::event::{MouseScrollDelta, TouchPhase};
 use crate::BorderLayout;
 use crate::ChildWindow;
 use crate::child_window::{
   BorderLocation,
   ChildType,
+  KeyboardEvent,
   Layout,
   LayoutArgs,
   LayoutType,
   Orientation,
 };
+use crate::event_proxy::EventProxy;
+use crate::theme::Theme;
 use crate::UserEvent;
+use crate::window_surface::{Damage, WindowSurface};
 use crate::WindowUtils;
 
 /// A pop-up window
 pub struct PopUp {
   uuid: Uuid,
   main_win_uuid: Uuid,
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
-  window: Window,
+  event_loop_proxy: Rc<EventProxy>,
+  window: Rc<Window>,
+  surface: Option<WindowSurface>, // created when the window is first displayed
   pixmap: Pixmap,
+  damage: Damage,                 // regions of the pixmap that haven't been displayed
   width: f64,
   height: f64,
   layout: Box<dyn Layout>,
@@ -55,10 +53,12 @@ pub struct PopUp {
   cursor_x: f64,                  // Current mouse location within the window
   cursor_y: f64,
   focus_window: Option<Rc<RefCell<dyn ChildWindow>>>,
+  modifiers: ModifiersState,      // Modifier keys that are currently pressed
   dragging: bool,
   drag_start_win_x: f64,
   drag_start_win_y: f64,
   mouse_left_button_down: bool,
+  last_mouse_left_click: Option<(Instant, f64, f64)>,   // time and location of the previous left click
 }
 
 impl PopUp {
@@ -67,7 +67,7 @@ impl PopUp {
         uuid: Uuid,
         main_win_uuid: Uuid,
         window: Window,
-        event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+        event_loop_proxy: Rc<EventProxy>,
         width: f64,
         height: f64
   ) -> PopUp {
@@ -76,12 +76,18 @@ impl PopUp {
     let mut layout = Box::new(BorderLayout::new(event_loop_proxy.clone(), uuid));
     layout.set_name("PopUp's BorderLayout".to_string());
 
+    // Record the layout under this pop-up, so that its windows are removed
+    // from the registry when the pop-up closes
+    event_loop_proxy.get_registry().borrow_mut().add_layout(uuid, layout.get_uuid());
+
     Self {
       uuid: uuid,
       main_win_uuid: main_win_uuid,
       event_loop_proxy: event_loop_proxy,
-      window: window,
-      pixmap: Pixmap::new(width as u32, height as u32).unwrap(),
+      window: Rc::new(window),
+      surface: None,
+      pixmap: Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32).unwrap(),
+      damage: Damage::default(),
       width: width,
       height: height,
       layout: layout,
@@ -89,37 +95,47 @@ impl PopUp {
       cursor_x: 0.0,
       cursor_y: 0.0,
       focus_window: None,
+      modifiers: ModifiersState::empty(),
       dragging: false,
       drag_start_win_x: 0.0,
       drag_start_win_y: 0.0,
       mouse_left_button_down: false,
+      last_mouse_left_click: None,
     }
   }
 
-  // Displays the Pixmap onto the screen
-  fn display_pixmap(&mut self) {
+  // Gives the input focus to a window, removing it from the window that had it
+  fn change_focus(&mut self, window: Rc<RefCell<dyn ChildWindow>>) {
 
-    // Get the size of the window
-    let width = self.pixmap.width();
-    let height = self.pixmap.height();
-
-    // Create the drawing surface
-    let context = (Context::new(&self.window)).unwrap();
-    let mut surface = (Surface::new(&context, &self.window)).unwrap();
-    surface
-        .resize(NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap())
-        .unwrap();
-
-    // Copy the pixmap onto the drawing surface
-    let mut buffer = surface.buffer_mut().unwrap();
-    for index in 0..(width * height) as usize {
-      buffer[index] =
-          (self.pixmap.data()[index * 4 + 2] as u32) |
-          ((self.pixmap.data()[index * 4 + 1] as u32) << 8) |
-          ((self.pixmap.data()[index * 4] as u32) << 16);
+    if let Some(focus_window) = &self.focus_window {
+      focus_window.borrow_mut().set_focused(false);
     }
 
-    buffer.present().unwrap();
+    window.borrow_mut().set_focused(true);
+    self.focus_window = Some(window);
+  }
+
+  /// Displays the regions of the Pixmap that have changed onto the screen
+  ///
+  /// The MainApp calls this when it draws the next frame.
+  pub(crate) fn display_pixmap(&mut self) {
+
+    // Create the drawing surface, which is kept until the window closes
+    if self.surface.is_none() {
+      match WindowSurface::new(self.window.clone()) {
+        Ok(surface) => self.surface = Some(surface),
+        Err(err) => {
+          println!("In PopUp::display_pixmap(), {err}");
+          return;
+        },
+      }
+    }
+
+    if let Some(surface) = &mut self.surface {
+      if let Err(err) = surface.present(&self.pixmap, self.damage.take()) {
+        println!("In PopUp::display_pixmap(), {err}");
+      }
+    }
   }
 
   pub fn get_size(&self) -> (f64, f64) {
@@ -135,27 +151,30 @@ impl PopUp {
   }
 
   /// Processes keyboard events when this window has focus
-  pub fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  pub fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
   }
-  pub fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  pub fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   /// Processes mouse click events when this window has focus
   ///
   /// mouse_x and mouse_y are relative to the main window
   pub fn handle_mouse_pressed(&mut self, _button: MouseButton,
-                          _mouse_x: f64, _mouse_y: f64) {
+                          _mouse_x: f64, _mouse_y: f64, _modifiers: ModifiersState) {
   }
   pub fn handle_mouse_released(&mut self, _button: MouseButton,
                            _mouse_x: f64, _mouse_y: f64) {
   }
+  pub fn handle_mouse_double_click(&mut self, _button: MouseButton,
+                           _mouse_x: f64, _mouse_y: f64) {
+  }
 
   /// Processes mouse drag event
-  pub fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  pub fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  pub fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  pub fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  pub fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  pub fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   /// Processes mouse movement events when this window has focus
@@ -166,6 +185,25 @@ impl PopUp {
   pub fn handle_mouse_wheel(&mut self, _delta: MouseScrollDelta, _phase: TouchPhase) {
   }
 
+  /// Returns true if regions of the pixmap have changed since it was last displayed
+  pub(crate) fn is_damaged(&self) -> bool {
+    !self.damage.is_empty()
+  }
+
+  // Moves the input focus to the next window in the focus chain, or the
+  // previous one if backwards is true
+  fn move_focus(&mut self, backwards: bool) {
+
+    let window = WindowUtils::get_next_focus_window(
+          self.layout.get_focus_chain(),
+          &self.focus_window,
+          backwards
+    );
+    if let Some(window) = window {
+      self.change_focus(window);
+    }
+  }
+
   pub fn process_event(&mut self, event: WindowEvent) {
 
     match event {
@@ -182,9 +220,9 @@ impl PopUp {
 
       WindowEvent::CursorMoved{position, ..} => {
 
-        // Save the new position, which is relative to the main window
-        self.cursor_x = position.x;
-        self.cursor_y = position.y;
+        // Save the new position, which is relative to the main window, in logical units
+        self.cursor_x = WindowUtils::to_logical(position.x);
+        self.cursor_y = WindowUtils::to_logical(position.y);
 
         // Pass the event to the window with focus
         match &self.focus_window {
@@ -192,7 +230,7 @@ impl PopUp {
           Some(window) => {
             let mut child_ref = window.borrow_mut();
             if self.mouse_left_button_down {
-              child_ref.handle_mouse_drag(position.x, position.y);
+              child_ref.handle_mouse_drag(position.x, position.y, self.modifiers);
             } else {
               child_ref.handle_mouse_movement(position.x, position.y);
             }
@@ -203,15 +241,24 @@ impl PopUp {
 
       WindowEvent::KeyboardInput{device_id: _, event, is_synthetic: _} => {
 
+        let event = KeyboardEvent::from(event);
         let key_pressed = event.state == ElementState::Pressed;
 
+        // Tab and Shift+Tab move the focus between the windows
+        if WindowUtils::is_focus_traversal(&event, self.modifiers, &self.focus_window) {
+          if key_pressed {
+            self.move_focus(self.modifiers.shift_key());
+          }
+          return;
+        }
+
         match &self.focus_window {
 
           Some(window) => {
             let mut child_ref = window.borrow_mut();
             child_ref.set_focused(true);
             if key_pressed {
-              child_ref.handle_keyboard_pressed_event(event);
+              child_ref.handle_keyboard_pressed_event(event, self.modifiers);
             } else {
               child_ref.handle_keyboard_released_event(event);
             }
@@ -220,6 +267,10 @@ impl PopUp {
         };
       },
 
+      WindowEvent::ModifiersChanged(modifiers) => {
+        self.modifiers = modifiers.state();
+      },
+
       WindowEvent::MouseInput{state, button, ..} => {
 
         match state {
@@ -228,10 +279,25 @@ impl PopUp {
 
             self.mouse_left_button_down = true;
 
+            let mut double_click = false;
             if MouseButton::Left == button {
               self.dragging = true;
               self.drag_start_win_x = self.cursor_x;
               self.drag_start_win_y = self.cursor_y;
+
+              // Is this the second part of a double click?
+              double_click = WindowUtils::is_double_click(
+                    self.last_mouse_left_click,
+                    self.cursor_x,
+                    self.cursor_y
+              );
+              if double_click {
+
+                // A third click starts a new double click
+                self.last_mouse_left_click = None;
+              } else {
+                self.last_mouse_left_click = Some((Instant::now(), self.cursor_x, self.cursor_y));
+              }
             }
 
             // Remove focus from the child that currently has it
@@ -249,8 +315,11 @@ impl PopUp {
               Some(window) => {
                 let mut child_ref = window.borrow_mut();
                 child_ref.set_focused(true);
-                child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y);
-                child_ref.handle_mouse_drag_start(self.cursor_x, self.cursor_y);
+                child_ref.handle_mouse_pressed(button, self.cursor_x, self.cursor_y, self.modifiers);
+                child_ref.handle_mouse_drag_start(self.cursor_x, self.cursor_y, self.modifiers);
+                if double_click {
+                  child_ref.handle_mouse_double_click(button, self.cursor_x, self.cursor_y);
+                }
               },
               None => {},
             }
@@ -270,8 +339,8 @@ impl PopUp {
 
                   // Pass the events to the window with focus
                   let mut child_ref = window.borrow_mut();
-                  child_ref.handle_mouse_drag(self.cursor_x, self.cursor_y);
-                  child_ref.handle_mouse_drag_end(self.cursor_x, self.cursor_y);
+                  child_ref.handle_mouse_drag(self.cursor_x, self.cursor_y, self.modifiers);
+                  child_ref.handle_mouse_drag_end(self.cursor_x, self.cursor_y, self.modifiers);
 
                   return;
                 },
@@ -326,14 +395,14 @@ impl PopUp {
       WindowEvent::Resized(size) => {
 
         // If the size of the window has changed, create a new Pixmap for drawing.
-        if size.width as f64 != self.width || size.height as f64 != self.height {
+        if size.width != self.pixmap.width() || size.height != self.pixmap.height() {
 
-          // Save the new size
-          self.width = size.width as f64;
-          self.height = size.height as f64;
+          // Save the new size, which is in physical pixels
+          self.width = WindowUtils::to_logical(size.width as f64);
+          self.height = WindowUtils::to_logical(size.height as f64);
 
           // Create the pixmap into which we will draw
-          self.pixmap = Pixmap::new(self.width as u32, self.height as u32).unwrap();
+          self.pixmap = Pixmap::new(size.width, size.height).unwrap();
 
 
           // On intial start-up, the main window will receive this event
@@ -350,27 +419,29 @@ impl PopUp {
     }
   }
 
-  pub fn process_user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+  pub fn process_user_event(&mut self, event: UserEvent) {
 
     match event {
 
-      UserEvent::Redraw(_main_win_uuid, x, y, pixmap) => {
+      UserEvent::Edit(_main_win_uuid, window_uuid, action) => {
 
-        // Update the MainApp's pixmap
-        self.pixmap.draw_pixmap(
-            x as i32,
-            y as i32,
-            pixmap.as_ref(),
-            &PixmapPaint::default(),
-            Transform::identity(),
-            None,
-        );
+        match self.layout.get_child_with_id(window_uuid) {
+
+          Some(window) => window.borrow_mut().handle_edit_action(action),
 
-        // Display the updated pixmap
-        self.display_pixmap();
+          None => println!("Cannot perform {action:?}; there is no window with the ID {window_uuid}"),
+        }
+      },
+
+      UserEvent::Redraw(_main_win_uuid, x, y, pixmap) => {
+
+        // Update the pixmap. The region is displayed with the others that
+        // change before the next frame.
+        WindowUtils::draw_child_pixmap(&mut self.pixmap, x, y, &pixmap);
+        self.damage.add_child_pixmap(x, y, &pixmap);
       },
 
-      UserEvent::UpdateScroller(_main_win_uuid, scroll_layout_uuid) => {
+      UserEvent::UpdateScroller(main_win_uuid, scroll_layout_uuid) => {
 
         // Tell the ScrollLayout to redraw
         match self.layout.get_layout_with_id(scroll_layout_uuid) {
@@ -380,7 +451,12 @@ impl PopUp {
             let mut layout_ref = layout.borrow_mut();
             let (x, y) = layout_ref.get_location();
             let (width, height) = layout_ref.get_size();
-            layout_ref.layout(x, y, width, height);
+            layout_ref.set_dirty(true);
+            let pixmap = layout_ref.layout(x, y, width, height);
+
+            // Draw the layout after the redraws that its windows requested
+            // before it was scrolled
+            self.event_loop_proxy.queue_redraw(main_win_uuid, x, y, pixmap);
           },
 
           None => {},
@@ -391,31 +467,29 @@ impl PopUp {
     }
   }
 
-  fn redraw(&mut self) {
+  pub(crate) fn redraw(&mut self) {
+
+    // The windows' pending redraws are included in the new pixmap
+    self.event_loop_proxy.discard_redraws(self.uuid);
 
     // Get the size of the window
     let (width, height) = self.get_size();
 
+    // If the scale factor has changed, create a pixmap of the new size
+    let (pixmap_width, pixmap_height) = (WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32);
+    if pixmap_width != self.pixmap.width() || pixmap_height != self.pixmap.height() {
+      self.pixmap = Pixmap::new(pixmap_width, pixmap_height).unwrap();
+    }
+
     // Fill the pixmap with the background color
-    self.pixmap.fill(Color::WHITE);
+    self.pixmap.fill(Theme::current().palette.background);
 
     // Get the layout's pixmap
     let layout_pixmap = self.layout.layout(0.0, 0.0, width as f64, height as f64);
 
-    let paint = PixmapPaint::default();
-
     // Copy the layout's pixmap image onto the full pixmap
-    self.pixmap.draw_pixmap(
-        0,
-        0,
-        layout_pixmap.as_ref(),
-        &paint,
-        Transform::identity(),
-        None,
-    );
-
-    // Display the pixmap
-    self.display_pixmap();
+    WindowUtils::draw_child_pixmap(&mut self.pixmap, 0.0, 0.0, &layout_pixmap);
+    self.damage.add_pixmap(&self.pixmap);
   }
 
   pub fn scroll_value_changed(
@@ -489,6 +563,20 @@ impl PopUp {
     }
   }
 
+  /// Gives the input focus to the window with the specified ID
+  ///
+  /// If an error occurred, the returned Result contains a string explaining why it failed.
+  pub fn set_focus(&mut self, uuid: Uuid) -> Result<(), String> {
+
+    match self.layout.get_child_with_id(uuid) {
+      Some(window) => {
+        self.change_focus(window);
+        Ok(())
+      },
+      None => Err(format!("There is no window with the ID {uuid}")),
+    }
+  }
+
   pub fn set_title(&self, title: &str) {
     self.window.set_title(title);
   }
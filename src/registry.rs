@@ -0,0 +1,215 @@
+use uuid::Uuid;
+
+use std::{
+  cell::{Ref, RefCell, RefMut},
+  fmt,
+  marker::PhantomData,
+  rc::{Rc, Weak},
+};
+
+use crate::ChildType;
+use crate::ChildWindow;
+use crate::child_window::AsAny;
+
+// Window that has been added to a layout
+struct RegisteredWindow {
+  uuid: Uuid,
+  parent: Uuid,               // layout, tab, or pop-up that contains the window
+  window: Weak<RefCell<dyn ChildWindow>>,
+}
+
+/// Windows that have been added to the layouts, so that they can be found by
+/// their IDs or names
+///
+/// The layouts add their children to the registry, and remove them when they
+/// are removed from the layout. Layouts are only recorded by their IDs, and
+/// their parents, so that removing a layout, tab, or pop-up also removes the
+/// windows that it contains.
+///
+/// The methods that forget windows return their IDs, so that EventProxy can
+/// disconnect their listeners.
+#[derive(Default)]
+pub(crate) struct Registry {
+  windows: Vec<RegisteredWindow>,
+  layouts: Vec<(Uuid, Uuid)>,   // IDs of each layout and its parent
+}
+
+impl Registry {
+
+  /// Adds a window or layout that has been added to a parent
+  ///
+  /// Returns the IDs of the windows that were forgotten because they have been dropped.
+  pub fn add_child(&mut self, parent: Uuid, child: &ChildType) -> Vec<Uuid> {
+    match child {
+      ChildType::Window(window) => self.add_window(parent, window),
+      ChildType::Layout(layout) => {
+        self.add_layout(parent, layout.borrow().get_uuid());
+        Vec::new()
+      },
+    }
+  }
+
+  /// Adds a layout that has been added to a parent
+  pub fn add_layout(&mut self, parent: Uuid, uuid: Uuid) {
+    self.layouts.retain(|(layout, _parent)| *layout != uuid);
+    self.layouts.push((uuid, parent));
+  }
+
+  /// Adds a window that has been added to a parent
+  ///
+  /// Returns the IDs of the windows that were forgotten because they have been dropped.
+  pub fn add_window(&mut self, parent: Uuid, window: &Rc<RefCell<dyn ChildWindow>>) -> Vec<Uuid> {
+
+    // Also forget the windows that have been dropped
+    let uuid = window.borrow().get_uuid();
+    let dropped = self.take_windows(|registered| registered.window.strong_count() == 0);
+    self.windows.retain(|registered| registered.uuid != uuid);
+    self.windows.push(RegisteredWindow {
+      uuid,
+      parent,
+      window: Rc::downgrade(window),
+    });
+
+    dropped
+  }
+
+  /// Gets the window with the specified ID
+  pub fn get_window_with_id(&self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>> {
+    self.windows.iter()
+      .find(|registered| registered.uuid == uuid)
+      .and_then(|registered| registered.window.upgrade())
+  }
+
+  /// Gets the windows with the specified name, in the order they were added
+  ///
+  /// A window that is currently borrowed mutably is skipped.
+  pub fn get_windows_with_name(&self, name: &str) -> Vec<Rc<RefCell<dyn ChildWindow>>> {
+    self.windows.iter()
+      .filter_map(|registered| registered.window.upgrade())
+      .filter(|window| window.try_borrow().is_ok_and(|window_ref| window_ref.get_name() == name))
+      .collect()
+  }
+
+  /// Removes a window or layout, along with everything that it contains
+  ///
+  /// uuid can also be the ID of a tab or pop-up. Returns the IDs of the
+  /// windows that were removed.
+  pub fn remove(&mut self, uuid: Uuid) -> Vec<Uuid> {
+
+    let mut removed = self.remove_children(uuid);
+    removed.append(&mut self.take_windows(|registered| registered.uuid == uuid));
+    self.layouts.retain(|(layout, _parent)| *layout != uuid);
+
+    removed
+  }
+
+  /// Removes the windows and layouts that a parent contains
+  ///
+  /// Returns the IDs of the windows that were removed.
+  pub fn remove_children(&mut self, parent: Uuid) -> Vec<Uuid> {
+
+    let mut removed = self.take_windows(|registered| registered.parent == parent);
+
+    let layouts: Vec<Uuid> = self.layouts.iter()
+      .filter(|(_layout, layout_parent)| *layout_parent == parent)
+      .map(|(layout, _parent)| *layout)
+      .collect();
+    for layout in layouts {
+      removed.append(&mut self.remove(layout));
+    }
+
+    removed
+  }
+
+  // Removes the windows that match a predicate, returning their IDs
+  fn take_windows<P: Fn(&RegisteredWindow) -> bool>(&mut self, predicate: P) -> Vec<Uuid> {
+
+    let mut taken = Vec::new();
+    self.windows.retain(|registered| {
+      if predicate(registered) {
+        taken.push(registered.uuid);
+        false
+      } else {
+        true
+      }
+    });
+
+    taken
+  }
+
+  /// Changes the ID of a window or layout whose UUID has changed
+  pub fn set_uuid(&mut self, old_uuid: Uuid, new_uuid: Uuid) {
+
+    for registered in self.windows.iter_mut() {
+      if registered.uuid == old_uuid {
+        registered.uuid = new_uuid;
+      }
+      if registered.parent == old_uuid {
+        registered.parent = new_uuid;
+      }
+    }
+
+    for (layout, parent) in self.layouts.iter_mut() {
+      if *layout == old_uuid {
+        *layout = new_uuid;
+      }
+      if *parent == old_uuid {
+        *parent = new_uuid;
+      }
+    }
+  }
+}
+
+/// Window of type T that was found with MainApp::find() or MainApp::find_by_id()
+///
+/// It is borrowed like a RefCell, and gives access to the window as its own
+/// type, such as a LineEdit, instead of as a ChildWindow.
+pub struct WindowRef<T> {
+  window: Rc<RefCell<dyn ChildWindow>>,
+  window_type: PhantomData<T>,
+}
+
+impl<T: ChildWindow> WindowRef<T> {
+
+  /// Creates the reference if the window is of type T
+  pub(crate) fn new(window: Rc<RefCell<dyn ChildWindow>>) -> Option<Self> {
+
+    let is_type = window.try_borrow().is_ok_and(|window_ref| AsAny::as_any(&*window_ref).is::<T>());
+    if is_type {
+      Some(Self {
+        window,
+        window_type: PhantomData,
+      })
+    } else {
+      None
+    }
+  }
+
+  /// Immutably borrows the window
+  ///
+  /// Panics if the window is currently borrowed mutably.
+  pub fn borrow(&self) -> Ref<'_, T> {
+    Ref::map(self.window.borrow(), |window| AsAny::as_any(window).downcast_ref::<T>().unwrap())
+  }
+
+  /// Mutably borrows the window
+  ///
+  /// Panics if the window is currently borrowed.
+  pub fn borrow_mut(&self) -> RefMut<'_, T> {
+    RefMut::map(self.window.borrow_mut(), |window| AsAny::as_any_mut(window).downcast_mut::<T>().unwrap())
+  }
+
+  /// Gets the window as a ChildWindow, such as to pass it to a layout
+  pub fn get_window(&self) -> Rc<RefCell<dyn ChildWindow>> {
+    self.window.clone()
+  }
+}
+
+impl<T> fmt::Debug for WindowRef<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.window.try_borrow() {
+      Ok(window) => write!(f, "WindowRef({window:?})"),
+      Err(_err) => write!(f, "WindowRef(<borrowed>)"),
+    }
+  }
+}
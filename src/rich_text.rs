@@ -0,0 +1,262 @@
+use tiny_skia::Color;
+
+use std::{
+  fmt::Debug,
+  ops::Range,
+  rc::Rc,
+};
+
+use crate::font_registry::{FONT_WEIGHT_BOLD, FONT_WEIGHT_NORMAL};
+use crate::text_font::TextFont;
+
+/// Appearance of a run of text
+///
+/// The fields that are None use the values of the window that draws the text.
+/// Bold and italic text is drawn by emboldening and slanting the run's font.
+#[derive(Clone, Debug)]
+pub struct TextStyle {
+  pub font: Option<Rc<TextFont>>,
+  pub weight: u16,                 // FONT_WEIGHT_BOLD or more is drawn bold
+  pub italic: bool,
+  pub color: Option<Color>,
+  pub background: Option<Color>,
+  pub underline: bool,
+  pub strikethrough: bool,
+}
+
+impl TextStyle {
+
+  /// Returns true if the text is drawn bold
+  pub fn is_bold(&self) -> bool {
+    self.weight >= FONT_WEIGHT_BOLD
+  }
+}
+
+impl Default for TextStyle {
+  fn default() -> Self {
+    Self {
+      font: None,
+      weight: FONT_WEIGHT_NORMAL,
+      italic: false,
+      color: None,
+      background: None,
+      underline: false,
+      strikethrough: false,
+    }
+  }
+}
+
+impl PartialEq for TextStyle {
+  fn eq(&self, other: &Self) -> bool {
+
+    let same_font = match (&self.font, &other.font) {
+      (Some(font), Some(other_font)) => Rc::ptr_eq(font, other_font),
+      (None, None) => true,
+      _ => false,
+    };
+
+    same_font && self.weight == other.weight && self.italic == other.italic &&
+          self.color == other.color && self.background == other.background &&
+          self.underline == other.underline && self.strikethrough == other.strikethrough
+  }
+}
+
+/// Text that is drawn with a single style
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+  pub text: String,
+  pub style: TextStyle,
+}
+
+/// Text made of runs that each have their own style, such as a word in bold
+/// or a warning in red within a message
+///
+/// It is drawn by TextFont::draw_rich_text(), and it can be created from
+/// markup with parse_markup().
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RichText {
+  runs: Vec<TextRun>,
+}
+
+impl RichText {
+
+  pub fn new() -> Self {
+    Self {
+      runs: Vec::new(),
+    }
+  }
+
+  /// Appends text with a style
+  pub fn append(&mut self, text: &str, style: TextStyle) {
+
+    if text.is_empty() {
+      return;
+    }
+
+    // Text with the same style as the last run is added to it
+    if let Some(last) = self.runs.last_mut() {
+      if last.style == style {
+        last.text.push_str(text);
+        return;
+      }
+    }
+
+    self.runs.push(TextRun {
+      text: text.to_string(),
+      style,
+    });
+  }
+
+  /// Replaces the characters that have a meaning in markup, so that text can be included in it
+  pub fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+  }
+
+  /// Gets the runs of text
+  pub fn get_runs(&self) -> &Vec<TextRun> {
+    &self.runs
+  }
+
+  /// Gets the text without its styles
+  pub fn get_text(&self) -> String {
+    self.runs.iter().map(|run| run.text.as_str()).collect()
+  }
+
+  /// Creates rich text from markup
+  ///
+  /// The markup is text that contains the following tags, which can be nested:
+  ///   `<b>`bold`</b>`, `<i>`italic`</i>`, `<u>`underline`</u>`, `<s>`strikethrough`</s>`,
+  ///   `<color=#rrggbb>`colored text`</color>`, and `<bg=#rrggbb>`highlighted text`</bg>`.
+  /// The colors can also have an alpha component, as in #rrggbbaa. The
+  /// characters &lt;, &gt;, and &amp; are written as `&lt;`, `&gt;`, and `&amp;`.
+  ///
+  /// If the markup is not valid, the returned Result contains a string explaining why.
+  pub fn parse_markup(markup: &str) -> Result<RichText, String> {
+
+    let mut rich_text = RichText::new();
+
+    // Each open tag's name and the style that was used before it
+    let mut open_tags: Vec<(String, TextStyle)> = Vec::new();
+    let mut style = TextStyle::default();
+
+    let mut remaining = markup;
+    while !remaining.is_empty() {
+
+      // Add the text up to the next tag
+      let text_end = remaining.find('<').unwrap_or(remaining.len());
+      rich_text.append(&RichText::unescape(&remaining[..text_end])?, style.clone());
+      remaining = &remaining[text_end..];
+      if remaining.is_empty() {
+        break;
+      }
+
+      let tag_end = match remaining.find('>') {
+        Some(tag_end) => tag_end,
+        None => return Err(format!("The tag {remaining} is not closed")),
+      };
+      let tag = &remaining[1..tag_end];
+      remaining = &remaining[tag_end + 1..];
+
+      // A closing tag restores the style that was used before its opening tag
+      if let Some(name) = tag.strip_prefix('/') {
+        match open_tags.pop() {
+          Some((open_name, previous_style)) if open_name == name => style = previous_style,
+          Some((open_name, _)) => return Err(format!("</{name}> found where </{open_name}> was expected")),
+          None => return Err(format!("</{name}> does not have an opening tag")),
+        }
+        continue;
+      }
+
+      let (name, value) = match tag.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (tag, None),
+      };
+      open_tags.push((name.to_string(), style.clone()));
+      match (name, value) {
+        ("b", None) => style.weight = FONT_WEIGHT_BOLD,
+        ("i", None) => style.italic = true,
+        ("u", None) => style.underline = true,
+        ("s", None) => style.strikethrough = true,
+        ("color", Some(value)) => style.color = Some(RichText::parse_color(value)?),
+        ("bg", Some(value)) => style.background = Some(RichText::parse_color(value)?),
+        _ => return Err(format!("<{tag}> is not a valid tag")),
+      }
+    }
+
+    match open_tags.pop() {
+      Some((name, _)) => Err(format!("<{name}> is not closed")),
+      None => Ok(rich_text),
+    }
+  }
+
+  // Parses a color written as #rrggbb or #rrggbbaa
+  fn parse_color(value: &str) -> Result<Color, String> {
+
+    let invalid = || format!("{value} is not a valid color");
+
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+      return Err(invalid());
+    }
+
+    let mut components = [255u8; 4];
+    for (index, component) in components.iter_mut().enumerate().take(hex.len() / 2) {
+      *component = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+
+    Ok(Color::from_rgba8(components[0], components[1], components[2], components[3]))
+  }
+
+  /// Gets the part of the text between two byte indexes of get_text()
+  pub fn slice(&self, range: Range<usize>) -> RichText {
+
+    let mut slice = RichText::new();
+
+    let mut run_start = 0;
+    for run in &self.runs {
+      let run_end = run_start + run.text.len();
+      let start = range.start.clamp(run_start, run_end);
+      let end = range.end.clamp(run_start, run_end);
+      if start < end {
+        slice.append(&run.text[start - run_start..end - run_start], run.style.clone());
+      }
+      run_start = run_end;
+    }
+
+    slice
+  }
+
+  // Replaces &lt;, &gt;, and &amp; with the characters they represent
+  fn unescape(text: &str) -> Result<String, String> {
+
+    let mut unescaped = String::new();
+    let mut remaining = text;
+    while let Some(start) = remaining.find('&') {
+      unescaped.push_str(&remaining[..start]);
+      remaining = &remaining[start..];
+
+      let (ch, length) = if remaining.starts_with("&lt;") {
+        ('<', 4)
+      } else if remaining.starts_with("&gt;") {
+        ('>', 4)
+      } else if remaining.starts_with("&amp;") {
+        ('&', 5)
+      } else {
+        return Err(format!("{remaining} contains an & that is not written as &amp;"));
+      };
+      unescaped.push(ch);
+      remaining = &remaining[length..];
+    }
+    unescaped.push_str(remaining);
+
+    Ok(unescaped)
+  }
+}
+
+impl From<&str> for RichText {
+  fn from(text: &str) -> Self {
+    let mut rich_text = RichText::new();
+    rich_text.append(text, TextStyle::default());
+    rich_text
+  }
+}
@@ -6,13 +6,12 @@ use crate::child_window::{
   LayoutFill,
   LayoutType,
   Orientation,
-  UserEvent,
 };
+use crate::event_proxy::EventProxy;
 use crate::layout_base::LayoutBase;
+use crate::window_utils::WindowUtils;
 
-use winit::event_loop::EventLoopProxy;
-
-use tiny_skia::{Pixmap, PixmapPaint, Transform};
+use tiny_skia::Pixmap;
 
 use uuid::Uuid;
 
@@ -44,7 +43,7 @@ pub struct RowLayout {
 impl RowLayout {
 
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
         orientation: Orientation,
         inner_padding: f64
@@ -81,6 +80,8 @@ impl RowLayout {
   /// Inserts a window at the specified zero-based index
   pub fn insert_child(&mut self, child: Rc<RefCell<dyn ChildWindow>>, index: usize) {
 
+    self.layout_base.get_event_loop().register_window(self.layout_base.get_uuid(), &child);
+
     let layout_data = LayoutData {
       index: self.children.len(),
       child: ChildType::Window(child),
@@ -88,11 +89,15 @@ impl RowLayout {
       y: 0.0,
     };
     self.children.insert(index, Box::new(layout_data));
+    self.layout_base.set_dirty(true);
   }
 
   /// Inserts a layout at the specified zero-based index
   pub fn insert_layout(&mut self, layout: Rc<RefCell<dyn Layout>>, index: usize) {
 
+    let uuid = layout.borrow().get_uuid();
+    self.layout_base.get_event_loop().get_registry().borrow_mut().add_layout(self.layout_base.get_uuid(), uuid);
+
     let layout_data = LayoutData {
       index: self.children.len(),
       child: ChildType::Layout(layout),
@@ -100,6 +105,7 @@ impl RowLayout {
       y: 0.0,
     };
     self.children.insert(index, Box::new(layout_data));
+    self.layout_base.set_dirty(true);
   }
 }
 
@@ -119,6 +125,8 @@ impl Layout for RowLayout {
   fn add_child(&mut self, child: Rc<RefCell<dyn ChildWindow>>,
       _args: LayoutArgs) -> Result<(), String> {
 
+    self.layout_base.get_event_loop().register_window(self.layout_base.get_uuid(), &child);
+
     let layout_data = LayoutData {
       index: self.children.len(),
       child: ChildType::Window(child),
@@ -126,6 +134,7 @@ impl Layout for RowLayout {
       y: 0.0,
     };
     self.children.push(Box::new(layout_data));
+    self.layout_base.set_dirty(true);
 
     Ok(())
   }
@@ -133,6 +142,9 @@ impl Layout for RowLayout {
   fn add_layout(&mut self, layout: Rc<RefCell<dyn Layout>>,
       _args: LayoutArgs) -> Result<(), String> {
 
+    let uuid = layout.borrow().get_uuid();
+    self.layout_base.get_event_loop().get_registry().borrow_mut().add_layout(self.layout_base.get_uuid(), uuid);
+
     let layout_data = LayoutData {
       index: self.children.len(),
       child: ChildType::Layout(layout),
@@ -140,12 +152,15 @@ impl Layout for RowLayout {
       y: 0.0,
     };
     self.children.push(Box::new(layout_data));
+    self.layout_base.set_dirty(true);
 
     Ok(())
   }
 
   fn clear(&mut self) {
+    self.layout_base.get_event_loop().unregister_children(self.layout_base.get_uuid());
     self.children.clear();
+    self.layout_base.set_dirty(true);
   }
 
   /// The only layout that performs any actions in this method is the TabLayout
@@ -193,6 +208,33 @@ impl Layout for RowLayout {
     None
   }
 
+  /// Gets the focusable children, in the order they were added
+  fn get_focus_chain(&self) -> Vec<Rc<RefCell<dyn ChildWindow>>> {
+
+    let mut chain: Vec<Rc<RefCell<dyn ChildWindow>>> = Vec::new();
+
+    // Children are visited in the order in which they were added
+    for child_data in &self.children {
+
+      match &child_data.child {
+
+        ChildType::Window(window) => {
+
+          let child_ref = window.borrow();
+          if child_ref.get_accepts_focus() && child_ref.get_enabled() {
+            chain.push(window.clone());
+          }
+        },
+
+        ChildType::Layout(layout) => {
+          chain.append(&mut layout.borrow().get_focus_chain());
+        },
+      }
+    }
+
+    chain
+  }
+
   /// Gets the child with the specified ID
   fn get_child_with_id(&mut self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>> {
 
@@ -291,6 +333,11 @@ impl Layout for RowLayout {
 
   fn layout(&mut self, main_win_x: f64, main_win_y: f64, width: f64, height: f64) -> Pixmap {
 
+    // If nothing has changed since the layout was drawn in this area, reuse its pixmap
+    if !self.is_dirty() && self.layout_base.is_laid_out_at(main_win_x, main_win_y, width, height) {
+      return self.layout_base.get_pixmap();
+    }
+
     // Save the layout's location within the main window
     self.layout_base.set_main_win_x(main_win_x);
     self.layout_base.set_main_win_y(main_win_y);
@@ -300,13 +347,11 @@ impl Layout for RowLayout {
     let layout_height = height;
 
     // Create the pixmap into which we will draw
-    let mut pixmap = match Pixmap::new(width as u32, height as u32) {
+    let mut pixmap = match Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32) {
       Some(pixmap) => pixmap,
       None => Pixmap::new(1, 1).unwrap(),
     };
 
-    let paint = PixmapPaint::default();
-
     // Create a vector to hold the locations and sizes of the children.
     // This information will be calculates during several passes over the children.
     // Each element's key is the child's UUID, and the data is x, y, width, height.
@@ -377,7 +422,15 @@ impl Layout for RowLayout {
           ChildType::Window(window) => {
 
             let child_ref = window.borrow();
-            let (child_width, child_height) = child_ref.get_drawing_size();
+            let (child_width, mut child_height) = child_ref.get_drawing_size();
+
+            // A vertical layout gives the child its full width, so a child
+            // that wraps its contents may need a different height
+            if let Orientation::Vertical = self.orientation {
+              if let Some(height) = child_ref.get_height_for_width(layout_width) {
+                child_height = height;
+              }
+            }
 
             // Initially, set the size to the child's unaltered drawing size
             drawing_info.insert(
@@ -711,13 +764,15 @@ impl Layout for RowLayout {
         ChildType::Window(window) => {
 
           let mut window_ref = window.borrow_mut();
-          window_ref.redraw(
+          let child_pixmap = window_ref.redraw(
                 self.layout_base.get_main_win_x() + *x as f64,
                 self.layout_base.get_main_win_y() + *y as f64,
                 new_width,
                 new_height,
                 false
-          )
+          );
+          window_ref.set_dirty(false);
+          child_pixmap
         },
 
         ChildType::Layout(layout) => {
@@ -733,17 +788,10 @@ impl Layout for RowLayout {
       };
 
       // Copy the child's pixmap image onto the full pixmap
-      pixmap.draw_pixmap(
-          *x as i32,
-          *y as i32,
-          child_pixmap.as_ref(),
-          &paint,
-          Transform::identity(),
-          None,
-      );
+      WindowUtils::draw_child_pixmap(&mut pixmap, *x, *y, &child_pixmap);
     }
 
-    self.layout_base.set_pixmap(pixmap.clone());
+    self.layout_base.set_laid_out(main_win_x, main_win_y, width, height, pixmap.clone());
 
     pixmap
   }
@@ -754,11 +802,13 @@ impl Layout for RowLayout {
 
   fn set_fill(&mut self, algorithm: Box<LayoutFill>) {
     self.fill_algorithm = algorithm;
+    self.layout_base.set_dirty(true);
   }
 
   fn set_max_size(&mut self, width: f64, height: f64) {
     self.max_width = Some(width);
     self.max_height = Some(height);
+    self.layout_base.set_dirty(true);
   }
 
   /// LayoutBase pass-through functions
@@ -770,10 +820,10 @@ impl Layout for RowLayout {
     self.layout_base.set_uuid(uuid);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.layout_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.layout_base.set_event_loop(event_loop);
   }
 
@@ -791,6 +841,13 @@ impl Layout for RowLayout {
     self.layout_base.set_pixmap(pixmap);
   }
 
+  fn is_dirty(&self) -> bool {
+    self.layout_base.is_dirty() || self.children.iter().any(|child_data| child_data.child.is_dirty())
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.layout_base.set_dirty(dirty);
+  }
+
   fn get_main_win_x(&self) -> f64 {
     self.layout_base.get_main_win_x()
   }
@@ -1,11 +1,10 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -13,7 +12,6 @@ use tiny_skia::{
   Color,
   Paint,
   Pixmap,
-  PixmapPaint,
   Rect,
   Transform,
 };
@@ -21,8 +19,9 @@ use tiny_skia::{
 use crate::button::Button;
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, LayoutType, Orientation};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, LayoutType, Orientation};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
@@ -52,8 +51,6 @@ pub struct ScrollBar {
   min_value: f64,
   max_value: f64,
   value: f64,
-  color_background: Color,
-  color_slide: Color,
   scrolling_callback: Option<Box<dyn Fn(Orientation, f64)>>,
   dragging_slide: bool,
 }
@@ -61,7 +58,7 @@ pub struct ScrollBar {
 impl ScrollBar {
 
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
         orientation: Orientation,
   ) -> Self {
@@ -91,7 +88,7 @@ impl ScrollBar {
       None,
       None,
       Some(dec_image_data),
-      Color::from_rgba8(191, 191, 191, 255),
+      window_base.get_theme().palette.control,
       move || {
       }
     );
@@ -101,7 +98,7 @@ impl ScrollBar {
       None,
       None,
       Some(inc_image_data),
-      Color::from_rgba8(191, 191, 191, 255),
+      window_base.get_theme().palette.control,
       move || {
       }
     );
@@ -115,8 +112,6 @@ impl ScrollBar {
       min_value: 0.0,
       max_value: 0.0,
       value: 0.0,
-      color_background: Color::from_rgba8(191, 191, 191, 255),
-      color_slide: Color::from_rgba8(64, 64, 255, 255),
       scrolling_callback: None,
       dragging_slide: false,
     }
@@ -137,19 +132,27 @@ impl ScrollBar {
     }
 
     // Create the new Pixmap
+    let (pixmap_width, pixmap_height) = self.window_base.get_physical_drawing_size();
     let mut pixmap = match Pixmap::new(
-          width as u32,
-          height as u32
+          pixmap_width as u32,
+          pixmap_height as u32
     ) {
       Some(pixmap) => pixmap,
       None => {
-        println!("In ScrollBar::draw(), cannot create a pixmap of size {width} x {height}");
+        println!("In ScrollBar::draw(), cannot create a pixmap of size {pixmap_width} x {pixmap_height}");
         Pixmap::new(1, 1).unwrap()
       },
     };
 
     // Fill the pixmap with the background color
-    pixmap.fill(self.color_background);
+    let theme = self.window_base.get_theme();
+    pixmap.fill(theme.palette.control);
+
+    // The buttons were created with the colors of the previous theme
+    if self.window_base.is_theme_changed() {
+      self.decrease.borrow_mut().set_background_color(theme.palette.control);
+      self.increase.borrow_mut().set_background_color(theme.palette.control);
+    }
 
     // Get the size and location of the slide
     let (slide_x, slide_y) = self.get_slide_location();
@@ -163,16 +166,15 @@ impl ScrollBar {
       },
     };
     let mut paint = Paint::default();
-    paint.set_color(self.color_slide);
+    paint.set_color(theme.palette.accent);
+    let scale_factor = WindowUtils::get_scale_factor() as f32;
     pixmap.fill_rect(
           rect,
           &paint,
-          Transform::identity(),
+          Transform::from_scale(scale_factor, scale_factor),
           None
     );
 
-    let paint = PixmapPaint::default();
-
     let (win_x, win_y) = self.window_base.get_location();
 
     // Draw the decrease button
@@ -185,14 +187,7 @@ impl ScrollBar {
           BAR_SIZE,
           false
     );
-    pixmap.draw_pixmap(
-        0,
-        0,
-        child_pixmap.as_ref(),
-        &paint,
-        Transform::identity(),
-        None,
-    );
+    WindowUtils::draw_child_pixmap(&mut pixmap, 0.0, 0.0, &child_pixmap);
 
     // Draw the increase button
     match self.orientation {
@@ -208,14 +203,7 @@ impl ScrollBar {
               BAR_SIZE,
               false
         );
-        pixmap.draw_pixmap(
-            (width - BAR_SIZE) as i32,
-            0,
-            child_pixmap.as_ref(),
-            &paint,
-            Transform::identity(),
-            None,
-        );
+        WindowUtils::draw_child_pixmap(&mut pixmap, width - BAR_SIZE, 0.0, &child_pixmap);
       },
 
       Orientation::Vertical => {
@@ -230,14 +218,7 @@ impl ScrollBar {
               BAR_SIZE,
               false
         );
-        pixmap.draw_pixmap(
-            0,
-            (height - BAR_SIZE) as i32,
-            child_pixmap.as_ref(),
-            &paint,
-            Transform::identity(),
-            None,
-        );
+        WindowUtils::draw_child_pixmap(&mut pixmap, 0.0, height - BAR_SIZE, &child_pixmap);
       },
     }
 
@@ -465,6 +446,13 @@ impl ChildWindow for ScrollBar {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -479,10 +467,10 @@ impl ChildWindow for ScrollBar {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -500,6 +488,20 @@ impl ChildWindow for ScrollBar {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -553,6 +555,10 @@ impl ChildWindow for ScrollBar {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -605,13 +611,16 @@ impl ChildWindow for ScrollBar {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
+  }
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-        mouse_x: f64, mouse_y: f64) {
+        mouse_x: f64, mouse_y: f64, _modifiers: ModifiersState) {
 
     // Ignore right clicks
     if MouseButton::Right == button {
@@ -735,7 +744,11 @@ impl ChildWindow for ScrollBar {
     self.dragging_slide = false;
   }
 
-  fn handle_mouse_drag(&mut self, main_win_x: f64, main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, main_win_x: f64, main_win_y: f64, _modifiers: ModifiersState) {
 
     if self.dragging_slide {
       let (x, y) = self.window_base.get_location();
@@ -749,9 +762,9 @@ impl ChildWindow for ScrollBar {
       self.set_value_from_mouse_location(mouse_x, mouse_y);
     }
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _main_win_x: f64, _main_win_y: f64) {
@@ -794,7 +807,7 @@ impl ChildWindow for ScrollBar {
     }
 
     // Has the size changed?
-    if force || width != self.window_base.get_width() ||
+    if force || self.window_base.is_theme_changed() || width != self.window_base.get_width() ||
         height != self.window_base.get_height()
     {
 
@@ -1,8 +1,4 @@
-use winit::{
-  event_loop::EventLoopProxy,
-};
-
-use tiny_skia::{Pixmap, PixmapPaint, Transform};
+use tiny_skia::Pixmap;
 
 use crate::ChildWindow;
 use crate::child_window::{
@@ -12,12 +8,13 @@ use crate::child_window::{
   LayoutType,
   Orientation,
 };
+use crate::event_proxy::EventProxy;
 use crate::layout_base::LayoutBase;
 use crate::scroll_bar::{
   BAR_SIZE,
   ScrollBar,
 };
-use crate::UserEvent;
+use crate::window_utils::WindowUtils;
 
 use uuid::Uuid;
 
@@ -40,7 +37,7 @@ pub struct ScrollLayout {
 impl ScrollLayout {
 
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
   ) -> ScrollLayout {
 
@@ -96,6 +93,9 @@ impl Layout for ScrollLayout {
   fn add_child(&mut self, child: Rc<RefCell<dyn ChildWindow>>,
       _args: LayoutArgs) -> Result<(), String> {
 
+    // Whether the child is being added again, so that it keeps its listeners
+    let is_same_child = self.child.as_ref().is_some_and(|previous| Rc::ptr_eq(previous, &child));
+
     // If this ScrollLayout already has a child, remove it as that child's parent.
     match &self.child {
 
@@ -109,6 +109,13 @@ impl Layout for ScrollLayout {
 
     self.child = Some(child.clone());
 
+    // Replace the previous child in the registry, disconnecting its listeners
+    let event_loop = self.layout_base.get_event_loop();
+    if !is_same_child {
+      event_loop.unregister_children(self.layout_base.get_uuid());
+    }
+    event_loop.register_window(self.layout_base.get_uuid(), &child);
+
     // Set the scroll handler for the horizontal scroll bar
     let h_scroll_rc = self.h_scroll.clone();
     let mut h_scroll_ref = h_scroll_rc.borrow_mut();
@@ -141,6 +148,8 @@ impl Layout for ScrollLayout {
       }
     }));
 
+    self.layout_base.set_dirty(true);
+
     Ok(())
   }
 
@@ -154,7 +163,9 @@ impl Layout for ScrollLayout {
   }
 
   fn clear(&mut self) {
+    self.layout_base.get_event_loop().unregister_children(self.layout_base.get_uuid());
     self.child = None;
+    self.layout_base.set_dirty(true);
   }
 
   /// The only layout that performs any actions in this method is the TabLayout
@@ -206,6 +217,21 @@ impl Layout for ScrollLayout {
     }
  }
 
+  /// Gets the scrolled child, if it can receive the input focus
+  fn get_focus_chain(&self) -> Vec<Rc<RefCell<dyn ChildWindow>>> {
+
+    let mut chain: Vec<Rc<RefCell<dyn ChildWindow>>> = Vec::new();
+
+    if let Some(child) = &self.child {
+      let child_ref = child.borrow();
+      if child_ref.get_accepts_focus() && child_ref.get_enabled() {
+        chain.push(child.clone());
+      }
+    }
+
+    chain
+  }
+
   /// Gets the child window with the specified ID
   fn get_child_with_id(&mut self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>> {
 
@@ -262,6 +288,11 @@ impl Layout for ScrollLayout {
   /// Returns a pixmap onto which the window has been drawn
   fn layout(&mut self, main_win_x: f64, main_win_y: f64, width: f64, height: f64) -> Pixmap {
 
+    // If nothing has changed since the layout was drawn in this area, reuse its pixmap
+    if !self.is_dirty() && self.layout_base.is_laid_out_at(main_win_x, main_win_y, width, height) {
+      return self.layout_base.get_pixmap();
+    }
+
     // Save the location
     self.layout_base.set_main_win_x(main_win_x);
     self.layout_base.set_main_win_y(main_win_y);
@@ -296,8 +327,8 @@ impl Layout for ScrollLayout {
 
     // Create the pixmap
     let mut pixmap = match Pixmap::new(
-          self.layout_base.get_width() as u32,
-          self.layout_base.get_height() as u32
+          WindowUtils::to_physical(self.layout_base.get_width()) as u32,
+          WindowUtils::to_physical(self.layout_base.get_height()) as u32
     ) {
 
       Some(pixmap) =>  pixmap,
@@ -332,8 +363,6 @@ impl Layout for ScrollLayout {
         v_scroll_ref.set_range(v_min, v_max, v_value);
         let v_visible = v_scroll_ref.is_visible();
 
-        let paint = PixmapPaint::default();
-
         // Draw the vertical scroll bar
         let v_scroll_size;
         if v_visible {
@@ -353,14 +382,8 @@ impl Layout for ScrollLayout {
                 v_height,
                 true
           );
-          pixmap.draw_pixmap(
-              (width - BAR_SIZE) as i32,
-              0,
-              child_pixmap.as_ref(),
-              &paint,
-              Transform::identity(),
-              None,
-          );
+          v_scroll_ref.set_dirty(false);
+          WindowUtils::draw_child_pixmap(&mut pixmap, width - BAR_SIZE, 0.0, &child_pixmap);
         } else {
           v_scroll_size = 0.0;
         }
@@ -384,14 +407,8 @@ impl Layout for ScrollLayout {
                 BAR_SIZE,
                 true
           );
-          pixmap.draw_pixmap(
-              0,
-              (height - BAR_SIZE) as i32,
-              child_pixmap.as_ref(),
-              &paint,
-              Transform::identity(),
-              None,
-          );
+          h_scroll_ref.set_dirty(false);
+          WindowUtils::draw_child_pixmap(&mut pixmap, 0.0, height - BAR_SIZE, &child_pixmap);
         } else {
           h_scroll_size = 0.0;
         }
@@ -420,20 +437,14 @@ impl Layout for ScrollLayout {
               height - h_scroll_size,
               true
         );
-        pixmap.draw_pixmap(
-            0,
-            0,
-            child_pixmap.as_ref(),
-            &paint,
-            Transform::identity(),
-            None,
-        );
+        child_ref.set_dirty(false);
+        WindowUtils::draw_child_pixmap(&mut pixmap, 0.0, 0.0, &child_pixmap);
       },
 
       None => {},
     }
 
-    self.layout_base.set_pixmap(pixmap.clone());
+    self.layout_base.set_laid_out(main_win_x, main_win_y, width, height, pixmap.clone());
 
     pixmap
   }
@@ -450,6 +461,7 @@ impl Layout for ScrollLayout {
   fn set_max_size(&mut self, width: f64, height: f64) {
     self.max_width = Some(width);
     self.max_height = Some(height);
+    self.layout_base.set_dirty(true);
   }
 
   /// LayoutBase pass-through functions
@@ -461,10 +473,10 @@ impl Layout for ScrollLayout {
     self.layout_base.set_uuid(uuid);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.layout_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.layout_base.set_event_loop(event_loop);
   }
 
@@ -482,6 +494,21 @@ impl Layout for ScrollLayout {
     self.layout_base.set_pixmap(pixmap);
   }
 
+  fn is_dirty(&self) -> bool {
+
+    // A window that is already borrowed is being changed
+    let child_dirty = match &self.child {
+      Some(child) => child.try_borrow().map_or(true, |child_ref| child_ref.is_dirty()),
+      None => false,
+    };
+    self.layout_base.is_dirty() || child_dirty ||
+          self.h_scroll.try_borrow().map_or(true, |h_scroll_ref| h_scroll_ref.is_dirty()) ||
+          self.v_scroll.try_borrow().map_or(true, |v_scroll_ref| v_scroll_ref.is_dirty())
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.layout_base.set_dirty(dirty);
+  }
+
   fn get_main_win_x(&self) -> f64 {
     self.layout_base.get_main_win_x()
   }
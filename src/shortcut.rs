@@ -0,0 +1,157 @@
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use crate::child_window::{KeyboardEvent, UserEvent};
+
+use std::fmt;
+
+/// Key combination, such as Ctrl+S, that triggers an action anywhere in an application
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shortcut {
+  modifiers: ModifiersState,    // modifier keys that must be held down
+  key: Key,                     // key that is pressed; characters are stored in lower case
+}
+
+impl Shortcut {
+
+  pub fn new(modifiers: ModifiersState, key: Key) -> Self {
+    Self {
+      modifiers,
+      key: Shortcut::normalize_key(key),
+    }
+  }
+
+  /// Creates a shortcut from a string, such as "Ctrl+S" or "Ctrl+Shift+N"
+  ///
+  /// The modifiers are Ctrl, Shift, Alt, and Super, followed by either a single
+  /// character or the name of a key, such as F5, Enter, or PageUp.
+  ///
+  /// If the string is not valid, the returned Result contains a string explaining why.
+  pub fn parse(text: &str) -> Result<Self, String> {
+
+    let mut modifiers = ModifiersState::empty();
+    let mut key: Option<Key> = None;
+
+    for part in text.split('+') {
+
+      let part = part.trim();
+      if key.is_some() {
+        return Err(format!("The key must be the last part of the shortcut \"{text}\""));
+      }
+
+      match part.to_lowercase().as_str() {
+        "ctrl" | "control" => modifiers |= ModifiersState::CONTROL,
+        "shift" => modifiers |= ModifiersState::SHIFT,
+        "alt" => modifiers |= ModifiersState::ALT,
+        "super" | "cmd" | "meta" => modifiers |= ModifiersState::SUPER,
+        _ => {
+          match Shortcut::parse_key(part) {
+            Some(parsed) => key = Some(parsed),
+            None => return Err(format!("Unknown key \"{part}\" in the shortcut \"{text}\"")),
+          }
+        },
+      }
+    }
+
+    match key {
+      Some(key) => Ok(Shortcut::new(modifiers, key)),
+      None => Err(format!("The shortcut \"{text}\" has no key")),
+    }
+  }
+
+  pub fn get_key(&self) -> Key {
+    self.key.clone()
+  }
+
+  pub fn get_modifiers(&self) -> ModifiersState {
+    self.modifiers
+  }
+
+  /// Returns true if a key press, with the specified modifier keys held down, triggers this shortcut
+  pub fn matches(&self, event: &KeyboardEvent, modifiers: ModifiersState) -> bool {
+    modifiers == self.modifiers && Shortcut::normalize_key(event.logical_key.clone()) == self.key
+  }
+
+  // Lower-cases character keys, so that Ctrl+Shift+N matches whether Shift turned "n" into "N"
+  fn normalize_key(key: Key) -> Key {
+    match key {
+      Key::Character(text) => Key::Character(text.to_lowercase().into()),
+      key => key,
+    }
+  }
+
+  // Converts the name of a key into a Key
+  fn parse_key(name: &str) -> Option<Key> {
+
+    if name.chars().count() == 1 {
+      return Some(Key::Character(name.into()));
+    }
+
+    let named_key = match name.to_lowercase().as_str() {
+      "backspace" => NamedKey::Backspace,
+      "delete" | "del" => NamedKey::Delete,
+      "down" => NamedKey::ArrowDown,
+      "end" => NamedKey::End,
+      "enter" | "return" => NamedKey::Enter,
+      "escape" | "esc" => NamedKey::Escape,
+      "home" => NamedKey::Home,
+      "insert" | "ins" => NamedKey::Insert,
+      "left" => NamedKey::ArrowLeft,
+      "pagedown" => NamedKey::PageDown,
+      "pageup" => NamedKey::PageUp,
+      "right" => NamedKey::ArrowRight,
+      "space" => NamedKey::Space,
+      "tab" => NamedKey::Tab,
+      "up" => NamedKey::ArrowUp,
+      "f1" => NamedKey::F1,
+      "f2" => NamedKey::F2,
+      "f3" => NamedKey::F3,
+      "f4" => NamedKey::F4,
+      "f5" => NamedKey::F5,
+      "f6" => NamedKey::F6,
+      "f7" => NamedKey::F7,
+      "f8" => NamedKey::F8,
+      "f9" => NamedKey::F9,
+      "f10" => NamedKey::F10,
+      "f11" => NamedKey::F11,
+      "f12" => NamedKey::F12,
+      _ => return None,
+    };
+
+    Some(Key::Named(named_key))
+  }
+}
+
+/// Displays the shortcut as accelerator text, such as "Ctrl+Shift+N"
+impl fmt::Display for Shortcut {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+
+    if self.modifiers.control_key() {
+      write!(fmt, "Ctrl+")?;
+    }
+    if self.modifiers.shift_key() {
+      write!(fmt, "Shift+")?;
+    }
+    if self.modifiers.alt_key() {
+      write!(fmt, "Alt+")?;
+    }
+    if self.modifiers.super_key() {
+      write!(fmt, "Super+")?;
+    }
+
+    match &self.key {
+      Key::Character(text) => write!(fmt, "{}", text.to_uppercase()),
+      Key::Named(NamedKey::ArrowDown) => write!(fmt, "Down"),
+      Key::Named(NamedKey::ArrowLeft) => write!(fmt, "Left"),
+      Key::Named(NamedKey::ArrowRight) => write!(fmt, "Right"),
+      Key::Named(NamedKey::ArrowUp) => write!(fmt, "Up"),
+      Key::Named(named_key) => write!(fmt, "{named_key:?}"),
+      key => write!(fmt, "{key:?}"),
+    }
+  }
+}
+
+/// What happens when a Shortcut is pressed
+pub enum ShortcutAction {
+  Callback(Box<dyn Fn()>),      // the closure is called
+  Event(UserEvent),             // the event is sent to the event loop
+}
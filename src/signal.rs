@@ -0,0 +1,75 @@
+use uuid::Uuid;
+
+use std::rc::Rc;
+
+use crate::UserEvent;
+
+/// Identifies a listener that was connected to a window, such as with
+/// List::on_selection_changed(), so that it can be disconnected
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ConnectionId(u64);
+
+/// Function that is called with the events that a window fires
+///
+/// It returns true if the event is one that it listens for.
+pub(crate) type Listener = Rc<dyn Fn(&UserEvent) -> bool>;
+
+/// Listeners that are connected to the windows
+///
+/// The MainApp passes each event that a window fires to the listeners that
+/// are connected to that window, before calling its callback for that kind
+/// of event. A window's listeners are disconnected when it is removed from
+/// its layout, or its tab or pop-up is closed.
+#[derive(Default)]
+pub(crate) struct Signals {
+  next_id: u64,
+  listeners: Vec<(Uuid, ConnectionId, Listener)>,   // window that the listener is connected to
+}
+
+impl Signals {
+
+  /// Connects a listener to a window
+  pub fn connect(&mut self, source: Uuid, listener: Listener) -> ConnectionId {
+
+    let id = ConnectionId(self.next_id);
+    self.next_id += 1;
+    self.listeners.push((source, id, listener));
+
+    id
+  }
+
+  /// Disconnects a listener, returning false if it isn't connected
+  pub fn disconnect(&mut self, id: ConnectionId) -> bool {
+
+    let count = self.listeners.len();
+    self.listeners.retain(|(_source, connection, _listener)| *connection != id);
+
+    self.listeners.len() != count
+  }
+
+  /// Disconnects all of the listeners that are connected to the windows
+  pub fn disconnect_sources(&mut self, sources: &[Uuid]) {
+    if !sources.is_empty() {
+      self.listeners.retain(|(source, _id, _listener)| !sources.contains(source));
+    }
+  }
+
+  /// Gets the listeners that are connected to a window, in the order they were connected
+  ///
+  /// They are copied, so that they can connect and disconnect listeners while they are called.
+  pub fn get_listeners(&self, source: Uuid) -> Vec<Listener> {
+    self.listeners.iter()
+      .filter(|(listener_source, _id, _listener)| *listener_source == source)
+      .map(|(_source, _id, listener)| listener.clone())
+      .collect()
+  }
+
+  /// Moves the listeners that are connected to a window whose UUID has changed
+  pub fn set_source_uuid(&mut self, old_uuid: Uuid, new_uuid: Uuid) {
+    for (source, _id, _listener) in self.listeners.iter_mut() {
+      if *source == old_uuid {
+        *source = new_uuid;
+      }
+    }
+  }
+}
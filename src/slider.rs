@@ -1,12 +1,11 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     MouseScrollDelta::{LineDelta, PixelDelta},
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -30,8 +29,10 @@ use std::{
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::{ContextMenuItem, Orientation};
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent, Orientation};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::signal::ConnectionId;
 use crate::text_font::TextFont;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
@@ -46,9 +47,9 @@ pub struct Slider {
   window_base: WindowBase,
   orientation: Orientation,
   font: Option<TextFont>,
-  color_text: Color,
-  color_background: Color,
-  color_slide: Color,
+  color_text: Option<Color>,        // None uses the theme's colors
+  color_background: Option<Color>,
+  color_slide: Option<Color>,
   min_value: f64,
   max_value: f64,
   value: f64,
@@ -60,13 +61,13 @@ pub struct Slider {
 impl Slider {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
     orientation: Orientation,
   ) -> Self {
 
     // Load the font
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", 14.0) {
+    let font = match TextFont::new_default() {
       Ok(font) => Some(font),
       Err(_err) => None,
     };
@@ -79,9 +80,9 @@ impl Slider {
       window_base: window_base,
       orientation: orientation,
       font: font,
-      color_text: Color::BLACK,
-      color_background: Color::from_rgba8(191, 191, 191, 255),
-      color_slide: Color::from_rgba8(64, 64, 255, 255),
+      color_text: None,
+      color_background: None,
+      color_slide: None,
       min_value: 1.0,
       max_value: 10.0,
       value: 1.0,
@@ -110,24 +111,36 @@ impl Slider {
     (pixel * range) / (max_clickable as f64)
   }
 
+  /// Disconnects a listener that was connected with one of the on_ methods,
+  /// returning false if it isn't connected
+  pub fn disconnect(&self, id: ConnectionId) -> bool {
+    self.window_base.disconnect(id)
+  }
+
   fn draw(&mut self) {
 
     let (width, height) = self.window_base.get_drawing_size();
+    let (pixmap_width, pixmap_height) = self.window_base.get_physical_drawing_size();
 
     // Create the pixmap
     let mut pixmap = match Pixmap::new(
-          width as u32,
-          height as u32
+          pixmap_width as u32,
+          pixmap_height as u32
     ) {
       Some(pixmap) => pixmap,
       None => {
-        println!("In Slider::draw(), cannot create a pixmap of size {width} x {height}");
+        println!("In Slider::draw(), cannot create a pixmap of size {pixmap_width} x {pixmap_height}");
         Pixmap::new(1, 1).unwrap()
       },
     };
 
+    // The slider is drawn in logical units, which are scaled to the pixmap's physical pixels
+    let scale_factor = WindowUtils::get_scale_factor() as f32;
+    let transform = Transform::from_scale(scale_factor, scale_factor);
+
     // Fill the pixmap with the background color
-    pixmap.fill(self.color_background);
+    let theme = self.window_base.get_theme();
+    pixmap.fill(self.color_background.unwrap_or(theme.palette.control));
 
     // If we are drawing ticks, set the background for the ticks to WHITE
     if self.show_ticks {
@@ -154,9 +167,9 @@ impl Slider {
             ticks_height as f32
       ).unwrap();
       let mut tick_bg_paint = Paint::default();
-      tick_bg_paint.set_color(Color::WHITE);
+      tick_bg_paint.set_color(theme.palette.base);
 
-      pixmap.fill_rect(rect, &tick_bg_paint, Transform::identity(), None);
+      pixmap.fill_rect(rect, &tick_bg_paint, transform, None);
     }
 
     // Get the size and location of the slide
@@ -186,18 +199,19 @@ impl Slider {
       },
     };
     let mut paint = Paint::default();
-    paint.set_color(self.color_slide);
+    paint.set_color(self.color_slide.unwrap_or(theme.palette.accent));
     pixmap.fill_rect(
           rect,
           &paint,
-          Transform::identity(),
+          transform,
           None
     );
 
     // Draw the ticks, if requested.
     if self.show_ticks {
 
-      let paint = Paint::default();
+      let mut paint = Paint::default();
+      paint.set_color(self.color_text.unwrap_or(theme.palette.text));
       let stroke = Stroke::default();
 
       match self.orientation {
@@ -222,7 +236,7 @@ impl Slider {
             let path = pb.finish().unwrap();
 
             // Draw the tick
-            pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+            pixmap.stroke_path(&path, &paint, &stroke, transform, None);
 
             // Move to the next tick's value
             tick_value += self.tick_steps;
@@ -249,7 +263,7 @@ impl Slider {
             let path = pb.finish().unwrap();
 
             // Draw the tick
-            pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+            pixmap.stroke_path(&path, &paint, &stroke, transform, None);
 
             // Move to the next tick's value
             tick_value += self.tick_steps;
@@ -330,8 +344,21 @@ impl Slider {
     self.value
   }
 
+  /// Calls a function with the new value each time the value changes
+  pub fn on_value_changed<F: Fn(f64) + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      match event {
+        UserEvent::SliderValueChange(_main_win_uuid, _source, value) => {
+          listener(*value);
+          true
+        },
+        _ => false,
+      }
+    }))
+  }
+
   pub fn set_background_color(&mut self, color: Color) {
-    self.color_background = color;
+    self.color_background = Some(color);
   }
 
   pub fn set_range(&mut self, min: f64, max: f64, value: f64) {
@@ -356,11 +383,11 @@ impl Slider {
   }
 
   pub fn set_slide_color(&mut self, color: Color) {
-    self.color_slide = color;
+    self.color_slide = Some(color);
   }
 
   pub fn set_text_color(&mut self, color: Color) {
-    self.color_text = color;
+    self.color_text = Some(color);
   }
 
   pub fn set_tick_steps(&mut self, steps: f64) {
@@ -401,6 +428,8 @@ impl Slider {
       WindowUtils::fire_user_event(
           self.window_base.get_event_loop().clone(),
           UserEvent::SliderValueChange(
+                self.window_base.get_main_win_uuid(),
+                self.window_base.get_uuid(),
                 value
           )
       );
@@ -489,6 +518,13 @@ impl ChildWindow for Slider {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -503,10 +539,10 @@ impl ChildWindow for Slider {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -524,6 +560,20 @@ impl ChildWindow for Slider {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -569,6 +619,10 @@ impl ChildWindow for Slider {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -621,13 +675,16 @@ impl ChildWindow for Slider {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
+  }
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-      mouse_x: f64, mouse_y: f64) {
+      mouse_x: f64, mouse_y: f64, _modifiers: ModifiersState) {
 
     if button == MouseButton::Left {
 
@@ -675,11 +732,15 @@ impl ChildWindow for Slider {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _main_win_x: f64, _main_win_y: f64) {
@@ -707,6 +768,8 @@ impl ChildWindow for Slider {
           WindowUtils::fire_user_event(
               self.window_base.get_event_loop().clone(),
               UserEvent::SliderValueChange(
+                    self.window_base.get_main_win_uuid(),
+                    self.window_base.get_uuid(),
                     new_value
               )
           );
@@ -744,7 +807,7 @@ impl ChildWindow for Slider {
 
     // If the size has changed, save the new size and redraw the window
     let (current_width, current_height) = self.window_base.get_drawing_size();
-    if force || width != current_width || height != current_height {
+    if force || self.window_base.is_theme_changed() || width != current_width || height != current_height {
       self.window_base.set_size(width, height);
       self.draw();
     }
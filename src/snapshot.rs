@@ -0,0 +1,210 @@
+use tiny_skia::{Color, Pixmap};
+
+use uuid::Uuid;
+
+use crate::child_window::{ChildType, ChildWindow, Layout};
+use crate::event_proxy::EventProxy;
+use crate::{MainApp, MainAppSize};
+
+use std::{
+  cell::RefCell,
+  env,
+  path::{Path, PathBuf},
+  rc::Rc,
+};
+
+/// Set this environment variable to replace the reference images with the
+/// rendered ones, instead of comparing them.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "FENETRE_UPDATE_SNAPSHOTS";
+
+/// Rendered image of a window or layout, used for golden-image testing
+///
+/// The window or layout must have been created with a headless EventProxy.
+/// It is drawn as the contents of a headless MainApp of the requested size.
+///
+/// When compared against a reference PNG file, such as "tests/snapshots/label.png",
+/// the following files are written next to the reference if they don't match:
+///   label.actual.png - the rendered image
+///   label.diff.png - the differing pixels in red over a faded copy of the reference
+pub struct Snapshot {
+  pixmap: Pixmap,
+}
+
+impl Snapshot {
+
+  /// Renders a layout
+  pub fn of_layout(layout: Rc<RefCell<dyn Layout>>, width: u32, height: u32) -> Self {
+
+    let (event_loop, main_win_uuid) = {
+      let layout_ref = layout.borrow();
+      (layout_ref.get_event_loop(), layout_ref.get_main_win_uuid())
+    };
+
+    Snapshot::render(ChildType::Layout(layout), event_loop, main_win_uuid, width, height)
+  }
+
+  /// Renders a window
+  pub fn of_window(window: Rc<RefCell<dyn ChildWindow>>, width: u32, height: u32) -> Self {
+
+    let (event_loop, main_win_uuid) = {
+      let window_ref = window.borrow();
+      (window_ref.get_event_loop(), window_ref.get_main_win_uuid())
+    };
+
+    Snapshot::render(ChildType::Window(window), event_loop, main_win_uuid, width, height)
+  }
+
+  /// Creates a snapshot from an existing pixmap, such as MainApp::get_pixmap()
+  pub fn from_pixmap(pixmap: Pixmap) -> Self {
+    Self {
+      pixmap,
+    }
+  }
+
+  /// Compares the snapshot against a reference PNG file
+  ///
+  /// Two pixels match if none of their color channels differ by more than
+  /// tolerance. If the images don't match, or the reference doesn't exist,
+  /// the returned Result contains a string explaining why.
+  ///
+  /// If the FENETRE_UPDATE_SNAPSHOTS environment variable is set, the
+  /// reference file is written instead.
+  pub fn compare<P: AsRef<Path>>(&self, reference_path: P, tolerance: u8) -> Result<(), String> {
+
+    let reference_path = reference_path.as_ref();
+
+    if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+      return self.save_png(reference_path);
+    }
+
+    let actual_path = Snapshot::sibling_path(reference_path, "actual");
+    let diff_path = Snapshot::sibling_path(reference_path, "diff");
+
+    let reference = match Pixmap::load_png(reference_path) {
+      Ok(reference) => reference,
+      Err(err) => {
+        self.save_png(&actual_path)?;
+        return Err(format!(
+              "Cannot load reference image {}: {err}; rendered image written to {} \
+               (set {UPDATE_SNAPSHOTS_VAR}=1 to create the reference)",
+              reference_path.display(),
+              actual_path.display()
+        ));
+      },
+    };
+
+    if reference.width() != self.pixmap.width() || reference.height() != self.pixmap.height() {
+      self.save_png(&actual_path)?;
+      return Err(format!(
+            "Size mismatch with {}: expected {}x{}, rendered {}x{}",
+            reference_path.display(),
+            reference.width(),
+            reference.height(),
+            self.pixmap.width(),
+            self.pixmap.height()
+      ));
+    }
+
+    // Count the differing pixels, marking them in the diff image
+    let mut diff = Pixmap::new(reference.width(), reference.height()).unwrap();
+    let mut differing_pixels = 0;
+    for (index, (expected, actual)) in reference.pixels().iter().zip(self.pixmap.pixels()).enumerate() {
+
+      let expected = expected.demultiply();
+      let actual = actual.demultiply();
+      let differs =
+            expected.red().abs_diff(actual.red()) > tolerance ||
+            expected.green().abs_diff(actual.green()) > tolerance ||
+            expected.blue().abs_diff(actual.blue()) > tolerance ||
+            expected.alpha().abs_diff(actual.alpha()) > tolerance;
+
+      let color = if differs {
+        differing_pixels += 1;
+        Color::from_rgba8(255, 0, 0, 255)
+      } else {
+        let gray = 191 + (expected.red() as u16 + expected.green() as u16 + expected.blue() as u16) / 12;
+        Color::from_rgba8(gray as u8, gray as u8, gray as u8, 255)
+      };
+
+      diff.pixels_mut()[index] = color.premultiply().to_color_u8();
+    }
+
+    if differing_pixels > 0 {
+      self.save_png(&actual_path)?;
+      match diff.save_png(&diff_path) {
+        Ok(_) => {},
+        Err(err) => println!("Cannot write diff image {}: {err}", diff_path.display()),
+      }
+
+      return Err(format!(
+            "{differing_pixels} pixels differ from {} by more than {tolerance}; see {} and {}",
+            reference_path.display(),
+            actual_path.display(),
+            diff_path.display()
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Returns the rendered image
+  pub fn get_pixmap(&self) -> &Pixmap {
+    &self.pixmap
+  }
+
+  /// Writes the rendered image to a PNG file
+  pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+
+    let path = path.as_ref();
+    match path.parent() {
+      Some(parent) if !parent.as_os_str().is_empty() => {
+        match std::fs::create_dir_all(parent) {
+          Ok(_) => {},
+          Err(err) => return Err(format!("Cannot create directory {}: {err}", parent.display())),
+        }
+      },
+      _ => {},
+    }
+
+    match self.pixmap.save_png(path) {
+      Ok(_) => Ok(()),
+      Err(err) => Err(format!("Cannot write {}: {err}", path.display())),
+    }
+  }
+
+  // Returns the path of a file next to the reference file, e.g. label.diff.png
+  fn sibling_path(reference_path: &Path, suffix: &str) -> PathBuf {
+
+    let stem = match reference_path.file_stem() {
+      Some(stem) => stem.to_string_lossy().to_string(),
+      None => "snapshot".to_string(),
+    };
+
+    reference_path.with_file_name(format!("{stem}.{suffix}.png"))
+  }
+
+  // Draws the contents of a headless MainApp
+  fn render(
+        contents: ChildType,
+        event_loop: Rc<EventProxy>,
+        main_win_uuid: Uuid,
+        width: u32,
+        height: u32
+  ) -> Self {
+
+    let app = MainApp::new(
+          main_win_uuid,
+          "Snapshot",
+          MainAppSize::Actual(0.0, 0.0, width as f64, height as f64),
+          event_loop,
+          || {}
+    );
+    let mut app_ref = app.borrow_mut();
+    app_ref.set_contents(contents);
+    app_ref.run_headless();
+
+    Self {
+      pixmap: app_ref.get_pixmap().clone(),
+    }
+  }
+}
@@ -1,6 +1,6 @@
 use winit::{
-  event::{KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
-  event_loop::EventLoopProxy,
+  event::{MouseButton, MouseScrollDelta, TouchPhase},
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -10,10 +10,11 @@ use uuid::Uuid;
 
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::ContextMenuItem;
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::rich_text::RichText;
 use crate::text_font::TextFont;
-use crate::UserEvent;
 use crate::window_base::WindowBase;
 use crate::window_utils::WindowUtils;
 
@@ -26,21 +27,24 @@ use std::{
 pub struct StatusBar {
   window_base: WindowBase,
   font: Option<TextFont>,
-  color_background: Color,
-  color_text: Color,
+  color_background: Option<Color>,    // None uses the theme's control color
+  rich_message: Option<RichText>,
 }
 
 impl StatusBar {
 
+  /// Creates a status bar
+  ///
+  /// If bg_color is None, the background is the theme's control color.
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
-        bg_color: Color,
+        bg_color: Option<Color>,
   ) -> Self {
 
     // Load the font
     let mut char_height = 18;
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", 14.0) {
+    let font = match TextFont::new_default() {
       Ok(font) => {
         let (_bounds_width, bounds_height) = font.get_bounds("Wy", None);
         char_height = bounds_height;
@@ -51,7 +55,7 @@ impl StatusBar {
       },
     };
 
-    let window_height = char_height as f64 + 4.0;
+    let window_height = WindowUtils::to_logical(char_height as f64) + 4.0;
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("Template".to_string());
     window_base.set_size(10.0, window_height);
@@ -61,14 +65,14 @@ impl StatusBar {
       window_base: window_base,
       font: font,
       color_background: bg_color,
-      color_text: Color::from_rgba8(0, 0, 0, 255),
+      rich_message: None,
     }
   }
 
   fn draw(&mut self) {
 
     // Create the new pixmap
-    let (width, height) = self.window_base.get_drawing_size();
+    let (width, height) = self.window_base.get_physical_drawing_size();
     let mut pixmap = match Pixmap::new(width as u32, height as u32) {
       Some(pixmap) => pixmap,
       None => {
@@ -98,15 +102,27 @@ impl StatusBar {
     self.window_base.get_text()
   }
 
+  /// Gets the message, if it was set with set_rich_message()
+  pub fn get_rich_message(&self) -> Option<RichText> {
+    self.rich_message.clone()
+  }
+
+  // Gets the background color, which is the theme's unless one has been set
+  fn get_bg_color(&self) -> Color {
+    self.color_background.unwrap_or(self.window_base.get_theme().palette.control)
+  }
+
   fn redraw_message(&mut self, _x: f64, _y: f64, width: f64, height: f64) -> Pixmap {
 
     // Create the pixmap into which we will draw
     let mut pixmap = Pixmap::new(width as u32, height as u32).unwrap();
 
     // Fill the pixmap with the background color
-    pixmap.fill(self.color_background);
+    let bg_color = self.get_bg_color();
+    pixmap.fill(bg_color);
 
     // Draw the text
+    let text_color = self.window_base.get_theme().palette.text;
     match &self.window_base.get_text() {
 
       Some(text) => match &self.font {
@@ -115,17 +131,31 @@ impl StatusBar {
           // Place the text at the left edge
           let x = 2;
           let y = 2;
-          font.draw_text(
-            text.as_str(),
-            &mut pixmap,
-            x as i32,
-            y as i32,
-            self.color_text,
-            self.color_background,
-            -1,
-            Color::BLACK,
-            None
-          );
+          match &self.rich_message {
+            Some(rich_message) => {
+              font.draw_rich_text(
+                rich_message,
+                &mut pixmap,
+                x,
+                y,
+                text_color,
+                bg_color,
+              );
+            },
+            None => {
+              font.draw_text(
+                text.as_str(),
+                &mut pixmap,
+                x as i32,
+                y as i32,
+                text_color,
+                bg_color,
+                -1,
+                Color::BLACK,
+                None
+              );
+            },
+          }
         },
         None => {},
       },
@@ -138,6 +168,25 @@ impl StatusBar {
   pub fn set_message(&mut self, message: String) {
 
     self.window_base.set_text(message);
+    self.rich_message = None;
+
+    self.draw_and_request_redraw();
+  }
+
+  /// Displays a message whose runs have their own fonts, colors, and decorations
+  ///
+  /// This can be used to show a warning in red within the message.
+  /// get_message() returns the message without its styles.
+  pub fn set_rich_message(&mut self, message: RichText) {
+
+    self.window_base.set_text(message.get_text());
+    self.rich_message = Some(message);
+
+    self.draw_and_request_redraw();
+  }
+
+  // Draws the window and requests that it be redrawn within the main window
+  fn draw_and_request_redraw(&mut self) {
 
     self.draw();
 
@@ -190,6 +239,13 @@ impl ChildWindow for StatusBar {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -204,10 +260,10 @@ impl ChildWindow for StatusBar {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -225,6 +281,20 @@ impl ChildWindow for StatusBar {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -270,6 +340,10 @@ impl ChildWindow for StatusBar {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -322,23 +396,30 @@ impl ChildWindow for StatusBar {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, _event: KeyEvent) {
+  fn handle_edit_action(&mut self, _action: EditAction) {
+  }
+
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
   }
-  fn handle_keyboard_released_event(&mut self, _event: KeyEvent) {
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, _button: MouseButton,
-        _mouse_x: f64, _mouse_y: f64) {
+        _mouse_x: f64, _mouse_y: f64, _modifiers: ModifiersState) {
   }
   fn handle_mouse_released(&mut self, _button: MouseButton,
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, _button: MouseButton,
+        _mouse_x: f64, _mouse_y: f64) {
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _main_win_x: f64, _main_win_y: f64) {
@@ -362,7 +443,7 @@ impl ChildWindow for StatusBar {
     let (_max_width, max_height) = self.window_base.get_max_size().unwrap(); // we can safely use unwrap(), because we know that it has been set
     self.window_base.set_max_size(width, max_height);
 
-    if width != win_width {
+    if self.window_base.is_theme_changed() || width != win_width {
 
       // Save the new width, but don't change the height
       self.window_base.set_size(width, win_height);
@@ -375,9 +456,10 @@ impl ChildWindow for StatusBar {
   }
 
   fn get_background_color(&self) -> Color {
-    self.window_base.get_background_color()
+    self.get_bg_color()
   }
   fn set_background_color(&mut self, color: Color) {
+    self.color_background = Some(color);
     self.window_base.set_background_color(color);
   }
 
@@ -7,19 +7,17 @@ use crate::child_window::{
   LayoutFill,
   LayoutType,
 };
+use crate::event_proxy::EventProxy;
 use crate::layout_base::LayoutBase;
 use crate::text_font::TextFont;
+use crate::theme::Theme;
 use crate::UserEvent;
 use crate::window_utils::WindowUtils;
 
-use winit::event_loop::EventLoopProxy;
-
 use tiny_skia::{
-  Color,
   Paint,
   PathBuilder,
   Pixmap,
-  PixmapPaint,
   Stroke,
   Transform,
 };
@@ -53,17 +51,18 @@ pub struct TabLayout {
   tab_top_height: f64,          // Height of the top of the tab (title and close button)
   max_width: Option<f64>,
   max_height: Option<f64>,
+  theme_generation: u64,        // generation of the theme that the close buttons' colors came from
 }
 
 impl TabLayout {
 
   pub fn new(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
   ) -> TabLayout {
 
     // Load the font
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", 14.0) {
+    let font = match TextFont::new_default() {
       Ok(font) => Some(font),
       Err(_err) => None,
     };
@@ -78,6 +77,7 @@ impl TabLayout {
       tab_top_height: 30.0,
       max_width: None,
       max_height: None,
+      theme_generation: Theme::get_generation(),
     }
   }
 
@@ -97,7 +97,7 @@ impl TabLayout {
           Some("x".to_string()),
           None,
           None,
-          Color::from_rgba8(191, 191, 191, 255),
+          self.layout_base.get_theme().palette.control,
           move || {
             WindowUtils::fire_user_event(
                   event_loop_clone.clone(),
@@ -110,6 +110,12 @@ impl TabLayout {
           }
     );
 
+    // Record the tab's contents in the registry, under the tab so that they
+    // are removed when it is closed
+    let event_loop = self.layout_base.get_event_loop();
+    event_loop.get_registry().borrow_mut().add_layout(layout_uuid, tab_uuid);
+    event_loop.register_child(tab_uuid, &contents);
+
     // Create the Tab
     let tab = Tab {
       uuid: tab_uuid,
@@ -129,6 +135,7 @@ impl TabLayout {
     self.active_tab = Some(tab_ref.uuid);
 
     // Request a redraw
+    self.layout_base.set_dirty(true);
     WindowUtils::request_full_redraw(
           self.layout_base.get_event_loop().clone(),
           self.layout_base.get_main_win_uuid()
@@ -171,6 +178,7 @@ impl TabLayout {
       Some(tab_rc) => {
         let tab_ref = tab_rc.borrow();
         self.active_tab = Some(tab_ref.uuid);
+        self.layout_base.set_dirty(true);
         WindowUtils::request_full_redraw(
               self.layout_base.get_event_loop().clone(),
               self.layout_base.get_main_win_uuid()
@@ -204,8 +212,10 @@ impl Layout for TabLayout {
   }
 
   fn clear(&mut self) {
+    self.layout_base.get_event_loop().unregister_children(self.layout_base.get_uuid());
     self.tabs.clear();
     self.active_tab = None;
+    self.layout_base.set_dirty(true);
   }
 
   /// The only layout that performs any actions in this method is the TabLayout
@@ -233,14 +243,16 @@ impl Layout for TabLayout {
 
     if index < self.tabs.len() {
 
-      // Remove the tab
-      self.tabs.remove(index);
+      // Remove the tab, along with its contents' entries in the registry and their listeners
+      let tab_uuid = self.tabs.remove(index).borrow().uuid;
+      self.layout_base.get_event_loop().unregister(tab_uuid);
 
       // Make the first tab the active tab
       let tab_ref = self.tabs.get(0).unwrap().borrow();
       self.active_tab = Some(tab_ref.uuid);
 
       // Request a redraw
+      self.layout_base.set_dirty(true);
       WindowUtils::request_full_redraw(
             self.layout_base.get_event_loop().clone(),
             self.layout_base.get_main_win_uuid()
@@ -270,7 +282,7 @@ impl Layout for TabLayout {
                 // If the location is on the close_button, return the button; otherwise,
                 // return None, but fire the TabSelected event
                 let btn_pixmap = tab_ref.close_btn.borrow_mut().redraw(x as f64, 2.0, 1.0, 1.0, false);   // width & height are ignore by Button
-                if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - btn_pixmap.width() as f32) as f64 &&
+                if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - WindowUtils::to_logical(btn_pixmap.width() as f64) as f32) as f64 &&
                       x <= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING) as f64 {
                   return Some(tab_ref.close_btn.clone());
                 } else {
@@ -289,7 +301,7 @@ impl Layout for TabLayout {
                 // If the location is on the close_button, return the button; otherwise,
                 // return None.
                 let btn_pixmap = tab_ref.close_btn.borrow_mut().redraw(x as f64, 2.0, 1.0, 1.0, false);   // width & height are ignore by Button
-                if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - btn_pixmap.width() as f32) as f64 &&
+                if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - WindowUtils::to_logical(btn_pixmap.width() as f64) as f32) as f64 &&
                       x <= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING) as f64 {
                   return Some(tab_ref.close_btn.clone());
                 } else {
@@ -302,7 +314,7 @@ impl Layout for TabLayout {
               // If the location is on the close_button, return the button; otherwise,
               // return None, but fire the TabSelected event
               let btn_pixmap = tab_ref.close_btn.borrow_mut().redraw(x as f64, 2.0, 1.0, 1.0, false);   // width & height are ignore by Button
-              if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - btn_pixmap.width() as f32) as f64 &&
+              if x >= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING - WindowUtils::to_logical(btn_pixmap.width() as f64) as f32) as f64 &&
                     x <= (tab_ref.top_start_x + tab_ref.top_width - TOP_RIGHT_PADDING) as f64 {
                 return Some(tab_ref.close_btn.clone());
               } else {
@@ -350,6 +362,37 @@ impl Layout for TabLayout {
     None
   }
 
+  /// Gets the focusable children within the active tab
+  fn get_focus_chain(&self) -> Vec<Rc<RefCell<dyn ChildWindow>>> {
+
+    let mut chain: Vec<Rc<RefCell<dyn ChildWindow>>> = Vec::new();
+
+    for tab in &self.tabs {
+
+      let tab_ref = tab.borrow();
+      if Some(tab_ref.uuid) != self.active_tab {
+        continue;
+      }
+
+      match &tab_ref.contents {
+
+        ChildType::Window(window) => {
+
+          let child_ref = window.borrow();
+          if child_ref.get_accepts_focus() && child_ref.get_enabled() {
+            chain.push(window.clone());
+          }
+        },
+
+        ChildType::Layout(layout) => {
+          chain.append(&mut layout.borrow().get_focus_chain());
+        },
+      }
+    }
+
+    chain
+  }
+
   /// Gets the child with the specified ID
   fn get_child_with_id(&mut self, uuid: Uuid) -> Option<Rc<RefCell<dyn ChildWindow>>> {
 
@@ -445,6 +488,11 @@ impl Layout for TabLayout {
 
   fn layout(&mut self, main_win_x: f64, main_win_y: f64, width: f64, height: f64) -> Pixmap {
 
+    // If nothing has changed since the layout was drawn in this area, reuse its pixmap
+    if !self.is_dirty() && self.layout_base.is_laid_out_at(main_win_x, main_win_y, width, height) {
+      return self.layout_base.get_pixmap();
+    }
+
     // Save the location
     self.layout_base.set_main_win_x(main_win_x);
     self.layout_base.set_main_win_y(main_win_y);
@@ -472,7 +520,7 @@ impl Layout for TabLayout {
     }
 
     // Create the pixmap into which we will draw
-    let mut pixmap = match Pixmap::new(width as u32, height as u32) {
+    let mut pixmap = match Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32) {
       Some(pixmap) => pixmap,
       None => {
         println!("In TabLayout::layout(), cannot create pixmap of size {width} x {height}");
@@ -480,10 +528,26 @@ impl Layout for TabLayout {
       },
     };
 
-    let paint = PixmapPaint::default();
+    // The edges are drawn in logical units
+    let scale = WindowUtils::get_scale_factor() as f32;
+    let transform = Transform::from_scale(scale, scale);
 
     // Create the stroke for drawing the tab edges
-    let stroke = Stroke::default();   // One pixel wide
+    let theme = self.layout_base.get_theme();
+    let stroke = Stroke {
+      width: theme.metrics.border_width,
+      ..Stroke::default()
+    };
+    let mut line_paint = Paint::default();
+    line_paint.set_color(theme.palette.line);
+
+    // The close buttons were created with the colors of the previous theme
+    if self.theme_generation != Theme::get_generation() {
+      self.theme_generation = Theme::get_generation();
+      for tab in &self.tabs {
+        tab.borrow().close_btn.borrow_mut().set_background_color(theme.palette.control);
+      }
+    }
 
     // Draw each tab's top containing the title and close button
     let mut x = 0.0;
@@ -501,7 +565,7 @@ impl Layout for TabLayout {
           path_builder.move_to(x, 0.0);
           path_builder.line_to(x, self.tab_top_height as f32);
           let path = path_builder.finish().unwrap();
-          pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+          pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
           x += 2.0;
 
           // Draw the text
@@ -509,28 +573,22 @@ impl Layout for TabLayout {
           let (drawn_width, _drawn_height) = font.draw_text(
             text.as_str(),
             &mut pixmap,
-            x as i32,
-            6,
-            Color::BLACK,
-            Color::WHITE,
+            WindowUtils::to_physical(x as f64) as i32,
+            WindowUtils::to_physical(6.0) as i32,
+            theme.palette.text,
+            theme.palette.background,
             -1,
-            Color::BLACK,
+            theme.palette.text,
             None
           );
-          x += drawn_width as f32;
+          x += WindowUtils::to_logical(drawn_width as f64) as f32;
 
           // Draw the close button
           x += 15.0;
           let btn_pixmap = tab_ref.close_btn.borrow_mut().redraw(x as f64, 2.0, 1.0, 1.0, false);   // width & height are ignore by Button
-          pixmap.draw_pixmap(
-              x as i32,
-              2,
-              btn_pixmap.as_ref(),
-              &paint,
-              Transform::identity(),
-              None,
-          );
-          x += btn_pixmap.width() as f32;
+          tab_ref.close_btn.borrow_mut().set_dirty(false);
+          WindowUtils::draw_child_pixmap(&mut pixmap, x as f64, 2.0, &btn_pixmap);
+          x += WindowUtils::to_logical(btn_pixmap.width() as f64) as f32;
 
           // Add some padding on the right
           x += TOP_RIGHT_PADDING;
@@ -544,14 +602,14 @@ impl Layout for TabLayout {
           path_builder.move_to(x, 0.0);
           path_builder.line_to(x, self.tab_top_height as f32);
           let path = path_builder.finish().unwrap();
-          pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+          pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
 
           // Draw the top edge of the tab top
           let mut path_builder = PathBuilder::new();
           path_builder.move_to(start_x, 0.0);
           path_builder.line_to(x, 0.0);
           let path = path_builder.finish().unwrap();
-          pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+          pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
 
           // If this is not the active tab, draw the bottom edge of the tab top
           match self.active_tab {
@@ -561,7 +619,7 @@ impl Layout for TabLayout {
                 path_builder.move_to(start_x, self.tab_top_height as f32);
                 path_builder.line_to(x, self.tab_top_height as f32);
                 let path = path_builder.finish().unwrap();
-                pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+                pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
               }
             },
             None => {
@@ -569,7 +627,7 @@ impl Layout for TabLayout {
               path_builder.move_to(start_x, self.tab_top_height as f32);
               path_builder.line_to(x, self.tab_top_height as f32);
               let path = path_builder.finish().unwrap();
-              pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+              pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
             }
           }
         }
@@ -582,7 +640,7 @@ impl Layout for TabLayout {
     path_builder.move_to(x, self.tab_top_height as f32);
     path_builder.line_to(width as f32, self.tab_top_height as f32);
     let path = path_builder.finish().unwrap();
-    pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+    pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
 
     // Calculate the upper left and lower right coordinates for the contents' frame
     let contents_frame_ul_x = 0.0;
@@ -597,7 +655,7 @@ impl Layout for TabLayout {
     path_builder.line_to(contents_frame_ul_x, contents_frame_lr_y);   // go to lower left corner
     path_builder.line_to(contents_frame_ul_x, contents_frame_ul_y);   // back to upper left corner
     let path = path_builder.finish().unwrap();
-    pixmap.stroke_path(&path, &Paint::default(), &stroke, Transform::identity(), None);
+    pixmap.stroke_path(&path, &line_paint, &stroke, transform, None);
 
     // Draw the contents of the active tab, leaving a one pixel margin all around it
     match self.active_tab {
@@ -618,13 +676,15 @@ impl Layout for TabLayout {
                 let mut child_ref = window.borrow_mut();
 
                 // Redraw the child
-                child_ref.redraw(
+                let child_pixmap = child_ref.redraw(
                       main_win_x,
                       main_win_y + self.tab_top_height + 2.0,
                       (contents_frame_lr_x - contents_frame_ul_x - 4.0) as f64,
                       (contents_frame_lr_y - contents_frame_ul_y - 4.0) as f64,
                       false
-                    )
+                    );
+                child_ref.set_dirty(false);
+                child_pixmap
               },
 
               ChildType::Layout(layout) => {
@@ -642,13 +702,11 @@ impl Layout for TabLayout {
               },
             };
 
-            pixmap.draw_pixmap(
-              contents_frame_ul_x as i32 + 2, // one for the frame and one for the margin
-              contents_frame_ul_y as i32 + 2,
-              child_pixmap.as_ref(),
-              &PixmapPaint::default(),
-              Transform::identity(),
-              None,
+            WindowUtils::draw_child_pixmap(
+              &mut pixmap,
+              contents_frame_ul_x as f64 + 2.0, // one for the frame and one for the margin
+              contents_frame_ul_y as f64 + 2.0,
+              &child_pixmap,
             );
           }
         }
@@ -658,7 +716,7 @@ impl Layout for TabLayout {
     }
 
     // Save the pixmap
-    self.layout_base.set_pixmap(pixmap.clone());
+    self.layout_base.set_laid_out(main_win_x, main_win_y, width, height, pixmap.clone());
 
     pixmap
   }
@@ -666,6 +724,7 @@ impl Layout for TabLayout {
   /// The only layout that performs any actions in this method is the TabLayout
   fn set_active_tab_by_uuid(&mut self, tab_uuid: Uuid) {
     self.active_tab = Some(tab_uuid);
+    self.layout_base.set_dirty(true);
     WindowUtils::request_full_redraw(self.layout_base.get_event_loop().clone(),
           self.layout_base.get_main_win_uuid());
   }
@@ -677,6 +736,7 @@ impl Layout for TabLayout {
   fn set_max_size(&mut self, width: f64, height: f64) {
     self.max_width = Some(width);
     self.max_height = Some(height);
+    self.layout_base.set_dirty(true);
   }
 
   /// LayoutBase pass-through functions
@@ -688,10 +748,10 @@ impl Layout for TabLayout {
     self.layout_base.set_uuid(uuid);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.layout_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.layout_base.set_event_loop(event_loop);
   }
 
@@ -709,6 +769,19 @@ impl Layout for TabLayout {
     self.layout_base.set_pixmap(pixmap);
   }
 
+  fn is_dirty(&self) -> bool {
+
+    // Only the active tab's contents are drawn
+    self.layout_base.is_dirty() || self.tabs.iter().any(|tab| {
+      let tab_ref = tab.borrow();
+      tab_ref.close_btn.try_borrow().map_or(true, |btn_ref| btn_ref.is_dirty()) ||
+            (Some(tab_ref.uuid) == self.active_tab && tab_ref.contents.is_dirty())
+    })
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.layout_base.set_dirty(dirty);
+  }
+
   fn get_main_win_x(&self) -> f64 {
     self.layout_base.get_main_win_x()
   }
@@ -1,11 +1,10 @@
 use winit::{
   event::{
-    KeyEvent,
     MouseButton,
     MouseScrollDelta,
     TouchPhase,
   },
-  event_loop::EventLoopProxy,
+  keyboard::ModifiersState,
   window::Window,
 };
 
@@ -27,12 +26,14 @@ use std::{
   rc::Rc,
 };
 use std::collections::HashMap;
-use winit::keyboard::{KeyCode, PhysicalKey};
 use crate::ChildType;
 use crate::ChildWindow;
-use crate::child_window::ContextMenuItem;
+use crate::child_window::{ContextMenuItem, EditAction, KeyboardEvent};
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::rich_text::RichText;
 use crate::list::SelectionMode;
+use crate::signal::ConnectionId;
 use crate::text_font::TextFont;
 use crate::UserEvent;
 use crate::window_base::WindowBase;
@@ -50,42 +51,35 @@ pub struct Table {
   font: Option<TextFont>,
   columns: Vec<Box<ColumnData>>,
   row_data: Vec<Vec<String>>,
-  line_color: Color,
-  header_bg_color: Color,
-  cell_bg_color: Color,
+  rich_cells: HashMap<(usize, usize), RichText>,  // key is the cell's row and column indexes
+  line_color: Option<Color>,        // None uses the theme's colors
+  header_bg_color: Option<Color>,
+  cell_bg_color: Option<Color>,
   selection_mode: SelectionMode,
   selected: Vec<usize>,
-  selected_color: Color,
-  ctrl_down: bool,
-  shift_down: bool,
   row_locations: HashMap<usize, f64>, // key is the row's zero-based index, value is the Y coordinate
-  char_width: u32,        // Width of a wide character
-  char_height: u32,
+  char_height: f64,
 }
 
 impl Table {
 
   pub fn new(
-    event_loop: Rc<EventLoopProxy<UserEvent>>,
+    event_loop: Rc<EventProxy>,
     main_win_uuid: Uuid,
   ) -> Self {
 
-    let mut char_width = 0;
-    let mut char_height = 0;
-    let font = match TextFont::new("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf", 14.0) {
+    let mut char_height = 0.0;
+    let font = match TextFont::new_default() {
       Ok(mut font) => {
-        char_width = font.get_max_char_width();
-        char_height = font.get_max_char_height();
+        char_height = WindowUtils::to_logical(font.get_max_char_height() as f64);
         Some(font)
       },
       Err(_err) => None,
     };
 
-    // Set the default header background color
-    let header_bg_color = Color::from_rgba8(50, 127, 150, 200);
-
     let mut window_base = WindowBase::new(event_loop.clone(), main_win_uuid);
     window_base.set_window_type("Table".to_string());
+    window_base.set_accepts_focus(true);
     window_base.set_size(200.0, 200.0);
 
     // Create the instance
@@ -94,16 +88,13 @@ impl Table {
       font: font,
       columns: Vec::new(),
       row_data: Vec::new(),
-      line_color: Color::BLACK,
-      header_bg_color: header_bg_color,
-      cell_bg_color: Color::WHITE,
+      rich_cells: HashMap::new(),
+      line_color: None,
+      header_bg_color: None,
+      cell_bg_color: None,
       selection_mode: SelectionMode::Single,
       selected: Vec::new(),
-      selected_color: Color::from_rgba8(32, 32, 150, 255),
-      ctrl_down: false,
-      shift_down: false,
       row_locations: HashMap::new(),
-      char_width: char_width,
       char_height: char_height,
     }
   }
@@ -125,6 +116,7 @@ impl Table {
 
     self.selected.clear();
     self.row_data.clear();
+    self.rich_cells.clear();
 
     self.draw();
 
@@ -158,28 +150,39 @@ impl Table {
     }
   }
 
+  /// Disconnects a listener that was connected with one of the on_ methods,
+  /// returning false if it isn't connected
+  pub fn disconnect(&self, id: ConnectionId) -> bool {
+    self.window_base.disconnect(id)
+  }
+
   fn draw(&mut self) {
 
-    let (width, height) = self.window_base.get_drawing_size();
+    let (width, height) = self.window_base.get_physical_drawing_size();
 
     // Create the pixmap into which we will draw
     let mut pixmap = Pixmap::new(width as u32, height as u32).unwrap();
 
+    // Colors that haven't been set come from the theme
+    let theme = self.window_base.get_theme();
+    let cell_bg_color = self.cell_bg_color.unwrap_or(theme.palette.base);
+    let header_bg_color = self.header_bg_color.unwrap_or(theme.palette.header);
+
     // Fill the pixmap with the cell background color
-    pixmap.fill(self.cell_bg_color);
+    pixmap.fill(cell_bg_color);
 
     // Set the border and cell divider colors
     let mut paint = Paint::default();
-    paint.set_color(self.line_color);
+    paint.set_color(self.line_color.unwrap_or(theme.palette.line));
     paint.anti_alias = true;
 
     // Set the header cell background color
     let mut header_bg_paint = Paint::default();
-    header_bg_paint.set_color(self.header_bg_color);
+    header_bg_paint.set_color(header_bg_color);
     header_bg_paint.anti_alias = true;
 
     // Draw the border
-    WindowUtils::draw_border(&mut pixmap, width, height, &paint);
+    WindowUtils::draw_border(&mut pixmap, width, height, &paint, theme.metrics.border_width);
 
     // For each column, find the widest value (header or data)
     match &self.font {
@@ -196,9 +199,11 @@ impl Table {
 
             let row = self.row_data.get(row_index).unwrap();
 
-            for cell_data in row {
-              let (bounds_width, _bounds_height) =
-                  font.get_bounds(cell_data.as_str(), None);
+            for (cell_index, cell_data) in row.iter().enumerate() {
+              let (bounds_width, _bounds_height) = match self.rich_cells.get(&(row_index, cell_index)) {
+                Some(rich_text) => font.get_rich_bounds(rich_text),
+                None => font.get_bounds(cell_data.as_str(), None),
+              };
               if bounds_width as f32 > col_data.width {
                 col_data.width = bounds_width as f32;
               }
@@ -212,7 +217,7 @@ impl Table {
     }
 
     // Calculate the maximum number of visible rows
-    let visible_row_count = height / self.char_height as f64;
+    let visible_row_count = WindowUtils::to_logical(height) / self.char_height;
     
     // Calculate the padding that each column will receive
     let mut total_width: f64 = 0.0;
@@ -240,13 +245,13 @@ impl Table {
     }
 
     // Set the padding for the rows
-    let row_padding: f64 = 10.0;
+    let row_padding: f64 = WindowUtils::to_physical(10.0);
     let row_height = match &self.font {
       Some(font) => {
         let (_bounds_width, bounds_height) = font.get_bounds("Wy", None);
-        bounds_height as f64 + 4.0   // add margins
+        bounds_height as f64 + WindowUtils::to_physical(4.0)   // add margins
       },
-      None => 18.0,
+      None => WindowUtils::to_physical(18.0),
     };
 
     // Draw the row horizontal dividers
@@ -293,10 +298,10 @@ impl Table {
             &mut pixmap,
             (col_data.x as i32) + 2,
             y,
-            Color::BLACK,
-            self.header_bg_color,
+            theme.palette.text,
+            header_bg_color,
             -1,
-            Color::BLACK,
+            theme.palette.text,
             None
           );
 
@@ -310,27 +315,41 @@ impl Table {
 
             // Set the background color based on whether this item is selected
 //            let mut bg_color = Color::WHITE;
-            let mut bg_color = Color::BLACK;
+            let mut bg_color = theme.palette.text;
             if self.selected.iter().any(|&i| i == row_index) {
-              bg_color = self.selected_color;
+              bg_color = theme.palette.selected_item;
             }
 
             // Draw this row's data for this column
             y += row_height as i32;
             y += row_padding as i32;
-            font.draw_text(
-              &row[col_index],
-              &mut pixmap,
-              (col_data.x as i32) + 2,
-              y,
-//              Color::BLACK,
-//              bg_color,
-              bg_color,
-              Color::WHITE,
-              -1,
-              Color::BLACK,
-              None
-            );
+            match self.rich_cells.get(&(row_index, col_index)) {
+              Some(rich_text) => {
+                font.draw_rich_text(
+                  rich_text,
+                  &mut pixmap,
+                  (col_data.x as i32) + 2,
+                  y,
+                  bg_color,
+                  cell_bg_color,
+                );
+              },
+              None => {
+                font.draw_text(
+                  &row[col_index],
+                  &mut pixmap,
+                  (col_data.x as i32) + 2,
+                  y,
+//                  Color::BLACK,
+//                  bg_color,
+                  bg_color,
+                  cell_bg_color,
+                  -1,
+                  theme.palette.text,
+                  None
+                );
+              },
+            }
 
             // Save this row's location (just the Y coordinate)
             if 0 == col_index {
@@ -350,6 +369,28 @@ impl Table {
     self.window_base.set_pixmap(pixmap);
   }
 
+  // Returns the index of the row at a Y coordinate relative to the main window
+  fn get_row_index_at(&self, mouse_y: f64) -> usize {
+
+    // Convert the mouse coordinates the coordinates relative to the top of the window
+    let (_x, y) = self.window_base.get_location();
+    let win_y = WindowUtils::to_physical(mouse_y - y);
+
+    let mut row_index = self.window_base.get_y_scroll() as usize;
+    for _i in 0..self.row_locations.len() {
+      // Start search if this row's Y coordinate is greater than the mouse location
+      if self.row_locations[&row_index] > win_y {
+        break;
+      }
+      row_index += 1;
+    }
+    if row_index > 0 {
+      row_index -= 1;   // The mouse is on the previous row
+    }
+
+    row_index
+  }
+
   /// Gets the data from the row with the specified zero-based index
   pub fn get_row_by_index(&self, index: usize) -> Option<&Vec<String>> {
 
@@ -361,16 +402,67 @@ impl Table {
     self.selected.clone()
   }
 
+  /// Calls a function each time the user chooses to process the selected
+  /// rows, such as by double-clicking one
+  pub fn on_process_selected_items<F: Fn() + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      let matched = matches!(event, UserEvent::ProcessSelectedItems(..));
+      if matched {
+        listener();
+      }
+      matched
+    }))
+  }
+
+  /// Calls a function each time the selected rows change
+  pub fn on_selection_changed<F: Fn() + 'static>(&self, listener: F) -> ConnectionId {
+    self.window_base.connect(Rc::new(move |event| {
+      let matched = matches!(event, UserEvent::SelectionChanged(..));
+      if matched {
+        listener();
+      }
+      matched
+    }))
+  }
+
+  /// Sets the text of a cell to text whose runs have their own fonts, colors, and decorations
+  ///
+  /// row and column are zero-based indexes. The row's data, returned by
+  /// get_row_by_index(), contains the text without its styles. If there is
+  /// no such cell, the returned Result contains a string explaining why.
+  pub fn set_cell_rich_text(&mut self, row: usize, column: usize, text: RichText) -> Result<(), String> {
+
+    let cell = match self.row_data.get_mut(row).and_then(|row_data| row_data.get_mut(column)) {
+      Some(cell) => cell,
+      None => return Err(format!("There is no cell at row {row}, column {column}")),
+    };
+    *cell = text.get_text();
+    self.rich_cells.insert((row, column), text);
+
+    self.draw();
+
+    let (x, y) = self.window_base.get_location();
+    WindowUtils::request_redraw(
+          self.window_base.get_event_loop().clone(),
+          self.window_base.get_main_win_uuid(),
+          x,
+          y,
+          self.window_base.get_pixmap()
+    );
+
+    Ok(())
+  }
+
   pub fn set_cell_bg_color(&mut self, color: Color) {
-    self.cell_bg_color = color;
+    self.cell_bg_color = Some(color);
   }
 
   pub fn set_header_bg_color(&mut self, color: Color) {
-    self.header_bg_color = color;
+    self.header_bg_color = Some(color);
   }
 
   pub fn set_line_color(&mut self, color: Color) {
-    self.line_color = color;
+    self.line_color = Some(color);
   }
 
   /// Sets the selection mode
@@ -411,6 +503,13 @@ impl ChildWindow for Table {
     self.window_base.get_pixmap()
   }
 
+  fn is_dirty(&self) -> bool {
+    self.window_base.is_dirty()
+  }
+  fn set_dirty(&mut self, dirty: bool) {
+    self.window_base.set_dirty(dirty);
+  }
+
   fn get_name(&self) -> String {
     self.window_base.get_name()
   }
@@ -425,10 +524,10 @@ impl ChildWindow for Table {
     self.window_base.set_window_type(window_type);
   }
 
-  fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  fn get_event_loop(&self) -> Rc<EventProxy> {
     self.window_base.get_event_loop()
   }
-  fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.window_base.set_event_loop(event_loop);
   }
 
@@ -446,6 +545,20 @@ impl ChildWindow for Table {
     self.window_base.set_focused(focused);
   }
 
+  fn get_accepts_focus(&self) -> bool {
+    self.window_base.get_accepts_focus()
+  }
+  fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.window_base.set_accepts_focus(accepts_focus);
+  }
+
+  fn get_accepts_tab(&self) -> bool {
+    self.window_base.get_accepts_tab()
+  }
+  fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.window_base.set_accepts_tab(accepts_tab);
+  }
+
   fn get_location(&self) -> (f64, f64) {
     self.window_base.get_location()
   }
@@ -491,6 +604,10 @@ impl ChildWindow for Table {
     self.window_base.get_drawing_size()
   }
 
+  fn get_height_for_width(&self, _width: f64) -> Option<f64> {
+    None
+  }
+
   fn get_x_scroll(&self) -> f64 {
     self.window_base.get_x_scroll()
   }
@@ -558,39 +675,16 @@ impl ChildWindow for Table {
     self.window_base.set_text(text);
   }
 
-  fn handle_keyboard_pressed_event(&mut self, event: KeyEvent) {
-
-    match event.physical_key {
-
-      PhysicalKey::Code(key_code) => {
-        if KeyCode::ControlLeft == key_code || KeyCode::ControlRight == key_code {
-          self.ctrl_down = true;
-        } else if KeyCode::ShiftLeft == key_code || KeyCode::ShiftRight == key_code {
-          self.shift_down = true;
-        }
-      },
-
-      PhysicalKey::Unidentified(_native_key_code) => {},
-    }
+  fn handle_edit_action(&mut self, _action: EditAction) {
   }
-  fn handle_keyboard_released_event(&mut self, event: KeyEvent) {
-
-    match event.physical_key {
-
-      PhysicalKey::Code(key_code) => {
-        if KeyCode::ControlLeft == key_code || KeyCode::ControlRight == key_code {
-          self.ctrl_down = false;
-        } else if KeyCode::ShiftLeft == key_code || KeyCode::ShiftRight == key_code {
-          self.shift_down = false;
-        }
-      },
 
-      PhysicalKey::Unidentified(_native_key_code) => {},
-    }
+  fn handle_keyboard_pressed_event(&mut self, _event: KeyboardEvent, _modifiers: ModifiersState) {
+  }
+  fn handle_keyboard_released_event(&mut self, _event: KeyboardEvent) {
   }
 
   fn handle_mouse_pressed(&mut self, button: MouseButton,
-        mouse_x: f64, mouse_y: f64) {
+        mouse_x: f64, mouse_y: f64, modifiers: ModifiersState) {
 
     // Right mouse click shows the context menu
     if MouseButton::Right == button {
@@ -602,22 +696,10 @@ impl ChildWindow for Table {
 
       Some(_font) => {
 
-        // Convert the mouse coordinates the coordinates relative to the top of the window
         let (x, y) = self.window_base.get_location();
-        let win_y = mouse_y - y;
 
         // Get the index of the item's row that was clicked
-        let mut row_index = self.window_base.get_y_scroll() as usize;
-        for _i in 0..self.row_locations.len() {
-          // Start search if this row's Y coordinate is greater than the mouse location
-          if self.row_locations[&row_index] > win_y {
-            break;
-          }
-          row_index += 1;
-        }
-        if row_index > 0 {
-          row_index -= 1;   // The mouse is on the previous row
-        }
+        let row_index = self.get_row_index_at(mouse_y);
 
         // If this item is already selected, deselect it; otherwise,
         // save the selection.
@@ -642,11 +724,11 @@ impl ChildWindow for Table {
 
                 SelectionMode::Multiple => {
 
-                  if !self.ctrl_down && !self.shift_down {
+                  if !modifiers.control_key() && !modifiers.shift_key() {
                     self.selected.clear();
                   }
 
-                  if self.shift_down {
+                  if modifiers.shift_key() {
 
                     // Sort the vector of indices of selected items
                     self.selected.sort();
@@ -694,7 +776,7 @@ impl ChildWindow for Table {
                         j += 1;
                       }
                     }
-                  } else if self.ctrl_down {
+                  } else if modifiers.control_key() {
 
                     // If this item is already selected, unselect it; otherwise,
                     // select it.
@@ -741,11 +823,54 @@ impl ChildWindow for Table {
         _mouse_x: f64, _mouse_y: f64) {
   }
 
-  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_double_click(&mut self, button: MouseButton,
+        _mouse_x: f64, mouse_y: f64) {
+
+    if MouseButton::Left != button {
+      return;
+    }
+
+    let row_index = self.get_row_index_at(mouse_y);
+    if row_index >= self.row_data.len() {
+      return;
+    }
+
+    // The second click toggled the row's selection off, so select it again
+    if !self.selected.contains(&row_index) {
+
+      match self.selection_mode {
+        SelectionMode::Single => self.selected.clear(),
+        SelectionMode::Multiple => {},
+      }
+      self.selected.push(row_index);
+
+      // Redraw the table so that the highlighted items is updated
+      self.draw();
+      let (x, y) = self.window_base.get_location();
+      WindowUtils::request_redraw(
+            self.window_base.get_event_loop().clone(),
+            self.window_base.get_main_win_uuid(),
+            x,
+            y,
+            self.window_base.get_pixmap()
+      );
+    }
+
+    // Open the selected items
+    WindowUtils::fire_user_event(
+      self.window_base.get_event_loop().clone(),
+      UserEvent::ProcessSelectedItems(
+            self.window_base.get_main_win_uuid(),
+            self.window_base.get_uuid()
+      )
+    );
+  }
+
+  fn handle_mouse_drag(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_start(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
-  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64) {
+  fn handle_mouse_drag_end(&mut self, _main_win_x: f64, _main_win_y: f64, _modifiers: ModifiersState) {
   }
 
   fn handle_mouse_movement(&mut self, _x: f64, _y: f64) {
@@ -765,7 +890,7 @@ impl ChildWindow for Table {
 
     // Has the size changed?
     let (win_width, win_height) = self.window_base.get_drawing_size();
-    if force || width != win_width || height != win_height {
+    if force || self.window_base.is_theme_changed() || width != win_width || height != win_height {
 
       // Save the new size
       self.window_base.set_size(width, height);
@@ -806,8 +931,8 @@ impl ChildWindow for Table {
     self.set_x_scroll_max(0.0);
     self.set_x_scroll(0.0);
     self.set_y_scroll_min(0.0);
-    if self.row_data.len() as f64 > win_height / self.char_height as f64 {
-      self.set_y_scroll_max(self.row_data.len() as f64 - (win_height / self.char_height as f64));
+    if self.row_data.len() as f64 > win_height / self.char_height {
+      self.set_y_scroll_max(self.row_data.len() as f64 - (win_height / self.char_height));
     } else {
       self.set_y_scroll_max(0.0);
     }
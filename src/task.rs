@@ -0,0 +1,249 @@
+use uuid::Uuid;
+
+use std::{
+  any::Any,
+  collections::HashMap,
+  future::Future,
+  panic::{self, AssertUnwindSafe},
+  pin::pin,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, Sender},
+    Arc,
+    Mutex,
+    OnceLock,
+  },
+  task::{Context, Poll, Wake, Waker},
+  thread::{self, Thread},
+  time::Duration,
+};
+
+use crate::MainApp;
+use crate::ui_handle::UiHandle;
+
+// How often a task that is waiting checks whether it has been cancelled
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+// Largest number of threads that run the tasks
+const MAX_WORKERS: usize = 8;
+
+// Threads that run the tasks, which are started when the first task is spawned
+static POOL: OnceLock<TaskPool> = OnceLock::new();
+
+/// Identifies a task that was started with MainApp::spawn()
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TaskId(u64);
+
+// Function that is given a finished task's output on the UI thread
+type CompletionCallback = Box<dyn FnOnce(&mut MainApp, Box<dyn Any + Send>)>;
+
+// Work that a thread in the pool performs
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Task {
+  owner: Option<Uuid>,            // window whose closing cancels the task
+  cancelled: Arc<AtomicBool>,
+  on_complete: CompletionCallback,
+}
+
+/// Tasks of a MainApp that are running in the background
+#[derive(Default)]
+pub(crate) struct Tasks {
+  next_id: u64,
+  tasks: HashMap<TaskId, Task>,
+}
+
+impl Tasks {
+
+  /// Runs a future on a background thread, and then calls on_complete with
+  /// its output on the UI thread
+  ///
+  /// If owner is set, the task is cancelled when that pop-up or tab is closed.
+  pub fn spawn<T, F, C>(
+        &mut self,
+        ui_handle: UiHandle,
+        owner: Option<Uuid>,
+        future: F,
+        on_complete: C
+  ) -> TaskId
+  where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+    C: FnOnce(&mut MainApp, T) + 'static,
+  {
+
+    let id = TaskId(self.next_id);
+    self.next_id += 1;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    self.tasks.insert(id, Task {
+      owner,
+      cancelled: cancelled.clone(),
+      on_complete: Box::new(move |app, output| {
+        if let Ok(output) = output.downcast::<T>() {
+          on_complete(app, *output);
+        }
+      }),
+    });
+
+    TaskPool::get().execute(Box::new(move || {
+
+      // Send the output to the UI thread, unless the task was cancelled. A
+      // future that panics fails its task, and the thread carries on.
+      let result = match panic::catch_unwind(AssertUnwindSafe(|| TaskPool::block_on(future, &cancelled))) {
+        Ok(Some(output)) => ui_handle.run(move |app| app.finish_task(id, Box::new(output))),
+        Ok(None) => Ok(()),
+        Err(payload) => {
+          let message = TaskPool::get_panic_message(payload.as_ref());
+          ui_handle.run(move |app| app.fail_task(id, message))
+        },
+      };
+      if let Err(err) = result {
+        println!("Cannot send the result of a task to the UI thread: {err}");
+      }
+    }));
+
+    id
+  }
+
+  /// Cancels a task, returning false if it has already finished or been cancelled
+  ///
+  /// Its future is dropped the next time it is waiting, and its completion
+  /// callback isn't called.
+  pub fn cancel(&mut self, id: TaskId) -> bool {
+
+    match self.tasks.remove(&id) {
+      Some(task) => {
+        task.cancelled.store(true, Ordering::SeqCst);
+        true
+      },
+      None => false,
+    }
+  }
+
+  /// Cancels the tasks that belong to a window, returning how many there were
+  pub fn cancel_owned_by(&mut self, owner: Uuid) -> usize {
+
+    let ids: Vec<TaskId> = self.tasks.iter()
+      .filter(|(_id, task)| task.owner == Some(owner))
+      .map(|(id, _task)| *id)
+      .collect();
+
+    ids.into_iter().filter(|id| self.cancel(*id)).count()
+  }
+
+  /// Gets the number of tasks that haven't finished or been cancelled
+  pub fn get_count(&self) -> usize {
+    self.tasks.len()
+  }
+
+  /// Removes a task that has finished, returning its completion callback
+  pub fn take_completion(&mut self, id: TaskId) -> Option<CompletionCallback> {
+    self.tasks.remove(&id).map(|task| task.on_complete)
+  }
+}
+
+// Threads that run the tasks' futures
+struct TaskPool {
+  sender: Mutex<Sender<Job>>,
+}
+
+impl TaskPool {
+
+  // Gets the pool, starting its threads the first time
+  fn get() -> &'static TaskPool {
+
+    POOL.get_or_init(|| {
+
+      let (sender, receiver) = mpsc::channel::<Job>();
+      let receiver = Arc::new(Mutex::new(receiver));
+      let workers = thread::available_parallelism().map(|count| count.get()).unwrap_or(2).clamp(2, MAX_WORKERS);
+      for index in 0..workers {
+        let receiver = receiver.clone();
+        let spawned = thread::Builder::new()
+          .name(format!("fenetre-task-{index}"))
+          .spawn(move || TaskPool::run_worker(receiver));
+        if let Err(err) = spawned {
+          println!("Cannot start a thread for running tasks: {err}");
+        }
+      }
+
+      TaskPool {
+        sender: Mutex::new(sender),
+      }
+    })
+  }
+
+  // Runs a future on the current thread until it finishes or is cancelled
+  //
+  // The thread sleeps while the future is waiting, until it is woken or it
+  // is time to check whether the task has been cancelled.
+  fn block_on<F: Future>(future: F, cancelled: &AtomicBool) -> Option<F::Output> {
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+
+      if cancelled.load(Ordering::SeqCst) {
+        return None;
+      }
+
+      match future.as_mut().poll(&mut context) {
+        Poll::Ready(output) => return Some(output),
+        Poll::Pending => thread::park_timeout(CANCEL_CHECK_INTERVAL),
+      }
+    }
+  }
+
+  // Gets the message that a future panicked with
+  fn get_panic_message(payload: &(dyn Any + Send)) -> String {
+
+    if let Some(message) = payload.downcast_ref::<&str>() {
+      message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+      message.clone()
+    } else {
+      "unknown panic".to_string()
+    }
+  }
+
+  // Queues a job for the next thread that is free
+  fn execute(&self, job: Job) {
+
+    let result = match self.sender.lock() {
+      Ok(sender) => sender.send(job).map_err(|err| format!("{err}")),
+      Err(err) => Err(format!("{err}")),
+    };
+    if let Err(err) = result {
+      println!("Cannot start a task: {err}");
+    }
+  }
+
+  // Performs the jobs that are queued, one at a time
+  fn run_worker(receiver: Arc<Mutex<Receiver<Job>>>) {
+
+    loop {
+
+      let job = match receiver.lock() {
+        Ok(receiver) => receiver.recv(),
+        Err(_err) => return,
+      };
+
+      match job {
+        Ok(job) => job(),
+        Err(_err) => return,
+      }
+    }
+  }
+}
+
+// Wakes the thread that is running a future
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+  fn wake(self: Arc<Self>) {
+    self.0.unpark();
+  }
+}
@@ -1,186 +1,346 @@
-use fontdue::{Font};
+use fontdue::{Font, Metrics};
 
 use tiny_skia::{
   Color,
+  ColorU8,
+  FilterQuality,
   Paint,
   PathBuilder,
   Pixmap,
   PixmapPaint,
+  Rect,
   Stroke,
   Transform,
 };
 
-pub struct TextFont {
+use crate::rich_text::RichText;
+use crate::window_utils::WindowUtils;
+
+/// Font used when an application doesn't specify one, which is bundled so that builds are reproducible
+const BUNDLED_FONT_DATA: &[u8] = include_bytes!("../resources/FreeMonoBold.ttf");
+
+/// Size of the default font, in points, when an application doesn't specify one
+pub const DEFAULT_FONT_SIZE: f32 = 14.0;
+
+/// Largest number of rasterized glyphs that are kept for each font, at all of its sizes
+pub const MAX_CACHED_GLYPHS: usize = 4096;
+
+// Horizontal distance that italic glyphs are slanted by, per pixel above the baseline
+const ITALIC_SLANT: f32 = 0.2;
+
+// A parsed font and the glyphs that have been rasterized with it. It is shared
+// by all of the TextFonts that use the same font, whatever their size.
+struct SharedFont {
   font: Font,
+  glyphs: Mutex<GlyphCache>,
+}
+
+// Metrics and coverage data of a rasterized glyph
+type Glyph = Arc<(Metrics, Vec<u8>)>;
+
+// Glyphs that have been rasterized with a font. When it is full, the glyphs
+// that were used least recently are dropped.
+#[derive(Default)]
+struct GlyphCache {
+  glyphs: HashMap<(char, u32), (Glyph, u64)>,  // glyph and when it was last used, by character and font size bits
+  clock: u64,                                  // incremented each time a glyph is used
+}
+
+impl GlyphCache {
+
+  // Gets a glyph, rasterizing it if it isn't in the cache
+  fn get_or_rasterize<F: FnOnce() -> (Metrics, Vec<u8>)>(&mut self, key: (char, u32), rasterize: F) -> Glyph {
+
+    self.clock += 1;
+    if let Some((glyph, last_used)) = self.glyphs.get_mut(&key) {
+      *last_used = self.clock;
+      return glyph.clone();
+    }
+
+    if self.glyphs.len() >= MAX_CACHED_GLYPHS {
+      self.evict();
+    }
+
+    let glyph = Arc::new(rasterize());
+    self.glyphs.insert(key, (glyph.clone(), self.clock));
+
+    glyph
+  }
+
+  // Drops the quarter of the glyphs that were used least recently, so that
+  // the cache isn't searched for every new glyph once it is full
+  fn evict(&mut self) {
+
+    let mut last_used: Vec<u64> = self.glyphs.values().map(|(_glyph, last_used)| *last_used).collect();
+    let count = self.glyphs.len() - MAX_CACHED_GLYPHS * 3 / 4;
+    let (_older, oldest_kept, _newer) = last_used.select_nth_unstable(count);
+    let oldest_kept = *oldest_kept;
+
+    self.glyphs.retain(|_key, (_glyph, last_used)| *last_used >= oldest_kept);
+  }
+}
+
+// Fonts that have been parsed, by their source and their index within it
+static FONT_CACHE: LazyLock<Mutex<HashMap<(String, u32), Arc<SharedFont>>>> =
+      LazyLock::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+  // Font used by TextFont::new_default(); the bundled font is used if there is none
+  static DEFAULT_FONT: RefCell<Option<Arc<SharedFont>>> = const { RefCell::new(None) };
+  static DEFAULT_SIZE: Cell<f32> = const { Cell::new(DEFAULT_FONT_SIZE) };
+}
+
+/// A font at a particular size
+///
+/// The text is drawn at the size multiplied by the scale factor, so the sizes
+/// and locations that a TextFont gives are in physical pixels.
+pub struct TextFont {
+  font: Arc<SharedFont>,
   font_path: String,
   font_size: f32,
   max_char_height: u32,
   max_char_width: u32,
+  max_char_scale: f64,      // scale factor that the maximum character size was measured at
 }
 
+use unicode_segmentation::UnicodeSegmentation;
+
 use std::{
+  cell::{Cell, RefCell},
+  collections::HashMap,
   fmt::Debug,
+  fs,
+  hash::{DefaultHasher, Hash, Hasher},
+  ops::Range,
+  sync::{Arc, LazyLock, Mutex},
 };
 
 impl TextFont {
 
+  /// Loads a font from a TrueType or OpenType file
+  ///
+  /// Fonts are cached, so loading a file that has already been loaded, at any
+  /// size, doesn't read it again. If the file cannot be read or is not a font,
+  /// the returned Result contains a string explaining why.
   pub fn new(font_path: &str, font_size: f32) -> Result<TextFont, String> {
+    TextFont::from_file(font_path, 0, font_size)
+  }
 
-    // Load the font
-    let font = include_bytes!("/usr/share/fonts/truetype/freefont/FreeMonoBold.ttf") as &[u8];
+  /// Loads a font from the contents of a TrueType or OpenType file
+  pub fn from_bytes(data: &[u8], font_size: f32) -> Result<TextFont, String> {
+    TextFont::from_collection(data, 0, font_size)
+  }
 
-    // Parse it into the font type.
-    let font = fontdue::Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
+  /// Loads one of the fonts in the contents of a TrueType or OpenType collection
+  ///
+  /// index is the font's index within the collection; it is 0 for a file
+  /// that contains a single font.
+  pub fn from_collection(data: &[u8], index: u32, font_size: f32) -> Result<TextFont, String> {
+
+    // The data is identified by its contents
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    let key = format!("(bytes {:x})", hasher.finish());
 
-    Ok(Self {
-          font: font,
-          font_path: font_path.to_string(),
-          font_size: font_size,
-          max_char_width: 0,
-          max_char_height: 0,
-    })
+    let font = TextFont::get_shared_font(&key, index, || Ok(data.to_vec()))?;
+
+    Ok(TextFont::with_shared_font(font, "(bytes)", font_size))
   }
 
-  // Returns a Pixmap the contains the specified character as an image.
-  //
-  // returns:
-  //  Pixmap - the character's Pixmap
-  //  u32 - number of pixels above the baseline
-  //  u32 - number of pixels below the baseline
-  fn draw_character(
-    &self,
-    ch: char,
-    red: u8,
-    green: u8,
-    blue: u8,
-    bg_color: Color,
-    fixed_size: Option<(u32, u32)>,
-  ) -> (Pixmap, u32, u32) {
+  // Loads one of the fonts in a TrueType or OpenType file or collection
+  pub(crate) fn from_file(font_path: &str, index: u32, font_size: f32) -> Result<TextFont, String> {
 
-    // Rasterize and get the layout metrics for the letter at the current
-    // font size.
-    //
-    // metrics is a Metrics and contains the sizing and positioning metadata
-    // for the rasterized glyph.
-    //
-    // coverage_data is Vec<u8> and contains the coverage vector for the glyph.
-    // Coverage is a linear scale where 0 represents 0% coverage of that pixel
-    // by the glyph and 255 represents 100% coverage. The vec starts at
-    // the top left corner of the glyph.
-    let (metrics, coverage_data) = self.font.rasterize(ch, self.font_size);
-
-    // Determine how many pixels are below the baseline. Some characters, like
-    // '~' and '-', have a positive value, which indicates how far down the
-    // character's pixmap needs to be shifted. Otherwise, the value is the
-    // depth below the baseline the pixmap extends.
-    let mut shift_down = 0;
-    let mut pixels_below = 0;
-    let mut pixels_above = metrics.height as u32;
-    if metrics.ymin < 0 {
-      pixels_below = -metrics.ymin;
-      // If any part of the character is below the baseline, shift the character
-      // down by filling in the top of the Pixmap.
-      shift_down = pixels_below;
-    } else if 0 < metrics.ymin {
-      pixels_above = (metrics.height as i32 + metrics.ymin) as u32;
+    let font = TextFont::get_shared_font(font_path, index, || {
+      match fs::read(font_path) {
+        Ok(data) => Ok(data),
+        Err(err) => Err(format!("Cannot read the font {font_path}: {err}")),
+      }
+    })?;
+
+    Ok(TextFont::with_shared_font(font, font_path, font_size))
+  }
+
+  /// Loads the default font at the default size
+  ///
+  /// This is the font that the windows use. Unless it has been changed with
+  /// set_default_font(), it is the bundled FreeMonoBold.
+  pub fn new_default() -> Result<TextFont, String> {
+
+    let font_size = DEFAULT_SIZE.with(|size| size.get());
+    match DEFAULT_FONT.with(|font| font.borrow().clone()) {
+      Some(font) => Ok(TextFont::with_shared_font(font, "(default)", font_size)),
+      None => {
+        let font = TextFont::get_shared_font("(bundled FreeMonoBold.ttf)", 0, || Ok(BUNDLED_FONT_DATA.to_vec()))?;
+        Ok(TextFont::with_shared_font(font, "FreeMonoBold.ttf", font_size))
+      },
     }
-    let mut full_height = metrics.height as i32 + pixels_below;
-    if 0 == full_height {   // Spaces have a height of 0
-      full_height = 1;
+  }
+
+  // Gets a font from the cache, parsing it if this is the first time it is used
+  //
+  // name identifies where the data comes from, and read_data gets the data if
+  // the font is not in the cache.
+  fn get_shared_font<F: FnOnce() -> Result<Vec<u8>, String>>(
+        name: &str,
+        index: u32,
+        read_data: F
+  ) -> Result<Arc<SharedFont>, String> {
+
+    let mut fonts = FONT_CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    let key = (name.to_string(), index);
+    if let Some(font) = fonts.get(&key) {
+      return Ok(font.clone());
     }
 
-    // Create a Pixmap for the image
-    let mut pixmap;
-    match fixed_size {
+    let data = read_data()?;
+    let settings = fontdue::FontSettings {
+      collection_index: index,
+      ..fontdue::FontSettings::default()
+    };
 
-      Some((width, height)) => {
-        if width < metrics.width as u32 {
-          pixmap = Pixmap::new(metrics.width as u32, height as u32).unwrap();
-        } else {
-          pixmap = match Pixmap::new(width, height as u32) {
+    // Parse it into the font type.
+    let font = match fontdue::Font::from_bytes(data, settings) {
+      Ok(font) => font,
+      Err(err) => return Err(format!("Cannot load the font {name}: {err}")),
+    };
 
-            Some(pixmap) => pixmap,
+    let font = Arc::new(SharedFont {
+      font,
+      glyphs: Mutex::new(GlyphCache::default()),
+    });
+    fonts.insert(key, font.clone());
 
-            None => {
-              println!("Cannot create a Pixmap of size {width} x {height}");
-              Pixmap::new(metrics.width as u32, height as u32).unwrap()
-            },
-          }
-        }
-      },
+    Ok(font)
+  }
 
-      None => {
+  // Forgets the maximum character size if it was measured at a different scale factor
+  fn check_max_char_scale(&mut self) {
 
-        pixmap = match Pixmap::new(
-              metrics.width as u32,
-              full_height as u32
-        ) {
-          Some(pixmap) => pixmap,
-
-          None => {
-            if ' ' == ch {
-              return (Pixmap::new(8, 16).unwrap(), 16, 0);
-            } else {
-              return (Pixmap::new(1, 1).unwrap(), 1, 0);
-            }
-          },
-        }
-      },
-    };
-    let pixmap_width = pixmap.width();
+    let scale_factor = WindowUtils::get_scale_factor();
+    if self.max_char_scale != scale_factor {
+      self.max_char_scale = scale_factor;
+      self.max_char_width = 0;
+      self.max_char_height = 0;
+    }
+  }
 
-    // Fill in the background color
-    pixmap.fill(bg_color);
+  /// Returns true if both TextFonts use the same font, whatever their sizes
+  pub fn is_same_font(&self, other: &TextFont) -> bool {
+    Arc::ptr_eq(&self.font, &other.font)
+  }
 
-    let pixels = pixmap.data_mut();
+  // Gets the coverage data for a character, rasterizing it if this is the first time it is drawn at this size
+  fn rasterize(&self, ch: char) -> Glyph {
 
-    // If any part of the character is below the baseline, shift the character
-    // down by filling in the top of the Pixmap.
-    let mut pixel_index = 0;
-    let mut i = 0;
-    while i < shift_down {
+    let mut glyphs = self.font.glyphs.lock().unwrap_or_else(|err| err.into_inner());
+    let font_size = self.get_scaled_size();
+    glyphs.get_or_rasterize((ch, font_size.to_bits()), || self.font.font.rasterize(ch, font_size))
+  }
 
-      let mut col = 0;
-      while col < pixmap_width && pixel_index < pixels.len() - 4 {
+  /// Gets the number of glyphs of this font that are cached, at all of its sizes
+  ///
+  /// It is at most MAX_CACHED_GLYPHS.
+  pub fn get_cached_glyph_count(&self) -> usize {
+    self.font.glyphs.lock().unwrap_or_else(|err| err.into_inner()).glyphs.len()
+  }
 
-        pixels[pixel_index] = 0;
-        pixels[pixel_index + 1] = 0;
-        pixels[pixel_index + 2] = 0;
-        pixels[pixel_index + 3] = 0;
+  /// Replaces the font that is used by the windows created after this is called
+  ///
+  /// The font's size is not used; it is set with set_default_font_size().
+  pub fn set_default_font(font: &TextFont) {
+    DEFAULT_FONT.with(|default| *default.borrow_mut() = Some(font.font.clone()));
+  }
 
-        pixel_index += 4;
-        col += 1;
-      }
+  /// Replaces the font that is used by the windows created after this is called
+  ///
+  /// data is the contents of a TrueType or OpenType file. If it is not a font,
+  /// the returned Result contains a string explaining why.
+  pub fn set_default_font_data(data: Vec<u8>) -> Result<(), String> {
+    TextFont::set_default_font_from_collection(data, 0)
+  }
 
-      i += 1;
-    }
+  /// Replaces the font that is used by the windows created after this is called with one from a collection
+  ///
+  /// index is the font's index within the collection; it is 0 for a file
+  /// that contains a single font.
+  pub fn set_default_font_from_collection(data: Vec<u8>, index: u32) -> Result<(), String> {
 
-    // Fill in the pixels according to the coverage data
-    let mut index = 0;
-    let mut row_index = 0;
-    while index < coverage_data.len() && pixel_index < pixels.len() - 4 {
+    let font = TextFont::from_collection(&data, index, DEFAULT_FONT_SIZE)?;
+    TextFont::set_default_font(&font);
 
-      pixels[pixel_index] = red;
-      pixels[pixel_index + 1] = green;
-      pixels[pixel_index + 2] = blue;
-      pixels[pixel_index + 3] = coverage_data[index];
+    Ok(())
+  }
 
-      index += 1;
-      pixel_index += 4;
+  /// Sets the size, in points, of the font that is used by the windows created after this is called
+  pub fn set_default_font_size(font_size: f32) {
+    DEFAULT_SIZE.with(|size| size.set(font_size));
+  }
 
-      // row_index is used to determine when padding is need on the right of a scanline
-      row_index += 1;
-      if row_index == metrics.width {
-        if pixmap_width > metrics.width as u32 {
+  // Creates a TextFont that uses a font from the cache
+  fn with_shared_font(font: Arc<SharedFont>, name: &str, font_size: f32) -> TextFont {
 
-          // Skip to the beginning of the next scanline
-          pixel_index += ((pixmap_width - metrics.width as u32) * 4) as usize;
-        }
-        row_index = 0;
-      }
+    Self {
+      font,
+      font_path: name.to_string(),
+      font_size,
+      max_char_width: 0,
+      max_char_height: 0,
+      max_char_scale: 1.0,
     }
+  }
+
+  // Draws a character's glyph into a pixmap
+  //
+  // x is the character's origin and baseline is the Y coordinate of the baseline.
+  // An italic glyph is slanted to the right around the baseline.
+  fn draw_glyph(&self, pixmap: &mut Pixmap, ch: char, x: f32, baseline: i32, color: ColorU8, italic: bool) {
 
-    (pixmap, pixels_above, pixels_below as u32)
+    // Rasterize and get the layout metrics for the letter at the current
+    // font size.
+    //
+    // metrics is a Metrics and contains the sizing and positioning metadata
+    // for the rasterized glyph.
+    //
+    // coverage_data is Vec<u8> and contains the coverage vector for the glyph.
+    // Coverage is a linear scale where 0 represents 0% coverage of that pixel
+    // by the glyph and 255 represents 100% coverage. The vec starts at
+    // the top left corner of the glyph.
+    let glyph = self.rasterize(ch);
+    let (metrics, coverage_data) = (&glyph.0, &glyph.1);
+
+    // Spaces don't have an image
+    let mut glyph_pixmap = match Pixmap::new(metrics.width as u32, metrics.height as u32) {
+      Some(glyph_pixmap) => glyph_pixmap,
+      None => return,
+    };
+
+    // Fill in the pixels according to the coverage data
+    for (pixel, coverage) in glyph_pixmap.pixels_mut().iter_mut().zip(coverage_data.iter()) {
+      let alpha = (*coverage as u32 * color.alpha() as u32 / 255) as u8;
+      *pixel = ColorU8::from_rgba(color.red(), color.green(), color.blue(), alpha).premultiply();
+    }
+
+    // ymin is the distance of the bottom of the glyph above the baseline
+    let left = (x + metrics.xmin as f32).round() as i32;
+    let top = baseline - metrics.height as i32 - metrics.ymin;
+    let (paint, transform) = match italic {
+      true => {
+        let paint = PixmapPaint {
+          quality: FilterQuality::Bilinear,
+          ..PixmapPaint::default()
+        };
+        (paint, Transform::from_row(1.0, 0.0, -ITALIC_SLANT, 1.0, ITALIC_SLANT * baseline as f32, 0.0))
+      },
+      false => (PixmapPaint::default(), Transform::identity()),
+    };
+    pixmap.draw_pixmap(
+      left,
+      top,
+      glyph_pixmap.as_ref(),
+      &paint,
+      transform,
+      None
+    );
   }
 
   // Returns a Pixmap that contains the image of a single line of text.
@@ -195,104 +355,37 @@ impl TextFont {
         fixed_width: Option<u32>
   ) -> Pixmap{
 
-    // Set the fixed height if a fixed width was specified
-    let fixed_size: Option<(u32, u32)> = match fixed_width {
+    let layout = self.layout(text, fixed_width);
+    let (mut pixmap_width, pixmap_height) = layout.get_size();
 
-      Some(width) => Some((width, 13)),
-
-      None => None,
-    };
-
-    // Get the text color's components
-    let color_u8 = color.to_color_u8();
-    let red = color_u8.red();
-    let green = color_u8.green();
-    let blue = color_u8.blue();
-
-    // Get the Pixmap for each character and use it to calculate
-    // the size of the full Pixmap
-    let mut pixmap_width = 0;
-    let mut max_height = 0;
-    let mut max_below = 0;
-    for ch in text.chars() {
-
-      // Get this character's Pixmap
-      let (ch_pixmap, _pixels_above, pixels_below) =
-          self.draw_character(ch, red, green, blue, bg_color, fixed_size);
-      if pixels_below > max_below {
-        max_below = pixels_below;
-      }
-
-      // Adjust the size of the full Pixmap
-      pixmap_width += ch_pixmap.width();
-      if ch_pixmap.height() > max_height {
-        max_height = ch_pixmap.height();
-      }
+    // Make room for the caret when it is after the last character
+    let caret_x = layout.get_caret_x(caret_index.max(0) as usize).round();
+    if draw_caret {
+      pixmap_width = pixmap_width.max(caret_x as u32 + 1);
     }
-    let baseline = max_height;
 
     // Create the full Pixmap
-    let pixmap_height = baseline + max_below;
-    let mut full_pixmap = match Pixmap::new(pixmap_width, pixmap_height) {
+    let mut full_pixmap = match Pixmap::new(pixmap_width.max(1), pixmap_height.max(1)) {
       Some(pixmap) => pixmap,
 
       None => {
         return Pixmap::new(1, 1).unwrap();
       },
     };
+    full_pixmap.fill(bg_color);
 
-    // Draw each character Pixmap into the full Pixmap
-    let mut x = 0;
-    let mut caret_x = 0.0;
-    let mut char_index = 0;
-    for ch in text.chars() {
-
-      // If this character is where the caret is location, save its
-      // X coordinate of its left edge within the full pixmap so that
-      // we can later draw the caret at this location.
-      if draw_caret && char_index == caret_index {
-        caret_x = x as f32;
-      }
-
-      // Get this character's Pixmap
-      let (ch_pixmap, pixels_above, _pixels_below) =
-            self.draw_character(ch, red, green, blue, bg_color, fixed_size);
-
-      // Determine where the top of this character's Pixmap goes. Some
-      // characters, lower case letters for example, have shorter Pixmaps
-      // than other chracters, so they need to be placed appropriately
-      // within the larger Pixmap.
-      let top;
-      if baseline > pixels_above {
-        top = baseline - pixels_above;
-      } else {
-        top = 0;
-      }
-
-      full_pixmap.draw_pixmap(
-        x,
-        top as i32,
-        ch_pixmap.as_ref(),
-        &PixmapPaint::default(),
-        Transform::identity(),
-        None
-      );
-
-      x += ch_pixmap.width() as i32;
-      char_index += 1;
+    // Draw each character at its position
+    let baseline = layout.get_baseline() as i32;
+    for glyph in layout.get_glyphs() {
+      self.draw_glyph(&mut full_pixmap, glyph.ch, glyph.x, baseline, color.to_color_u8(), false);
     }
 
     // Draw the caret
     if draw_caret {
 
-      // Is the caret at the end of the text?
-      if caret_index == text.len() as i64 {
-        caret_x = x as f32;
-      }
-
       let mut pb = PathBuilder::new();
-      pb.move_to(caret_x, 0.0);
-      pb.line_to(caret_x, pixmap_height as f32);
+      pb.move_to(caret_x + 0.5, 0.0);
+      pb.line_to(caret_x + 0.5, pixmap_height as f32);
       let path = pb.finish().unwrap();
 
       let mut paint = Paint::default();
@@ -310,6 +403,9 @@ impl TextFont {
   ///
   /// If fixed_width has a value, that value will be the width of each character.
   ///
+  /// caret_index is the number of grapheme clusters before the caret, or -1
+  /// if the caret is not drawn.
+  ///
   /// Returns the size of the drawn text
   pub fn draw_text(
     &self,
@@ -354,27 +450,109 @@ impl TextFont {
     (text_pixmap.width(), text_pixmap.height())
   }
 
+  /// Draws rich text into the specified pixmap at the given location
+  ///
+  /// Each run is drawn with its own style. The runs that don't have a font,
+  /// color, or background use this font, color, and bg_color, and all of the
+  /// runs share a baseline.
+  ///
+  /// Returns the size of the drawn text
+  pub fn draw_rich_text(
+    &self,
+    text: &RichText,
+    pixmap: &mut Pixmap,
+    x: i32,
+    y: i32,
+    color: Color,
+    bg_color: Color,
+  ) -> (u32, u32) {
+
+    let (layouts, width, ascent, descent) = self.layout_rich_text(text);
+    let height = ascent + descent;
+
+    let mut text_pixmap = match Pixmap::new(width.max(1), height.max(1)) {
+      Some(text_pixmap) => text_pixmap,
+      None => return (0, 0),
+    };
+    text_pixmap.fill(bg_color);
+
+    for (run, (layout, run_x)) in text.get_runs().iter().zip(layouts.iter()) {
+
+      let font = run.style.font.as_deref().unwrap_or(self);
+      let run_color = run.style.color.unwrap_or(color);
+      let run_width = layout.get_caret_x(layout.get_grapheme_count());
+
+      let mut paint = Paint::default();
+      if let Some(background) = run.style.background {
+        paint.set_color(background);
+        if let Some(rect) = Rect::from_xywh(*run_x, 0.0, run_width, height as f32) {
+          text_pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+        }
+      }
+
+      // Bold text is drawn a second time, one pixel to the right
+      for glyph in layout.get_glyphs() {
+        font.draw_glyph(&mut text_pixmap, glyph.ch, run_x + glyph.x, ascent as i32, run_color.to_color_u8(), run.style.italic);
+        if run.style.is_bold() {
+          font.draw_glyph(&mut text_pixmap, glyph.ch, run_x + glyph.x + 1.0, ascent as i32, run_color.to_color_u8(), run.style.italic);
+        }
+      }
+
+      // The strikethrough is drawn at about half the height of a lowercase letter
+      paint.set_color(run_color);
+      let mut lines = Vec::new();
+      if run.style.underline {
+        lines.push(ascent as f32 + 1.0);
+      }
+      if run.style.strikethrough {
+        lines.push((ascent as f32 * 0.7).round());
+      }
+      for line_y in lines {
+        if let Some(rect) = Rect::from_xywh(*run_x, line_y, run_width, 1.0) {
+          text_pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+        }
+      }
+    }
+
+    pixmap.draw_pixmap(
+      x,
+      y,
+      text_pixmap.as_ref(),
+      &PixmapPaint::default(),
+      Transform::identity(),
+      None
+    );
+
+    (text_pixmap.width(), text_pixmap.height())
+  }
+
   /// Gets the size of a string, in pixels, when it is rendered using the font.
   ///
   /// If fixed_width has a value, that value will be the width of each character.
   pub fn get_bounds(&self, text: &str, fixed_width: Option<u32>) -> (u32, u32) {
 
-    let pixmap = self.draw_image(
-          text,
-          Color::BLACK,
-          Color::WHITE,
-          false,
-          0,
-          Color::BLACK,
-          fixed_width,
-    );
+    let (width, height) = self.layout(text, fixed_width).get_size();
 
-    (pixmap.width(), pixmap.height())
+    (width.max(1), height.max(1))
+  }
+
+  /// Gets the size of rich text, in pixels, when it is drawn with draw_rich_text()
+  pub fn get_rich_bounds(&self, text: &RichText) -> (u32, u32) {
+
+    let (_layouts, width, ascent, descent) = self.layout_rich_text(text);
+
+    (width.max(1), (ascent + descent).max(1))
+  }
+
+  // Gets the font's size, in pixels, on a display with the current scale factor
+  fn get_scaled_size(&self) -> f32 {
+    self.font_size * WindowUtils::get_scale_factor() as f32
   }
 
   /// Gets the maximum height of a character in the font.
   pub fn get_max_char_height(&mut self) -> u32 {
 
+    self.check_max_char_scale();
     if self.max_char_height == 0 {
 
       // Get the width of the widest letter
@@ -393,6 +571,7 @@ impl TextFont {
   /// Gets the maximum width of a character in the font.
   pub fn get_max_char_width(&mut self) -> u32 {
 
+    self.check_max_char_scale();
     if self.max_char_width == 0 {
 
       let (width, height) = self.get_bounds(&"W".to_string(), None);
@@ -402,6 +581,165 @@ impl TextFont {
 
     self.max_char_width
   }
+
+  /// Lays out a single line of text
+  ///
+  /// Each grapheme cluster is advanced by the advance width of its first
+  /// character, adjusted by the font's kerning with the cluster before it.
+  /// If fixed_width has a value, each grapheme cluster is given that width
+  /// instead, and kerning is not applied.
+  pub fn layout(&self, text: &str, fixed_width: Option<u32>) -> TextLayout {
+
+    let font = &self.font.font;
+    let font_size = self.get_scaled_size();
+
+    // Get the distances above and below the baseline that fit every character
+    let (ascent, descent) = match font.horizontal_line_metrics(font_size) {
+      Some(line_metrics) => (line_metrics.ascent.ceil(), (-line_metrics.descent).ceil()),
+      None => {
+        let upper = font.metrics('W', font_size);
+        let lower = font.metrics('y', font_size);
+        ((upper.height as i32 + upper.ymin) as f32, (-lower.ymin).max(0) as f32)
+      },
+    };
+
+    let mut glyphs = Vec::new();
+    let mut boundaries = Vec::new();
+    let mut right_edge: f32 = 0.0;
+    let mut x: f32 = 0.0;
+    let mut previous: Option<char> = None;
+    for (grapheme_index, grapheme) in text.graphemes(true).enumerate() {
+
+      let first = match grapheme.chars().next() {
+        Some(first) => first,
+        None => continue,
+      };
+
+      // Move the cluster closer to, or further from, the one before it
+      if fixed_width.is_none() {
+        if let Some(previous) = previous {
+          x += font.horizontal_kern(previous, first, font_size).unwrap_or(0.0);
+        }
+      }
+      boundaries.push(x);
+
+      // The other characters in the cluster, such as accents, share its origin
+      for ch in grapheme.chars() {
+        let metrics = font.metrics(ch, font_size);
+        right_edge = right_edge.max(x + (metrics.xmin + metrics.width as i32) as f32);
+        glyphs.push(GlyphPosition {
+          ch,
+          grapheme_index,
+          x,
+        });
+      }
+
+      x += match fixed_width {
+        Some(width) => width as f32,
+        None => font.metrics(first, font_size).advance_width,
+      };
+      previous = Some(first);
+    }
+    boundaries.push(x);
+
+    TextLayout {
+      glyphs,
+      boundaries,
+      width: x.max(right_edge).ceil() as u32,
+      ascent: ascent as u32,
+      descent: descent as u32,
+    }
+  }
+
+  // Lays out each run of rich text with its font
+  //
+  // Returns each run's layout and X coordinate, the width of the text, and the
+  // distances above and below the shared baseline that fit every run.
+  fn layout_rich_text(&self, text: &RichText) -> (Vec<(TextLayout, f32)>, u32, u32, u32) {
+
+    // Text without any runs is as high as a line of this font
+    let empty = self.layout("", None);
+    let (mut ascent, mut descent) = (empty.ascent, empty.descent);
+
+    let mut layouts = Vec::new();
+    let mut width: f32 = 0.0;
+    let mut x: f32 = 0.0;
+    for run in text.get_runs() {
+
+      let font = run.style.font.as_deref().unwrap_or(self);
+      let layout = font.layout(&run.text, None);
+      ascent = ascent.max(layout.ascent);
+      descent = descent.max(layout.descent);
+
+      // Emboldening makes the glyphs a pixel wider
+      let bold_width = if run.style.is_bold() { 1.0 } else { 0.0 };
+      width = width.max(x + layout.width as f32 + bold_width);
+
+      let advance = layout.get_caret_x(layout.get_grapheme_count());
+      layouts.push((layout, x));
+      x += advance;
+    }
+
+    (layouts, width.max(x).ceil() as u32, ascent, descent)
+  }
+
+  /// Breaks text into lines that are no wider than width pixels
+  ///
+  /// Lines are broken at newlines and between words, and the spaces where a
+  /// line is broken are removed. A word that is wider than width is broken
+  /// between grapheme clusters.
+  pub fn wrap_text(&self, text: &str, width: u32) -> Vec<String> {
+    self.get_wrap_ranges(text, width).into_iter().map(|range| text[range].to_string()).collect()
+  }
+
+  // Breaks text into lines like wrap_text(), returning the byte range of each line within the text
+  pub(crate) fn get_wrap_ranges(&self, text: &str, width: u32) -> Vec<Range<usize>> {
+
+    let fits = |range: Range<usize>| self.layout(text[range].trim_end(), None).get_size().0 <= width;
+    let trim_end = |range: Range<usize>| range.start..range.start + text[range].trim_end().len();
+
+    let mut lines = Vec::new();
+    let mut paragraph_start = 0;
+    for paragraph in text.split('\n') {
+
+      let mut line = paragraph_start..paragraph_start;
+      for (offset, word) in paragraph.trim_end_matches('\r').split_word_bound_indices() {
+
+        let word_start = paragraph_start + offset;
+        let word_end = word_start + word.len();
+        if fits(line.start..word_end) {
+          line.end = word_end;
+          continue;
+        }
+
+        // The word starts a new line, unless it is the space between words
+        if !text[line.clone()].trim_end().is_empty() {
+          lines.push(trim_end(line.clone()));
+        }
+        line = word_start..word_start;
+        if word.trim().is_empty() {
+          line = word_end..word_end;
+          continue;
+        }
+
+        // Break a word that doesn't fit on a line by itself
+        for (grapheme_offset, grapheme) in word.grapheme_indices(true) {
+          let grapheme_start = word_start + grapheme_offset;
+          let grapheme_end = grapheme_start + grapheme.len();
+          if !line.is_empty() && !fits(line.start..grapheme_end) {
+            lines.push(line.clone());
+            line = grapheme_start..grapheme_start;
+          }
+          line.end = grapheme_end;
+        }
+      }
+      lines.push(trim_end(line));
+
+      paragraph_start += paragraph.len() + 1;
+    }
+
+    lines
+  }
 }
 
 impl Debug for TextFont {
@@ -409,3 +747,94 @@ impl Debug for TextFont {
     write!(fmt, "TextFont; font path: {}, font size: {}", self.font_path, self.font_size)
    }
 }
+
+/// Position of a character within a TextLayout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphPosition {
+  pub ch: char,
+  pub grapheme_index: usize,   // index of the grapheme cluster that contains the character
+  pub x: f32,                  // distance of the character's origin from the left edge of the text
+}
+
+/// Positions of the characters in a single line of text, created by TextFont::layout()
+///
+/// It is used to find where the caret is drawn and which character was
+/// clicked. Locations within the text are grapheme cluster indexes.
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+  glyphs: Vec<GlyphPosition>,
+  boundaries: Vec<f32>,        // left edge of each grapheme cluster, followed by the end of the text
+  width: u32,
+  ascent: u32,                 // pixels above the baseline
+  descent: u32,                // pixels below the baseline
+}
+
+impl TextLayout {
+
+  /// Gets the distance from the top of the text to its baseline
+  pub fn get_baseline(&self) -> u32 {
+    self.ascent
+  }
+
+  /// Gets the X coordinate of the caret when it is before a grapheme cluster
+  ///
+  /// If grapheme_index is past the end of the text, the end of the text is returned.
+  pub fn get_caret_x(&self, grapheme_index: usize) -> f32 {
+
+    match self.boundaries.get(grapheme_index) {
+      Some(x) => *x,
+      None => *self.boundaries.last().unwrap_or(&0.0),
+    }
+  }
+
+  /// Gets the position of each character
+  pub fn get_glyphs(&self) -> &Vec<GlyphPosition> {
+    &self.glyphs
+  }
+
+  /// Gets the number of grapheme clusters in the text
+  pub fn get_grapheme_count(&self) -> usize {
+    self.boundaries.len() - 1
+  }
+
+  /// Gets the location of the caret that is closest to an X coordinate
+  ///
+  /// The returned value is the number of grapheme clusters before the caret,
+  /// so clicking on the right half of a character puts the caret after it.
+  pub fn get_grapheme_at(&self, x: f32) -> usize {
+
+    let mut index = 0;
+    while index < self.get_grapheme_count() &&
+          x >= (self.boundaries[index] + self.boundaries[index + 1]) / 2.0 {
+      index += 1;
+    }
+
+    index
+  }
+
+  /// Gets the first grapheme cluster to show so that the caret is visible
+  ///
+  /// first_visible is the first grapheme cluster that is currently shown, and
+  /// width is the width of the area that shows the text. The text is scrolled
+  /// as little as possible.
+  pub fn get_scroll_for_caret(&self, grapheme_index: usize, first_visible: usize, width: f32) -> usize {
+
+    if grapheme_index <= first_visible {
+      return grapheme_index;
+    }
+
+    // Leave room for the caret after the last character
+    let mut first = first_visible;
+    while first < grapheme_index &&
+          self.get_caret_x(grapheme_index) - self.get_caret_x(first) + 1.0 > width {
+      first += 1;
+    }
+
+    first
+  }
+
+  /// Gets the width and height of the text, in pixels
+  pub fn get_size(&self) -> (u32, u32) {
+    (self.width, self.ascent + self.descent)
+  }
+}
@@ -0,0 +1,141 @@
+use tiny_skia::Color;
+
+use std::{
+  cell::{Cell, RefCell},
+  fmt::Debug,
+  rc::Rc,
+};
+
+use crate::text_font::{TextFont, DEFAULT_FONT_SIZE};
+
+thread_local! {
+  // Theme of the MainApp running on this thread
+  static CURRENT_THEME: RefCell<Rc<Theme>> = RefCell::new(Rc::new(Theme::light()));
+
+  // Incremented each time the theme or the scale factor is replaced, so that windows know to redraw themselves
+  static THEME_GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Colors used to draw the windows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+  pub background: Color,       // main window, pop-ups, context menus, and tooltips
+  pub base: Color,             // background of editors, lists, and tables
+  pub text: Color,
+  pub accent: Color,           // menu bar, carets, and slides
+  pub selection: Color,        // highlight behind selected text
+  pub selected_item: Color,    // text of the selected items in lists and tables
+  pub control: Color,          // scroll bars, sliders, tab buttons, and the status bar
+  pub header: Color,           // background of table headings
+  pub line: Color,             // borders and dividers
+  pub edit_border: Color,      // border of LineEdits
+}
+
+/// Sizes used to lay out the windows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+  pub padding: f64,            // space between a window's edges and its contents
+  pub border_width: f32,       // width of the lines drawn around windows
+}
+
+/// Appearance of an application's windows
+///
+/// The MainApp owns the theme, and each window gets it from its WindowBase
+/// when it is drawn. Replacing it with MainApp::set_theme() redraws all of
+/// the windows. Colors that are set on a window, such as with
+/// Table::set_header_bg_color(), take precedence over the theme's.
+///
+/// The font and font size are only used by the windows that are created
+/// after the theme is set. Windows that already exist keep the font they
+/// were created with, and are redrawn with it in the new theme's colors.
+#[derive(Clone, Debug)]
+pub struct Theme {
+  pub name: String,
+  pub palette: Palette,
+  pub font: Option<Rc<TextFont>>,  // font of the windows created after the theme is set, not of existing ones; None keeps the default font
+  pub font_size: f32,              // size of that font, also only for windows created afterwards
+  pub metrics: Metrics,
+}
+
+impl Theme {
+
+  /// Gets the theme that the windows on this thread are drawn with
+  pub(crate) fn current() -> Rc<Theme> {
+    CURRENT_THEME.with(|theme| theme.borrow().clone())
+  }
+
+  /// Creates a theme with light text on dark backgrounds
+  pub fn dark() -> Self {
+    Self {
+      name: "Dark".to_string(),
+      palette: Palette {
+        background: Color::from_rgba8(40, 40, 44, 255),
+        base: Color::from_rgba8(30, 30, 33, 255),
+        text: Color::from_rgba8(225, 225, 225, 255),
+        accent: Color::from_rgba8(70, 90, 190, 255),
+        selection: Color::from_rgba8(110, 130, 255, 90),
+        selected_item: Color::from_rgba8(140, 170, 255, 255),
+        control: Color::from_rgba8(80, 80, 86, 255),
+        header: Color::from_rgba8(40, 90, 105, 255),
+        line: Color::from_rgba8(110, 110, 115, 255),
+        edit_border: Color::from_rgba8(230, 90, 90, 220),
+      },
+      font: None,
+      font_size: DEFAULT_FONT_SIZE,
+      metrics: Metrics::default(),
+    }
+  }
+
+  /// Gets the number of times that the theme or the scale factor has been replaced on this thread
+  pub(crate) fn get_generation() -> u64 {
+    THEME_GENERATION.with(|generation| generation.get())
+  }
+
+  /// Makes the windows redraw themselves the next time that they are laid out
+  pub(crate) fn invalidate() {
+    THEME_GENERATION.with(|generation| generation.set(generation.get() + 1));
+  }
+
+  /// Creates a theme with dark text on light backgrounds, which is the default
+  pub fn light() -> Self {
+    Self {
+      name: "Light".to_string(),
+      palette: Palette {
+        background: Color::WHITE,
+        base: Color::WHITE,
+        text: Color::BLACK,
+        accent: Color::from_rgba8(64, 64, 255, 255),
+        selection: Color::from_rgba8(64, 64, 255, 80),
+        selected_item: Color::from_rgba8(32, 32, 150, 255),
+        control: Color::from_rgba8(191, 191, 191, 255),
+        header: Color::from_rgba8(50, 127, 150, 200),
+        line: Color::BLACK,
+        edit_border: Color::from_rgba8(200, 0, 0, 220),
+      },
+      font: None,
+      font_size: DEFAULT_FONT_SIZE,
+      metrics: Metrics::default(),
+    }
+  }
+
+  /// Replaces the theme that the windows on this thread are drawn with
+  pub(crate) fn set_current(theme: Rc<Theme>) {
+    CURRENT_THEME.with(|current| *current.borrow_mut() = theme);
+    Theme::invalidate();
+  }
+}
+
+impl Default for Metrics {
+  fn default() -> Self {
+    Self {
+      padding: 4.0,
+      border_width: 1.0,
+    }
+  }
+}
+
+impl Default for Theme {
+  fn default() -> Self {
+    Theme::light()
+  }
+}
@@ -0,0 +1,102 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use crate::MainApp;
+
+/// Identifies a timer that was added with MainApp::add_timer()
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TimerId(u64);
+
+/// Function that is called on the UI thread when a timer expires
+pub type TimerCallback = Box<dyn FnMut(&mut MainApp)>;
+
+struct Timer {
+  interval: Duration,
+  repeat: bool,
+  deadline: Instant,                  // when the timer next expires
+  callback: Option<TimerCallback>,    // None while the callback is being called
+}
+
+/// Timers of a MainApp, which are run by its event loop
+#[derive(Default)]
+pub(crate) struct Timers {
+  next_id: u64,
+  timers: HashMap<TimerId, Timer>,
+}
+
+impl Timers {
+
+  /// Adds a timer that expires after interval, and then every interval if repeat is true
+  pub fn add(&mut self, interval: Duration, repeat: bool, callback: TimerCallback) -> TimerId {
+
+    let id = TimerId(self.next_id);
+    self.next_id += 1;
+
+    self.timers.insert(id, Timer {
+      interval,
+      repeat,
+      deadline: Instant::now() + interval,
+      callback: Some(callback),
+    });
+
+    id
+  }
+
+  /// Removes a timer, returning false if there is no timer with the ID
+  pub fn cancel(&mut self, id: TimerId) -> bool {
+    self.timers.remove(&id).is_some()
+  }
+
+  /// Gets when the next timer expires
+  pub fn get_next_deadline(&self) -> Option<Instant> {
+    self.timers.values()
+      .filter(|timer| timer.callback.is_some())
+      .map(|timer| timer.deadline)
+      .min()
+  }
+
+  /// Takes the callbacks of the timers that have expired, oldest first
+  ///
+  /// Each callback must be given back with restore() after it is called.
+  pub fn take_expired(&mut self, now: Instant) -> Vec<(TimerId, TimerCallback)> {
+
+    let mut expired: Vec<(Instant, TimerId)> = self.timers.iter()
+      .filter(|(_id, timer)| timer.callback.is_some() && timer.deadline <= now)
+      .map(|(id, timer)| (timer.deadline, *id))
+      .collect();
+    expired.sort_by_key(|(deadline, _id)| *deadline);
+
+    expired.into_iter()
+      .filter_map(|(_deadline, id)| {
+        let timer = self.timers.get_mut(&id)?;
+        timer.callback.take().map(|callback| (id, callback))
+      })
+      .collect()
+  }
+
+  /// Gives back the callback of an expired timer
+  ///
+  /// A repeating timer is scheduled to expire again, and any other timer is
+  /// removed. Nothing is done if the timer was cancelled by its callback.
+  pub fn restore(&mut self, id: TimerId, callback: TimerCallback, now: Instant) {
+
+    let timer = match self.timers.get_mut(&id) {
+      Some(timer) => timer,
+      None => return,
+    };
+
+    if timer.repeat {
+
+      // If the event loop fell behind, skip the intervals that were missed
+      timer.deadline += timer.interval;
+      if timer.deadline < now {
+        timer.deadline = now + timer.interval;
+      }
+      timer.callback = Some(callback);
+    } else {
+      self.timers.remove(&id);
+    }
+  }
+}
@@ -1,20 +1,11 @@
 use winit::{
-  dpi::{LogicalSize, PhysicalPosition},
+  dpi::{LogicalPosition, LogicalSize},
   event::{WindowEvent},
-  event_loop::EventLoopProxy,
   window::{ Window },
 };
 
-use softbuffer::{
-  Context,
-  Surface,
-};
-
 use tiny_skia::{
-  Color,
   Pixmap,
-  PixmapPaint,
-  Transform,
 };
 
 use uuid::Uuid;
@@ -22,7 +13,6 @@ use uuid::Uuid;
 use std:: {
   cell::RefCell,
   fmt::Debug,
-  num::NonZeroU32,
   rc::Rc,
 };
 
@@ -32,18 +22,24 @@ use crate::child_window::{
   LayoutArgs,
   Orientation,
 };
+use crate::event_proxy::EventProxy;
 use crate::label::Label;
+use crate::rich_text::RichText;
 use crate::row_layout::RowLayout;
+use crate::theme::Theme;
 use crate::UserEvent;
+use crate::window_surface::{Damage, WindowSurface};
 use crate::WindowUtils;
 
 /// A pop-up tooltip window
 pub struct ToolTip {
   uuid: Uuid,
   main_win_uuid: Uuid,
-  event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
-  window: Window,
+  event_loop_proxy: Rc<EventProxy>,
+  window: Rc<Window>,
+  surface: Option<WindowSurface>, // created when the window is first displayed
   pixmap: Pixmap,
+  damage: Damage,                 // regions of the pixmap that haven't been displayed
   width: f64,
   height: f64,
   layout: Box<dyn Layout>,
@@ -57,7 +53,7 @@ impl ToolTip {
         uuid: Uuid,
         main_win_uuid: Uuid,
         window: Window,
-        event_loop_proxy: Rc<EventLoopProxy<UserEvent>>,
+        event_loop_proxy: Rc<EventProxy>,
         width: f64,
         height: f64
   ) -> ToolTip {
@@ -71,12 +67,13 @@ impl ToolTip {
     ));
 
     // Create the Label that will display the text
+    let palette = Theme::current().palette.clone();
     let label = Label::new(
           event_loop_proxy.clone(),
           main_win_uuid,
           "".to_string(),
-          Color::BLACK,
-          Color::WHITE
+          palette.text,
+          palette.background
     );
     let layout_rc = Rc::new(RefCell::new(label));
     let _ = layout.add_child(layout_rc.clone(), LayoutArgs::None);
@@ -88,8 +85,10 @@ impl ToolTip {
       uuid: uuid,
       main_win_uuid: main_win_uuid,
       event_loop_proxy: event_loop_proxy.clone(),
-      window: window,
-      pixmap: Pixmap::new(width as u32, height as u32).unwrap(),
+      window: Rc::new(window),
+      surface: None,
+      pixmap: Pixmap::new(WindowUtils::to_physical(width) as u32, WindowUtils::to_physical(height) as u32).unwrap(),
+      damage: Damage::default(),
       width: width,
       height: height,
       layout: layout,
@@ -98,30 +97,25 @@ impl ToolTip {
     }
   }
 
-  // Displays the Pixmap onto the screen
+  // Displays the regions of the Pixmap that have changed onto the screen
   fn display_pixmap(&mut self) {
 
-    // Get the size of the window
-    let width = self.pixmap.width();
-    let height = self.pixmap.height();
-
-    // Create the drawing surface
-    let context = (Context::new(&self.window)).unwrap();
-    let mut surface = (Surface::new(&context, &self.window)).unwrap();
-    surface
-        .resize(NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap())
-        .unwrap();
-
-    // Copy the pixmap onto the drawing surface
-    let mut buffer = surface.buffer_mut().unwrap();
-    for index in 0..(width * height) as usize {
-      buffer[index] =
-          (self.pixmap.data()[index * 4 + 2] as u32) |
-          ((self.pixmap.data()[index * 4 + 1] as u32) << 8) |
-          ((self.pixmap.data()[index * 4] as u32) << 16);
+    // Create the drawing surface, which is kept until the window closes
+    if self.surface.is_none() {
+      match WindowSurface::new(self.window.clone()) {
+        Ok(surface) => self.surface = Some(surface),
+        Err(err) => {
+          println!("In ToolTip::display_pixmap(), {err}");
+          return;
+        },
+      }
     }
 
-    buffer.present().unwrap();
+    if let Some(surface) = &mut self.surface {
+      if let Err(err) = surface.present(&self.pixmap, self.damage.take()) {
+        println!("In ToolTip::display_pixmap(), {err}");
+      }
+    }
   }
 
   pub fn get_size(&self) -> (f64, f64) {
@@ -132,9 +126,9 @@ impl ToolTip {
     self.uuid
   }
 
-  /// Moves the window
+  /// Moves the window, in logical units
   pub fn move_window(&self, x: f64, y: f64) {
-    self.window.set_outer_position(PhysicalPosition::new(x, y));
+    self.window.set_outer_position(LogicalPosition::new(x, y));
   }
 
   /// Called by the main window when there is an event for the context menu.
@@ -169,22 +163,14 @@ impl ToolTip {
     let (width, height) = self.get_size();
 
     // Fill the pixmap with the background color
-    self.pixmap.fill(Color::WHITE);
+    self.pixmap.fill(Theme::current().palette.background);
 
     // Get the layout's pixmap
     let layout_pixmap = self.layout.layout(0.0, 0.0, width as f64, height as f64);
 
-    let paint = PixmapPaint::default();
-
     // Copy the layout's pixmap image onto the full pixmap
-    self.pixmap.draw_pixmap(
-        0,
-        0,
-        layout_pixmap.as_ref(),
-        &paint,
-        Transform::identity(),
-        None,
-    );
+    WindowUtils::draw_child_pixmap(&mut self.pixmap, 0.0, 0.0, &layout_pixmap);
+    self.damage.add_pixmap(&self.pixmap);
 
     // Display the pixmap
     self.display_pixmap();
@@ -198,6 +184,10 @@ impl ToolTip {
     self.window.set_max_inner_size(Some(size));
   }
 
+  /// Sets the text that is displayed
+  ///
+  /// The text can contain the markup accepted by RichText::parse_markup(),
+  /// such as <b>bold</b>. Text that is not valid markup is displayed as it is.
   pub fn set_text(&mut self, text: String) {
 
     let child_width;
@@ -205,7 +195,12 @@ impl ToolTip {
     {
       // Set the text
       let mut label_ref = self.label_rc.borrow_mut();
-      label_ref.set_text(text);
+      let palette = Theme::current().palette.clone();
+      label_ref.set_colors(palette.text, palette.background);
+      match RichText::parse_markup(&text) {
+        Ok(rich_text) => label_ref.set_rich_text(rich_text),
+        Err(_err) => label_ref.set_text(text),
+      }
       (child_width, child_height) = label_ref.get_drawing_size();
     }
 
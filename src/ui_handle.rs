@@ -0,0 +1,112 @@
+use winit::event_loop::EventLoopProxy;
+
+use std::{
+  collections::VecDeque,
+  fmt,
+  sync::{Arc, Mutex},
+};
+
+use crate::MainApp;
+use crate::UserEvent;
+
+/// Function that a UiHandle asks the UI thread to call
+type UiFunction = Box<dyn FnOnce(&mut MainApp) + Send>;
+
+/// Handle for asking the UI thread to do something from another thread
+///
+/// The windows and the EventProxy can only be used on the thread that runs
+/// the MainApp. A UiHandle can be cloned and sent to other threads, such as
+/// threads that load data, which use it to send UserEvents to the MainApp or
+/// to have functions called on the UI thread. It is created with
+/// MainApp::get_ui_handle() or EventProxy::get_ui_handle().
+#[derive(Clone)]
+pub struct UiHandle {
+  sender: UiSender,
+}
+
+// Where the events are sent
+#[derive(Clone)]
+enum UiSender {
+  EventLoop(EventLoopProxy<UserEvent>),
+  Headless(Arc<Mutex<VecDeque<UserEvent>>>),
+}
+
+/// Function that is called on the UI thread, sent in a UserEvent::Run
+///
+/// The function is only called once, even if the event is cloned.
+#[derive(Clone)]
+pub struct UiTask(Arc<Mutex<Option<UiFunction>>>);
+
+impl UiHandle {
+
+  pub(crate) fn new_event_loop(proxy: EventLoopProxy<UserEvent>) -> Self {
+    Self {
+      sender: UiSender::EventLoop(proxy),
+    }
+  }
+
+  pub(crate) fn new_headless(queue: Arc<Mutex<VecDeque<UserEvent>>>) -> Self {
+    Self {
+      sender: UiSender::Headless(queue),
+    }
+  }
+
+  /// Sends an event to the MainApp, which processes it on the UI thread
+  pub fn post(&self, event: UserEvent) -> Result<(), String> {
+    match &self.sender {
+      UiSender::EventLoop(proxy) => match proxy.send_event(event) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("{err}")),
+      },
+      UiSender::Headless(queue) => match queue.lock() {
+        Ok(mut queue) => {
+          queue.push_back(event);
+          Ok(())
+        },
+        Err(err) => Err(format!("{err}")),
+      },
+    }
+  }
+
+  /// Has a function called on the UI thread
+  ///
+  /// The function is given the MainApp, so that it can change the windows.
+  /// It is called after the events that were sent before it.
+  pub fn run<F: FnOnce(&mut MainApp) + Send + 'static>(&self, function: F) -> Result<(), String> {
+    self.post(UserEvent::Run(UiTask(Arc::new(Mutex::new(Some(Box::new(function)))))))
+  }
+}
+
+impl fmt::Debug for UiHandle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.sender {
+      UiSender::EventLoop(_) => write!(f, "UiHandle::EventLoop"),
+      UiSender::Headless(_) => write!(f, "UiHandle::Headless"),
+    }
+  }
+}
+
+impl UiTask {
+
+  /// Calls the function, unless it has already been called
+  pub(crate) fn call(&self, app: &mut MainApp) {
+
+    let function = match self.0.lock() {
+      Ok(mut function) => function.take(),
+      Err(err) => {
+        println!("Cannot call a function on the UI thread: {err}");
+        None
+      },
+    };
+
+    if let Some(function) = function {
+      function(app);
+    }
+  }
+}
+
+impl fmt::Debug for UiTask {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "UiTask")
+  }
+}
@@ -0,0 +1,127 @@
+/// A single change to a window's text
+///
+/// The text that was removed from start is replaced by the inserted text.
+/// P is the type used for a location within the text, such as a character
+/// index or a line and character index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit<P> {
+  pub start: P,              // where the change was made
+  pub removed: String,       // text that was removed
+  pub inserted: String,      // text that was inserted in its place
+  pub caret_before: P,       // location of the caret before the change
+  pub caret_after: P,        // location of the caret after the change
+}
+
+// A TextEdit and whether it was made by typing
+#[derive(Debug)]
+struct UndoStep<P> {
+  edit: TextEdit<P>,
+  typing: bool,
+}
+
+/// History of the changes made to a window's text, which are undone and redone in order
+///
+/// Consecutive typing is combined into a single step.
+#[derive(Debug)]
+pub struct UndoStack<P> {
+  undo_steps: Vec<UndoStep<P>>,
+  redo_steps: Vec<UndoStep<P>>,
+  saved_len: Option<usize>,  // number of undo steps when the text was saved, if the state can be reached
+}
+
+impl<P: Copy + PartialEq> UndoStack<P> {
+
+  pub fn new() -> Self {
+    Self {
+      undo_steps: Vec::new(),
+      redo_steps: Vec::new(),
+      saved_len: Some(0),
+    }
+  }
+
+  /// Returns true if there is a change that can be redone
+  pub fn can_redo(&self) -> bool {
+    !self.redo_steps.is_empty()
+  }
+
+  /// Returns true if there is a change that can be undone
+  pub fn can_undo(&self) -> bool {
+    !self.undo_steps.is_empty()
+  }
+
+  /// Removes all of the changes and marks the current text as saved
+  pub fn clear(&mut self) {
+    self.undo_steps.clear();
+    self.redo_steps.clear();
+    self.saved_len = Some(0);
+  }
+
+  /// Returns true if the text is the same as when set_saved() was last called
+  pub fn is_saved(&self) -> bool {
+    self.saved_len == Some(self.undo_steps.len())
+  }
+
+  /// Adds a change to the history, discarding the changes that were undone
+  ///
+  /// If typing is true and the previous change was also typing that ended
+  /// where this one starts, the two are combined.
+  pub fn record(&mut self, edit: TextEdit<P>, typing: bool) {
+
+    // The changes that were undone can no longer be redone
+    if !self.redo_steps.is_empty() {
+      self.redo_steps.clear();
+      if let Some(saved_len) = self.saved_len {
+        if saved_len > self.undo_steps.len() {
+          self.saved_len = None;
+        }
+      }
+    }
+
+    // Combine the typing with the previous step, unless that step is where the text was saved
+    let at_saved_state = self.is_saved();
+    if let Some(previous) = self.undo_steps.last_mut() {
+      if typing && previous.typing && !at_saved_state &&
+            edit.removed.is_empty() && previous.edit.caret_after == edit.caret_before {
+        previous.edit.inserted += &edit.inserted;
+        previous.edit.caret_after = edit.caret_after;
+        return;
+      }
+    }
+
+    self.undo_steps.push(UndoStep {
+      edit,
+      typing,
+    });
+  }
+
+  /// Returns the change to redo, if there is one
+  pub fn redo(&mut self) -> Option<TextEdit<P>> {
+
+    let step = self.redo_steps.pop()?;
+    let edit = step.edit.clone();
+    self.undo_steps.push(step);
+
+    Some(edit)
+  }
+
+  /// Marks the current text as saved
+  pub fn set_saved(&mut self) {
+    self.saved_len = Some(self.undo_steps.len());
+  }
+
+  /// Returns the change to undo, if there is one
+  pub fn undo(&mut self) -> Option<TextEdit<P>> {
+
+    let step = self.undo_steps.pop()?;
+    let edit = step.edit.clone();
+    self.redo_steps.push(step);
+
+    Some(edit)
+  }
+}
+
+impl<P: Copy + PartialEq> Default for UndoStack<P> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -1,6 +1,5 @@
 use winit::{
   event::MouseButton,
-  event_loop::EventLoopProxy,
 };
 
 use tiny_skia::{
@@ -17,6 +16,9 @@ use crate::child_window::{
   ContextMenuItem,
 };
 use crate::context_menu::ContextMenu;
+use crate::event_proxy::EventProxy;
+use crate::signal::{ConnectionId, Listener};
+use crate::theme::Theme;
 use crate::UserEvent;
 use crate::window_utils::WindowUtils;
 
@@ -29,11 +31,13 @@ use std::{
 #[derive(Clone, Debug)]
 pub struct WindowBase {
   window_type: String,                        // struct type of the window
-  event_loop: Rc<EventLoopProxy<UserEvent>>,  // main window's event loop
+  event_loop: Rc<EventProxy>,                 // main window's event loop
   uuid: Uuid,                                 // window's unique ID
   main_win_uuid: Uuid,                        // ID of the outermost parent window
   enabled: bool,                              // whether the window is enabled
   focused: bool,                              // whether the window has input focus
+  accepts_focus: bool,                        // whether Tab can move the input focus to the window
+  accepts_tab: bool,                          // whether the window processes Tab itself
   main_win_x: f64,                            // window's location within the main window
   main_win_y: f64,
   width: f64,                                 // size of the window
@@ -50,16 +54,18 @@ pub struct WindowBase {
   y_scroll_max: f64,
   pixmap: Pixmap,                             // window's contents
   text: Option<String>,                       // text associated with the window
-  background_color: Color,                    // solid background color
-  name: String,                               // only used in Debug
+  background_color: Option<Color>,            // solid background color; None uses the theme's
+  name: String,                               // used by MainApp::find() and in Debug
   context_menu_items: Vec<Rc<RefCell<Button>>>, // Items for a window's context menu
   parent: Option<ChildType>,                  // parent of this window
   tooltip_text: Option<String>,               // tooltip text
+  theme_generation: u64,                      // generation of the theme that the pixmap was drawn with
+  dirty: bool,                                // whether the pixmap has changed since the layout copied it
 }
 
 impl WindowBase {
 
-  pub fn new(event_loop: Rc<EventLoopProxy<UserEvent>>, main_win_uuid: Uuid) -> Self {
+  pub fn new(event_loop: Rc<EventProxy>, main_win_uuid: Uuid) -> Self {
 
     // Set all of the default values
     Self {
@@ -69,6 +75,8 @@ impl WindowBase {
       main_win_uuid: main_win_uuid,
       enabled: true,
       focused: false,
+      accepts_focus: false,
+      accepts_tab: false,
       main_win_x: 0.0,
       main_win_y: 0.0,
       width: 0.0,
@@ -85,11 +93,13 @@ impl WindowBase {
       y_scroll_max: 0.0,
       pixmap: Pixmap::new(1, 1).unwrap(),   // empty pixmap
       text: None,
-      background_color: Color::WHITE,
+      background_color: None,
       name: "unspecified".to_string(),
       context_menu_items: Vec::new(),
       parent: None,
       tooltip_text: None,
+      theme_generation: Theme::get_generation(),
+      dirty: true,
     }
   }
 
@@ -105,7 +115,7 @@ impl WindowBase {
           Some(item.label),
           None,
           None,
-          Color::WHITE,
+          self.get_theme().palette.background,
           item.callback
     );
 
@@ -121,7 +131,7 @@ impl WindowBase {
       Some("----------".to_string()),
       None,
       None,
-      Color::WHITE,
+      self.get_theme().palette.background,
       ||{}
     );
 
@@ -136,6 +146,8 @@ impl WindowBase {
     self.uuid
   }
   pub fn set_uuid(&mut self, uuid: Uuid) {
+    self.event_loop.get_signals().borrow_mut().set_source_uuid(self.uuid, uuid);
+    self.event_loop.get_registry().borrow_mut().set_uuid(self.uuid, uuid);
     self.uuid = uuid;
   }
   pub fn get_main_win_uuid(&self) -> Uuid {
@@ -145,6 +157,7 @@ impl WindowBase {
   pub fn get_name(&self) -> String {
     self.name.clone()
   }
+  /// Sets the name that MainApp::find() looks the window up by
   pub fn set_name(&mut self, name: String) {
     self.name = name;
   }
@@ -156,10 +169,23 @@ impl WindowBase {
     self.window_type = window_type;
   }
 
-  pub fn get_event_loop(&self) -> Rc<EventLoopProxy<UserEvent>> {
+  /// Connects a listener to the events that the window fires
+  ///
+  /// It stays connected until it is disconnected, or the window is removed
+  /// from its layout or its tab or pop-up is closed.
+  pub(crate) fn connect(&self, listener: Listener) -> ConnectionId {
+    self.event_loop.get_signals().borrow_mut().connect(self.uuid, listener)
+  }
+
+  /// Disconnects a listener, returning false if it isn't connected
+  pub fn disconnect(&self, id: ConnectionId) -> bool {
+    self.event_loop.get_signals().borrow_mut().disconnect(id)
+  }
+
+  pub fn get_event_loop(&self) -> Rc<EventProxy> {
     self.event_loop.clone()
   }
-  pub fn set_event_loop(&mut self, event_loop: Rc<EventLoopProxy<UserEvent>>) {
+  pub fn set_event_loop(&mut self, event_loop: Rc<EventProxy>) {
     self.event_loop = event_loop;
   }
 
@@ -177,6 +203,20 @@ impl WindowBase {
     self.focused = focused;
   }
 
+  pub fn get_accepts_focus(&self) -> bool {
+    self.accepts_focus
+  }
+  pub fn set_accepts_focus(&mut self, accepts_focus: bool) {
+    self.accepts_focus = accepts_focus;
+  }
+
+  pub fn get_accepts_tab(&self) -> bool {
+    self.accepts_tab
+  }
+  pub fn set_accepts_tab(&mut self, accepts_tab: bool) {
+    self.accepts_tab = accepts_tab;
+  }
+
   // Location related functions
   pub fn get_location(&self) -> (f64, f64) {
     (self.main_win_x, self.main_win_y)
@@ -276,6 +316,16 @@ impl WindowBase {
     (width, height)
   }
 
+  /// Gets the size, in physical pixels, of the pixmap that the window is drawn into
+  ///
+  /// It is the drawing size, which is in logical units, multiplied by the scale factor.
+  pub fn get_physical_drawing_size(&self) -> (f64, f64) {
+
+    let (width, height) = self.get_drawing_size();
+
+    (WindowUtils::to_physical(width), WindowUtils::to_physical(height))
+  }
+
   pub fn get_x_scroll(&self) -> f64 {
     self.x_scroll
   }
@@ -319,6 +369,15 @@ impl WindowBase {
   }
   pub fn set_pixmap(&mut self, pixmap: Pixmap) {
     self.pixmap = pixmap;
+    self.theme_generation = Theme::get_generation();
+    self.dirty = true;
+  }
+
+  pub fn is_dirty(&self) -> bool {
+    self.dirty
+  }
+  pub fn set_dirty(&mut self, dirty: bool) {
+    self.dirty = dirty;
   }
 
   pub fn get_text(&self) -> Option<String> {
@@ -332,11 +391,25 @@ impl WindowBase {
     self.text = Some(text);
   }
 
+  /// Gets the background color, which is the theme's base color unless one has been set
   pub fn get_background_color(&self) -> Color {
-    self.background_color
+    match self.background_color {
+      Some(color) => color,
+      None => self.get_theme().palette.base,
+    }
   }
   pub fn set_background_color(&mut self, color: Color) {
-    self.background_color = color;
+    self.background_color = Some(color);
+  }
+
+  /// Gets the theme that the window is drawn with
+  pub fn get_theme(&self) -> Rc<Theme> {
+    Theme::current()
+  }
+
+  /// Returns true if the theme has been replaced since the window was last drawn
+  pub fn is_theme_changed(&self) -> bool {
+    self.theme_generation != Theme::get_generation()
   }
 
   pub fn get_parent(&self) -> Option<ChildType> {
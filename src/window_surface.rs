@@ -0,0 +1,171 @@
+use winit::window::Window;
+
+use softbuffer::{
+  Context,
+  Rect,
+  Surface,
+};
+
+use tiny_skia::{IntRect, Pixmap};
+
+use std::{
+  num::NonZeroU32,
+  rc::Rc,
+};
+
+use crate::WindowUtils;
+
+// Number of separate regions that are kept before they are merged into one
+const MAX_DAMAGE_REGIONS: usize = 16;
+
+/// Regions of a top-level window's pixmap that have changed since it was last presented
+///
+/// The regions are in physical pixels.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Damage {
+  regions: Vec<IntRect>,
+}
+
+impl Damage {
+
+  /// Adds a region
+  pub fn add(&mut self, region: IntRect) {
+
+    // Ignore a region that is already covered
+    if self.regions.iter().any(|existing| existing.contains(&region)) {
+      return;
+    }
+    self.regions.retain(|existing| !region.contains(existing));
+    self.regions.push(region);
+
+    // Many small regions cost more to present than their bounds
+    if self.regions.len() > MAX_DAMAGE_REGIONS {
+      let bounds = self.regions.iter().skip(1).fold(self.regions[0], |bounds, region| {
+        IntRect::from_ltrb(
+              bounds.left().min(region.left()),
+              bounds.top().min(region.top()),
+              bounds.right().max(region.right()),
+              bounds.bottom().max(region.bottom())
+        ).unwrap()
+      });
+      self.regions = vec![bounds];
+    }
+  }
+
+  /// Adds the region covered by a child's pixmap
+  ///
+  /// x and y are the child's location within the top-level window, in logical units.
+  pub fn add_child_pixmap(&mut self, x: f64, y: f64, pixmap: &Pixmap) {
+
+    if let Some(region) = IntRect::from_xywh(
+          WindowUtils::to_physical(x) as i32,
+          WindowUtils::to_physical(y) as i32,
+          pixmap.width(),
+          pixmap.height()
+    ) {
+      self.add(region);
+    }
+  }
+
+  /// Adds the whole of a pixmap
+  pub fn add_pixmap(&mut self, pixmap: &Pixmap) {
+    self.add_child_pixmap(0.0, 0.0, pixmap);
+  }
+
+  /// Returns true if no regions have changed
+  pub fn is_empty(&self) -> bool {
+    self.regions.is_empty()
+  }
+
+  /// Removes all of the regions, returning them
+  pub fn take(&mut self) -> Vec<IntRect> {
+    std::mem::take(&mut self.regions)
+  }
+}
+
+/// Displays a top-level window's pixmap on the screen
+///
+/// The softbuffer surface is kept from one frame to the next, so only the
+/// regions of the pixmap that have changed are copied into it and presented.
+pub(crate) struct WindowSurface {
+  surface: Surface<Rc<Window>, Rc<Window>>,
+  width: u32,                         // size of the surface's buffers
+  height: u32,
+  previous_damage: Vec<IntRect>,      // regions presented in the previous frame
+}
+
+impl WindowSurface {
+
+  pub fn new(window: Rc<Window>) -> Result<Self, String> {
+
+    let context = Context::new(window.clone())
+          .map_err(|err| format!("Cannot create drawing context: {err}"))?;
+    let surface = Surface::new(&context, window)
+          .map_err(|err| format!("Cannot create drawing surface: {err}"))?;
+
+    Ok(Self {
+      surface,
+      width: 0,
+      height: 0,
+      previous_damage: Vec::new(),
+    })
+  }
+
+  /// Copies the damaged regions of a pixmap onto the screen
+  pub fn present(&mut self, pixmap: &Pixmap, damage: Vec<IntRect>) -> Result<(), String> {
+
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let bounds = IntRect::from_xywh(0, 0, width, height).unwrap();
+    let mut damage: Vec<IntRect> = damage.iter().filter_map(|region| region.intersect(&bounds)).collect();
+
+    // If the size has changed, resize the buffers, which loses their contents
+    if width != self.width || height != self.height {
+      self.surface.resize(NonZeroU32::new(width).unwrap(), NonZeroU32::new(height).unwrap())
+            .map_err(|err| format!("Cannot resize drawing surface: {err}"))?;
+      self.width = width;
+      self.height = height;
+      damage = vec![bounds];
+    } else if damage.is_empty() {
+      return Ok(());
+    }
+
+    let mut buffer = self.surface.buffer_mut()
+          .map_err(|err| format!("Cannot get drawing buffer: {err}"))?;
+
+    // The buffer contains the frame that was presented age frames ago, or
+    // nothing if its age is 0, so bring it up to date
+    let regions = match buffer.age() {
+      1 => damage.clone(),
+      2 => damage.iter().chain(self.previous_damage.iter()).copied().collect(),
+      _ => vec![bounds],
+    };
+
+    // Copy the regions of the pixmap onto the drawing surface
+    let data = pixmap.data();
+    for region in &regions {
+      for y in region.top()..region.bottom() {
+        let row = y as usize * width as usize;
+        for index in (row + region.left() as usize)..(row + region.right() as usize) {
+          buffer[index] =
+              (data[index * 4 + 2] as u32) |
+              ((data[index * 4 + 1] as u32) << 8) |
+              ((data[index * 4] as u32) << 16);
+        }
+      }
+    }
+
+    // Tell the display server which regions changed
+    let rects: Vec<Rect> = damage.iter().map(|region| Rect {
+      x: region.x() as u32,
+      y: region.y() as u32,
+      width: NonZeroU32::new(region.width()).unwrap(),
+      height: NonZeroU32::new(region.height()).unwrap(),
+    }).collect();
+    buffer.present_with_damage(&rects)
+          .map_err(|err| format!("Cannot present drawing buffer: {err}"))?;
+
+    self.previous_damage = damage;
+
+    Ok(())
+  }
+}
@@ -1,6 +1,7 @@
-use winit::event_loop::EventLoopProxy;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
 
 use tiny_skia::{
+  FilterQuality,
   IntRect,
   IntSize,
   Paint,
@@ -21,16 +22,30 @@ use image::{
 
 use chrono::Local;
 
+use unicode_segmentation::UnicodeSegmentation;
+
 use uuid::Uuid;
 
+use crate::child_window::{ChildWindow, ContextMenuItem, EditAction, KeyboardEvent};
+use crate::event_proxy::EventProxy;
+use crate::theme::Theme;
 use crate::UserEvent;
+use crate::{DOUBLE_CLICK_DISTANCE, DOUBLE_CLICK_TIME};
 
 use std::{
+  cell::{Cell, RefCell},
   io::Cursor,
   rc::Rc,
+  time::{Duration, Instant},
 };
 
-static mut IGNORE_EVENTS: bool = true;
+thread_local! {
+  // Each thread can run its own (possibly headless) MainApp
+  static IGNORE_EVENTS: Cell<bool> = const { Cell::new(true) };
+
+  // Number of physical pixels in each logical unit on the MainApp's monitor
+  static SCALE_FACTOR: Cell<f64> = const { Cell::new(1.0) };
+}
 
 /// Utility functions used by child windows
 pub struct WindowUtils {
@@ -101,6 +116,34 @@ impl WindowUtils {
     pixmap
 
   }
+
+  /// Draws an image into a Pixmap whose size is the physical size of the image
+  ///
+  /// Images are measured in logical units, so an image is drawn with twice as
+  /// many pixels on a display whose scale factor is 2.
+  pub fn create_scaled_image_pixmap(image: &DynamicImage) -> Pixmap {
+    WindowUtils::to_physical_pixmap(&WindowUtils::create_image_pixmap(image))
+  }
+
+  /// Creates a context menu item that performs an editing action on a window
+  pub fn create_edit_menu_item(
+        label: &str,
+        action: EditAction,
+        event_loop: Rc<EventProxy>,
+        main_win_uuid: Uuid,
+        uuid: Uuid
+  ) -> ContextMenuItem {
+
+    ContextMenuItem {
+      label: label.to_string(),
+      callback: Box::new(move || {
+        WindowUtils::fire_user_event(
+              event_loop.clone(),
+              UserEvent::Edit(main_win_uuid, uuid, action)
+        );
+      }),
+    }
+  }
   
   /// Gets the current date and time as a string
   pub fn date_str() -> String {
@@ -108,11 +151,31 @@ impl WindowUtils {
     format!("{}", date.format("%Y-%m-%d %H:%M:%S%.3f"))
   }
 
+  /// Draws a pixmap onto another one at a location given in logical units
+  ///
+  /// Child windows are laid out in logical units, but their pixmaps are drawn
+  /// in physical pixels.
+  pub fn draw_child_pixmap(dest_pixmap: &mut Pixmap, x: f64, y: f64, src_pixmap: &Pixmap) {
+    dest_pixmap.draw_pixmap(
+      WindowUtils::to_physical(x) as i32,
+      WindowUtils::to_physical(y) as i32,
+      src_pixmap.as_ref(),
+      &PixmapPaint::default(),
+      Transform::identity(),
+      None
+    );
+  }
+
   /// Draws a border around the inner perimeter of a window
-  pub fn draw_border(pixmap: &mut Pixmap, width: f64, height: f64, paint: &Paint) {
+  ///
+  /// border_width is in logical units.
+  pub fn draw_border(pixmap: &mut Pixmap, width: f64, height: f64, paint: &Paint, border_width: f32) {
 
     // Draw the border
-    let stroke = Stroke::default();   // One pixel wide
+    let stroke = Stroke {
+      width: border_width * WindowUtils::get_scale_factor() as f32,
+      ..Stroke::default()
+    };
     let path = PathBuilder::from_rect(Rect::from_ltrb(0.0, 0.0, width as f32, height as f32).unwrap());
     pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
 /*
@@ -126,14 +189,12 @@ impl WindowUtils {
 
   // Sends a UserEvent
   pub fn fire_user_event(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         event: UserEvent
   ) {
 
-    unsafe {
-      if IGNORE_EVENTS {
-        return;
-      }
+    if IGNORE_EVENTS.with(|ignore| ignore.get()) {
+      return;
     }
 
     match event_loop.send_event(event) {
@@ -142,6 +203,167 @@ impl WindowUtils {
     }
   }
 
+  /// Gets the editing action for a key press
+  ///
+  /// The keys are Ctrl+C, Ctrl+X, and Ctrl+V for the clipboard, Ctrl+Z for undo,
+  /// and Ctrl+Y or Ctrl+Shift+Z for redo.
+  pub fn get_edit_action(event: &KeyboardEvent, modifiers: ModifiersState) -> Option<EditAction> {
+
+    let text = match &event.logical_key {
+      Key::Character(text) => text.to_lowercase(),
+      _ => return None,
+    };
+
+    if modifiers == ModifiersState::CONTROL {
+      match text.as_str() {
+        "c" => Some(EditAction::Copy),
+        "x" => Some(EditAction::Cut),
+        "v" => Some(EditAction::Paste),
+        "y" => Some(EditAction::Redo),
+        "z" => Some(EditAction::Undo),
+        _ => None,
+      }
+    } else if modifiers == ModifiersState::CONTROL | ModifiersState::SHIFT && "z" == text {
+      Some(EditAction::Redo)
+    } else {
+      None
+    }
+  }
+
+  /// Gets the number of physical pixels in each logical unit
+  ///
+  /// It is the scale factor of the monitor that the main window is on, or the
+  /// one set with MainApp::set_scale_factor() when it runs headless.
+  pub fn get_scale_factor() -> f64 {
+    SCALE_FACTOR.with(|scale_factor| scale_factor.get())
+  }
+
+  /// Gets the byte index at which a grapheme cluster starts
+  ///
+  /// Returns the length of the text if there are not that many grapheme clusters.
+  pub fn get_grapheme_byte_index(text: &str, grapheme_index: usize) -> usize {
+
+    match text.grapheme_indices(true).nth(grapheme_index) {
+      Some((byte_index, _grapheme)) => byte_index,
+      None => text.len(),
+    }
+  }
+
+  /// Gets the number of grapheme clusters, which are the characters that a user sees, in text
+  pub fn get_grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+  }
+
+  /// Gets the number of grapheme clusters before a byte index
+  pub fn get_grapheme_index(text: &str, byte_index: usize) -> usize {
+    text.grapheme_indices(true).take_while(|(index, _grapheme)| *index < byte_index).count()
+  }
+
+  /// Gets the byte index of the grapheme cluster after the one that starts at byte_index
+  pub fn get_next_grapheme_boundary(text: &str, byte_index: usize) -> usize {
+
+    match text[byte_index..].graphemes(true).next() {
+      Some(grapheme) => byte_index + grapheme.len(),
+      None => text.len(),
+    }
+  }
+
+  /// Gets the byte index of the grapheme cluster before byte_index
+  pub fn get_previous_grapheme_boundary(text: &str, byte_index: usize) -> usize {
+
+    match text[..byte_index].graphemes(true).next_back() {
+      Some(grapheme) => byte_index - grapheme.len(),
+      None => 0,
+    }
+  }
+
+  /// Returns true if a left mouse click is the second click of a double click
+  ///
+  /// last_click is the time and location of the previous left click, if any.
+  /// x and y are the location of this click.
+  pub fn is_double_click(last_click: Option<(Instant, f64, f64)>, x: f64, y: f64) -> bool {
+
+    match last_click {
+
+      Some((time, last_x, last_y)) => {
+        time.elapsed() < Duration::from_millis(DOUBLE_CLICK_TIME) &&
+              (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE &&
+              (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE
+      },
+
+      None => false,
+    }
+  }
+
+  /// Determines whether a key press moves the input focus to another window
+  ///
+  /// Tab moves the focus, unless the window with focus processes Tab itself.
+  /// Ctrl+Tab always moves the focus.
+  pub fn is_focus_traversal(
+        event: &KeyboardEvent,
+        modifiers: ModifiersState,
+        focus_window: &Option<Rc<RefCell<dyn ChildWindow>>>
+  ) -> bool {
+
+    if event.logical_key != Key::Named(NamedKey::Tab) {
+      return false;
+    }
+
+    if modifiers.control_key() {
+      return true;
+    }
+
+    match focus_window {
+      Some(window) => !window.borrow().get_accepts_tab(),
+      None => true,
+    }
+  }
+
+  /// Gets the window that follows the one with focus in a focus chain
+  ///
+  /// If backwards is true, the preceding window is returned instead. The chain
+  /// wraps around at both ends. None is only returned if the chain is empty.
+  pub fn get_next_focus_window(
+        chain: Vec<Rc<RefCell<dyn ChildWindow>>>,
+        focus_window: &Option<Rc<RefCell<dyn ChildWindow>>>,
+        backwards: bool
+  ) -> Option<Rc<RefCell<dyn ChildWindow>>> {
+
+    if chain.is_empty() {
+      return None;
+    }
+
+    // Find the window with focus within the chain
+    let current_index = match focus_window {
+      Some(window) => {
+        let uuid = window.borrow().get_uuid();
+        chain.iter().position(|child| child.borrow().get_uuid() == uuid)
+      },
+      None => None,
+    };
+
+    let next_index = match current_index {
+      Some(index) => {
+        if backwards {
+          (index + chain.len() - 1) % chain.len()
+        } else {
+          (index + 1) % chain.len()
+        }
+      },
+
+      // Start from one of the ends of the chain
+      None => {
+        if backwards {
+          chain.len() - 1
+        } else {
+          0
+        }
+      },
+    };
+
+    Some(chain[next_index].clone())
+  }
+
   /// Gets the pixel data of a portion of a Pixmap
   pub fn get_pixel_data(
         src_pixmap: Pixmap,
@@ -223,26 +445,77 @@ impl WindowUtils {
 
   // Sends a redraw request event
   pub fn request_full_redraw(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid
   ) {
     WindowUtils::fire_user_event(event_loop, UserEvent::RedrawAll(main_win_uuid));
   }
 
-  // Sends a redraw request event
+  // Requests that a window's pixmap be drawn within its top-level window
+  //
+  // The requests are kept until the next frame is drawn, instead of being sent
+  // as events, so that the top-level window is only displayed once for all of them.
   pub fn request_redraw(
-        event_loop: Rc<EventLoopProxy<UserEvent>>,
+        event_loop: Rc<EventProxy>,
         main_win_uuid: Uuid,
         x: f64,
         y: f64,
         pixmap: Pixmap
   ) {
-    WindowUtils::fire_user_event(event_loop, UserEvent::Redraw(main_win_uuid, x, y, pixmap));
+
+    if IGNORE_EVENTS.with(|ignore| ignore.get()) {
+      return;
+    }
+
+    event_loop.queue_redraw(main_win_uuid, x, y, pixmap);
   }
 
   pub fn set_ignore_events(flag: bool) {
-    unsafe {
-      IGNORE_EVENTS = flag;
+    IGNORE_EVENTS.with(|ignore| ignore.set(flag));
+  }
+
+  /// Sets the number of physical pixels in each logical unit, which makes the windows redraw themselves
+  pub(crate) fn set_scale_factor(scale_factor: f64) {
+    SCALE_FACTOR.with(|current| current.set(scale_factor));
+    Theme::invalidate();
+  }
+
+  /// Converts a length in physical pixels to logical units
+  pub fn to_logical(pixels: f64) -> f64 {
+    pixels / WindowUtils::get_scale_factor()
+  }
+
+  /// Scales a pixmap whose pixels are logical units so that they are physical pixels
+  pub fn to_physical_pixmap(pixmap: &Pixmap) -> Pixmap {
+
+    if WindowUtils::get_scale_factor() == 1.0 {
+      return pixmap.clone();
     }
+
+    let width = WindowUtils::to_physical(pixmap.width() as f64).max(1.0) as u32;
+    let height = WindowUtils::to_physical(pixmap.height() as f64).max(1.0) as u32;
+    let mut scaled = Pixmap::new(width, height).unwrap();
+    let paint = PixmapPaint {
+      quality: FilterQuality::Bicubic,
+      ..PixmapPaint::default()
+    };
+    scaled.draw_pixmap(
+      0,
+      0,
+      pixmap.as_ref(),
+      &paint,
+      Transform::from_scale(
+            width as f32 / pixmap.width() as f32,
+            height as f32 / pixmap.height() as f32
+      ),
+      None
+    );
+
+    scaled
+  }
+
+  /// Converts a length in logical units to whole physical pixels
+  pub fn to_physical(units: f64) -> f64 {
+    (units * WindowUtils::get_scale_factor()).round()
   }
 }
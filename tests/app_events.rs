@@ -0,0 +1,71 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  app_event::AppEvent,
+  child_window::UserEvent,
+};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+  thread,
+};
+
+struct SaveFile {
+  contents: String,
+}
+
+struct Progress(u32);
+
+#[test]
+fn handlers_receive_typed_data() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  // Both of the SaveFile handlers are called, and only for SaveFile events
+  let received = Rc::new(RefCell::new(Vec::new()));
+  for handler in ["first", "second"] {
+    let received_clone = received.clone();
+    app_ref.add_app_event_handler(move |event: &SaveFile| {
+      received_clone.borrow_mut().push(format!("{handler}: {}", event.contents));
+    });
+  }
+  let received_clone = received.clone();
+  app_ref.add_app_event_handler(move |event: &Progress| {
+    received_clone.borrow_mut().push(format!("progress: {}", event.0));
+  });
+
+  proxy.send_event(UserEvent::App(id, AppEvent::new(SaveFile { contents: "text".to_string() }))).unwrap();
+  proxy.send_event(UserEvent::App(id, AppEvent::new(Progress(50)))).unwrap();
+  app_ref.process_events();
+
+  assert_eq!(*received.borrow(), vec!["first: text", "second: text", "progress: 50"]);
+}
+
+#[test]
+fn app_events_are_sent_from_other_threads() {
+
+  let (app, _proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  let received = Rc::new(RefCell::new(Vec::new()));
+  let received_clone = received.clone();
+  app_ref.add_app_event_handler(move |event: &Progress| received_clone.borrow_mut().push(event.0));
+
+  let ui_handle = app_ref.get_ui_handle();
+  thread::spawn(move || {
+    for percent in [25, 100] {
+      let event = AppEvent::new(Progress(percent));
+      assert!(event.is::<Progress>());
+      ui_handle.post(UserEvent::App(id, event)).unwrap();
+    }
+  }).join().unwrap();
+  app_ref.process_events();
+
+  assert_eq!(*received.borrow(), vec![25, 100]);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, EditAction, UserEvent},
+  clipboard::{Clipboard, ClipboardBackend, CommandClipboard},
+  line_edit::LineEdit,
+  multi_line_edit::MultiLineEdit,
+};
+
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+#[test]
+fn copy_and_paste_between_line_edits() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let first = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let second = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let uuids = show_windows(&app, &proxy, vec![first.clone(), second.clone()], None);
+  let (first_uuid, second_uuid) = (uuids[0], uuids[1]);
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(first_uuid).unwrap();
+  app_ref.send_text("hello");
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("c".into()), Some("\u{3}"));
+  assert_eq!(Clipboard::get_text(), Ok(Some("hello".to_string())));
+
+  // The control character typed with Ctrl+V is not inserted
+  app_ref.set_focus(second_uuid).unwrap();
+  app_ref.send_key(Key::Character("v".into()), Some("\u{16}"));
+  app_ref.send_key(Key::Character("v".into()), Some("\u{16}"));
+  assert_eq!(second.borrow().get_text(), Some("hellohello".to_string()));
+  assert!(second.borrow().is_modified());
+
+  // Cut empties the window
+  app_ref.set_focus(first_uuid).unwrap();
+  Clipboard::set_text("other").unwrap();
+  app_ref.send_key(Key::Character("x".into()), Some("\u{18}"));
+  assert_eq!(first.borrow().get_text(), Some("".to_string()));
+  assert_eq!(Clipboard::get_text(), Ok(Some("hello".to_string())));
+}
+
+#[test]
+fn paste_lines_into_multi_line_edit() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let editor = Rc::new(RefCell::new(MultiLineEdit::new(proxy.clone(), id, 2)));
+  let uuids = show_windows(&app, &proxy, vec![editor.clone()], Some(0));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(uuids[0]).unwrap();
+  app_ref.send_text("ad");
+  app_ref.send_key(Key::Named(NamedKey::ArrowLeft), None);
+
+  // Carriage returns are dropped and the text after the caret follows the pasted text
+  Clipboard::set_text("b\r\nc").unwrap();
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("v".into()), Some("\u{16}"));
+  assert_eq!(editor.borrow().get_text(), Some("ab\ncd".to_string()));
+  assert!(editor.borrow().is_modified());
+
+  // Cut removes the line containing the caret
+  app_ref.send_key(Key::Character("x".into()), Some("\u{18}"));
+  assert_eq!(editor.borrow().get_text(), Some("ab".to_string()));
+  assert_eq!(Clipboard::get_text(), Ok(Some("cd\n".to_string())));
+}
+
+#[test]
+fn context_menu_event_pastes() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(edit.clone()));
+  app_ref.run_headless();
+
+  // The context menu's Paste item sends this event
+  Clipboard::set_text("pasted").unwrap();
+  let edit_uuid = edit.borrow().get_uuid();
+  proxy.send_event(UserEvent::Edit(id, edit_uuid, EditAction::Paste)).unwrap();
+  app_ref.process_events();
+  assert_eq!(edit.borrow().get_text(), Some("pasted".to_string()));
+}
+
+#[cfg(unix)]
+#[test]
+fn clipboard_programs_that_hang_are_killed() {
+
+  // Programs that never finish, like a clipboard owner that doesn't respond
+  let mut clipboard = CommandClipboard::new(
+        vec!["sleep".to_string(), "30".to_string()],
+        vec!["sleep".to_string(), "30".to_string()]
+  );
+  clipboard.set_timeout(Duration::from_millis(200));
+
+  let start = Instant::now();
+  assert!(clipboard.get_text().is_err());
+  assert!(clipboard.set_text("hello").is_err());
+
+  // Text that doesn't fit in the pipe isn't waited for either
+  assert!(clipboard.set_text(&"x".repeat(1024 * 1024)).is_err());
+  assert!(start.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,60 @@
+use fenetre::{
+  child_window::{ChildType, ChildWindow, Layout, LayoutArgs, LayoutFill, Orientation},
+  event_proxy::EventProxy,
+  row_layout::RowLayout,
+  MainApp,
+  MainAppSize,
+};
+
+use uuid::Uuid;
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+// Creates a headless MainApp of the specified size
+pub fn create_app(width: f64, height: f64) -> (RefCell<MainApp>, Rc<EventProxy>, Uuid) {
+
+  let id = Uuid::new_v4();
+  let proxy = Rc::new(EventProxy::new_headless());
+  let app = MainApp::new(
+        id,
+        "Headless",
+        MainAppSize::Actual(0.0, 0.0, width, height),
+        proxy.clone(),
+        || {}
+  );
+
+  (app, proxy, id)
+}
+
+// Makes the windows the contents of a MainApp from create_app(), one above
+// the other, and runs it headless
+//
+// The window at index fill, if any, is given the height that the others
+// leave. Returns the UUIDs of the windows.
+#[allow(dead_code)]
+pub fn show_windows(
+      app: &RefCell<MainApp>,
+      proxy: &Rc<EventProxy>,
+      windows: Vec<Rc<RefCell<dyn ChildWindow>>>,
+      fill: Option<usize>
+) -> Vec<Uuid> {
+
+  let mut app_ref = app.borrow_mut();
+  let uuids: Vec<Uuid> = windows.iter().map(|window| window.borrow().get_uuid()).collect();
+
+  let mut row = RowLayout::new(proxy.clone(), app_ref.get_uuid(), Orientation::Vertical, 2.0);
+  for window in windows {
+    row.add_child(window, LayoutArgs::None).unwrap();
+  }
+  if let Some(index) = fill {
+    row.set_fill(Box::new(LayoutFill::Single(uuids[index])));
+  }
+
+  app_ref.set_contents(ChildType::Layout(Rc::new(RefCell::new(row))));
+  app_ref.run_headless();
+
+  uuids
+}
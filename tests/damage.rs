@@ -0,0 +1,140 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, Layout, LayoutArgs, UserEvent},
+  label::Label,
+  list::List,
+  multi_line_edit::MultiLineEdit,
+  scroll_layout::ScrollLayout,
+  window_utils::WindowUtils,
+};
+
+use tiny_skia::{Color, IntRect, PremultipliedColorU8};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+// Returns the region that a window covers within the main window
+fn window_region(window: &dyn ChildWindow) -> IntRect {
+  let (x, y) = window.get_location();
+  IntRect::from_xywh(x as i32, y as i32, window.get_width() as u32, window.get_height() as u32).unwrap()
+}
+
+#[test]
+fn typing_redraws_only_the_editor() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Notes".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+  let editor = Rc::new(RefCell::new(MultiLineEdit::new(proxy.clone(), id, 2)));
+  let uuids = show_windows(&app, &proxy, vec![label.clone(), editor.clone()], Some(1));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(uuids[1]).unwrap();
+  app_ref.take_damage();
+
+  app_ref.send_text("abc");
+  let damage = app_ref.take_damage();
+  let editor_region = window_region(&*editor.borrow());
+  assert!(!damage.is_empty());
+  assert!(damage.iter().all(|region| editor_region.contains(region)));
+  assert!(damage.iter().all(|region| region.intersect(&window_region(&*label.borrow())).is_none()));
+}
+
+#[test]
+fn unchanged_layouts_are_not_redrawn() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Before".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+  show_windows(&app, &proxy, vec![label.clone()], None);
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.take_damage();
+
+  // Nothing has changed, so a full redraw does nothing
+  WindowUtils::request_full_redraw(proxy.clone(), id);
+  app_ref.process_events();
+  assert!(app_ref.take_damage().is_empty());
+
+  // A window that redraws itself only damages its own region
+  label.borrow_mut().set_text("After".to_string());
+  app_ref.process_events();
+  assert_eq!(app_ref.take_damage(), vec![window_region(&*label.borrow())]);
+
+  // Its layout is drawn again the next time, and only that time
+  WindowUtils::request_full_redraw(proxy.clone(), id);
+  app_ref.process_events();
+  assert_eq!(app_ref.take_damage().len(), 1);
+  WindowUtils::request_full_redraw(proxy.clone(), id);
+  app_ref.process_events();
+  assert!(app_ref.take_damage().is_empty());
+}
+
+#[test]
+fn changes_between_frames_are_drawn_once() {
+
+  let (app, proxy, id) = create_app(200.0, 300.0);
+  let mut app_ref = app.borrow_mut();
+
+  let list = Rc::new(RefCell::new(List::new(proxy.clone(), id)));
+  app_ref.set_contents(ChildType::Window(list.clone()));
+  app_ref.run_headless();
+  app_ref.take_damage();
+
+  // Each append requests a redraw, and each one replaces the one before it
+  for index in 0..100 {
+    list.borrow_mut().append(format!("Item {index}"));
+  }
+  assert_eq!(proxy.get_pending_redraw_count(), 1);
+
+  assert_eq!(app_ref.process_events(), 1);
+  assert_eq!(app_ref.take_damage(), vec![window_region(&*list.borrow())]);
+  assert_eq!(proxy.get_pending_redraw_count(), 0);
+}
+
+#[test]
+fn scrolling_draws_over_earlier_redraws() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+
+  let editor = Rc::new(RefCell::new(MultiLineEdit::new(proxy.clone(), id, 2)));
+  let lines: Vec<String> = (0..50).map(|index| format!("Line {index}")).collect();
+  editor.borrow_mut().set_text(lines.join("\n"));
+  let scroller = Rc::new(RefCell::new(ScrollLayout::new(proxy.clone(), id)));
+  scroller.borrow_mut().add_child(editor.clone(), LayoutArgs::None).unwrap();
+  let scroller_uuid = scroller.borrow().get_uuid();
+  app_ref.set_contents(ChildType::Layout(scroller));
+  app_ref.run_headless();
+
+  // The editor scrolls, and then requests a redraw with contents that are
+  // out of date once its layout is updated
+  editor.borrow_mut().set_y_scroll(10.0);
+  let (x, y) = editor.borrow().get_location();
+  let mut stale = editor.borrow().get_pixmap();
+  stale.fill(Color::from_rgba8(255, 0, 0, 255));
+  WindowUtils::request_redraw(proxy.clone(), id, x, y, stale);
+  proxy.send_event(UserEvent::UpdateScroller(id, scroller_uuid)).unwrap();
+  app_ref.process_events();
+
+  // The scrolled layout is drawn on top of the editor's earlier redraw
+  let is_stale = |pixel: &PremultipliedColorU8| pixel.red() == 255 && pixel.green() == 0 && pixel.blue() == 0;
+  assert!(!app_ref.get_pixmap().pixels().iter().any(is_stale));
+}
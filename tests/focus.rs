@@ -0,0 +1,113 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  button::Button,
+  child_window::ChildWindow,
+  label::Label,
+  line_edit::LineEdit,
+  multi_line_edit::MultiLineEdit,
+};
+
+use tiny_skia::Color;
+
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn tab_walks_the_focus_chain() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let first = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let label = Rc::new(RefCell::new(Label::new(proxy.clone(), id, "Label".to_string(), Color::BLACK, Color::WHITE)));
+  let second = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let clicks = Rc::new(RefCell::new(0));
+  let clicks_clone = clicks.clone();
+  let button = Rc::new(RefCell::new(Button::new(
+        proxy.clone(),
+        id,
+        Some("OK".to_string()),
+        None,
+        None,
+        Color::WHITE,
+        move || *clicks_clone.borrow_mut() += 1
+  )));
+  show_windows(&app, &proxy, vec![first.clone(), label.clone(), second.clone(), button.clone()], None);
+
+  let mut app_ref = app.borrow_mut();
+
+  // The label is skipped
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(first.borrow().get_focused());
+  app_ref.send_text("ab");
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(!first.borrow().get_focused());
+  assert!(second.borrow().get_focused());
+  app_ref.send_text("cd");
+  assert_eq!(first.borrow().get_text(), Some("ab".to_string()));
+  assert_eq!(second.borrow().get_text(), Some("cd".to_string()));
+
+  // Enter clicks a button that has focus
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(button.borrow().get_focused());
+  app_ref.send_key(Key::Named(NamedKey::Enter), Some("\r"));
+  assert_eq!(*clicks.borrow(), 1);
+
+  // The chain wraps around in both directions
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(first.borrow().get_focused());
+  app_ref.send_modifiers(ModifiersState::SHIFT);
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(button.borrow().get_focused());
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(second.borrow().get_focused());
+}
+
+#[test]
+fn set_focus_directs_typing() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let first = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let second = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let uuids = show_windows(&app, &proxy, vec![first.clone(), second.clone()], None);
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(uuids[1]).unwrap();
+  app_ref.send_text("xyz");
+  assert_eq!(first.borrow().get_text(), Some("".to_string()));
+  assert_eq!(second.borrow().get_text(), Some("xyz".to_string()));
+
+  // Windows that aren't in the layout cannot receive focus
+  let other = LineEdit::new(proxy.clone(), id, "".to_string());
+  assert!(app_ref.set_focus(other.get_uuid()).is_err());
+  assert!(second.borrow().get_focused());
+}
+
+#[test]
+fn ctrl_tab_leaves_a_window_that_accepts_tab() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let editor = Rc::new(RefCell::new(MultiLineEdit::new(proxy.clone(), id, 2)));
+  let line_edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  let uuids = show_windows(&app, &proxy, vec![editor.clone(), line_edit.clone()], Some(0));
+  let editor_uuid = uuids[0];
+
+  let mut app_ref = app.borrow_mut();
+
+  // Tab is typed into the editor
+  app_ref.set_focus(editor_uuid).unwrap();
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(editor.borrow().get_focused());
+  assert_eq!(editor.borrow().get_text(), Some("  ".to_string()));
+
+  // Ctrl+Tab moves on
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Named(NamedKey::Tab), Some("\t"));
+  assert!(line_edit.borrow().get_focused());
+  assert!(!editor.borrow().get_focused());
+}
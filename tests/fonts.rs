@@ -0,0 +1,126 @@
+use fenetre::{
+  font_registry::{FontRegistry, FontStyle, FONT_WEIGHT_BOLD, FONT_WEIGHT_NORMAL},
+  text_font::{TextFont, MAX_CACHED_GLYPHS},
+};
+
+use tiny_skia::{Color, Pixmap};
+
+use std::{
+  fs,
+  path::PathBuf,
+};
+
+// Directory containing the bundled font
+fn get_resources_dir() -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources")
+}
+
+#[test]
+fn load_font_from_file_and_bytes() {
+
+  let path = get_resources_dir().join("FreeMonoBold.ttf");
+  let from_file = TextFont::new(path.to_str().unwrap(), 14.0).unwrap();
+  let from_bytes = TextFont::from_bytes(&fs::read(&path).unwrap(), 14.0).unwrap();
+  assert_eq!(from_file.get_bounds("Wy", None), from_bytes.get_bounds("Wy", None));
+
+  assert!(TextFont::new("/no/such/font.ttf", 14.0).is_err());
+  assert!(TextFont::from_bytes(b"not a font", 14.0).is_err());
+  assert!(TextFont::set_default_font_data(b"not a font".to_vec()).is_err());
+}
+
+#[test]
+fn registry_finds_fonts_by_family() {
+
+  let registry = FontRegistry::with_directories(vec![get_resources_dir()]);
+  assert_eq!(registry.get_families(), vec!["FreeMono".to_string()]);
+
+  // The closest match is returned when there is no exact one
+  let info = registry.find("freemono", FONT_WEIGHT_NORMAL, FontStyle::Italic).unwrap();
+  assert_eq!(info.weight, FONT_WEIGHT_BOLD);
+
+  assert!(registry.find("No Such Family", FONT_WEIGHT_NORMAL, FontStyle::Normal).is_none());
+  assert!(registry.load("FreeMono", FONT_WEIGHT_BOLD, FontStyle::Normal, 12.0).is_ok());
+  assert!(registry.load("No Such Family", FONT_WEIGHT_BOLD, FontStyle::Normal, 12.0).is_err());
+}
+
+#[test]
+fn fonts_are_shared() {
+
+  let first = TextFont::new_default().unwrap();
+  let second = TextFont::new_default().unwrap();
+  assert!(first.is_same_font(&second));
+
+  // The same file is only parsed once, whatever the size
+  let path = get_resources_dir().join("FreeMonoBold.ttf");
+  let small = TextFont::new(path.to_str().unwrap(), 10.0).unwrap();
+  let large = TextFont::new(path.to_str().unwrap(), 20.0).unwrap();
+  assert!(small.is_same_font(&large));
+
+  // The cached glyphs are kept separately for each size
+  assert!(small.get_bounds("W", None).0 < large.get_bounds("W", None).0);
+  let other = TextFont::new(path.to_str().unwrap(), 10.0).unwrap();
+  assert_eq!(small.get_bounds("W", None), other.get_bounds("W", None));
+}
+
+#[test]
+fn layout_finds_carets_and_characters() {
+
+  let font = TextFont::new_default().unwrap();
+  let layout = font.layout("Wie\u{301}ll", None);
+  assert_eq!(layout.get_grapheme_count(), 5);
+  assert_eq!(layout.get_size(), font.get_bounds("Wie\u{301}ll", None));
+
+  // The accent shares the position of the letter it is on
+  let glyphs = layout.get_glyphs();
+  assert_eq!(glyphs.len(), 6);
+  assert_eq!(glyphs[2].x, glyphs[3].x);
+  assert_eq!(glyphs[3].grapheme_index, 2);
+
+  // Clicking a character's right half puts the caret after it
+  let left = layout.get_caret_x(2);
+  let right = layout.get_caret_x(3);
+  assert!(left < right);
+  assert_eq!(layout.get_grapheme_at(left + 1.0), 2);
+  assert_eq!(layout.get_grapheme_at(right - 1.0), 3);
+  assert_eq!(layout.get_grapheme_at(-10.0), 0);
+  assert_eq!(layout.get_grapheme_at(1000.0), 5);
+  assert_eq!(layout.get_caret_x(100), layout.get_caret_x(5));
+}
+
+#[test]
+fn glyph_cache_is_bounded() {
+
+  // Draw more glyphs than are cached, with padding so that no other test uses the font
+  let mut data = fs::read(get_resources_dir().join("FreeMonoBold.ttf")).unwrap();
+  data.extend_from_slice(&[0; 4]);
+  let text: String = ('!'..='~').collect();
+  let mut pixmap = Pixmap::new(2000, 100).unwrap();
+  for size in 6..60 {
+    let font = TextFont::from_bytes(&data, size as f32).unwrap();
+    font.draw_text(&text, &mut pixmap, 0, 0, Color::BLACK, Color::WHITE, -1, Color::BLACK, None);
+    assert!(font.get_cached_glyph_count() <= MAX_CACHED_GLYPHS);
+  }
+
+  // Recently drawn glyphs are still cached
+  let font = TextFont::from_bytes(&data, 59.0).unwrap();
+  let count = font.get_cached_glyph_count();
+  font.draw_text(&text, &mut pixmap, 0, 0, Color::BLACK, Color::WHITE, -1, Color::BLACK, None);
+  assert_eq!(font.get_cached_glyph_count(), count);
+}
+
+#[cfg(unix)]
+#[test]
+fn registry_scans_each_directory_once() {
+
+  // A font directory with a sub-directory that links back to it
+  let directory = std::env::temp_dir().join(format!("fenetre-fonts-{}", std::process::id()));
+  let nested = directory.join("nested");
+  fs::create_dir_all(&nested).unwrap();
+  fs::copy(get_resources_dir().join("FreeMonoBold.ttf"), directory.join("FreeMonoBold.ttf")).unwrap();
+  std::os::unix::fs::symlink(&directory, nested.join("loop")).unwrap();
+
+  // Listing the sub-directory too doesn't add the font again
+  let registry = FontRegistry::with_directories(vec![directory.clone(), nested]);
+  fs::remove_dir_all(&directory).unwrap();
+  assert_eq!(registry.get_fonts().len(), 1);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{
+    ChildType,
+    ChildWindow,
+    Layout,
+    LayoutArgs,
+    Orientation,
+  },
+  event_proxy::EventProxy,
+  label::Label,
+  line_edit::LineEdit,
+  MainApp,
+  MainAppSize,
+  row_layout::RowLayout,
+};
+
+use tiny_skia::{Color, Pixmap};
+
+use uuid::Uuid;
+
+use winit::{
+  event::MouseButton,
+  keyboard::{Key, NamedKey},
+};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+// Returns the RGB value of a pixel
+fn pixel_at(pixmap: &Pixmap, x: u32, y: u32) -> (u8, u8, u8) {
+  let pixel = pixmap.pixel(x, y).unwrap();
+  (pixel.red(), pixel.green(), pixel.blue())
+}
+
+#[test]
+fn frame_has_requested_size() {
+
+  let (app, _proxy, _id) = create_app(320.0, 240.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  let pixmap = app_ref.get_pixmap();
+  assert_eq!((pixmap.width(), pixmap.height()), (320, 240));
+  assert_eq!(pixel_at(pixmap, 10, 10), (255, 255, 255));
+}
+
+#[test]
+fn startup_callback_is_called() {
+
+  let called = Rc::new(RefCell::new(false));
+  let called_clone = called.clone();
+  let proxy = Rc::new(EventProxy::new_headless());
+  let app = MainApp::new(
+        Uuid::new_v4(),
+        "Headless",
+        MainAppSize::Actual(0.0, 0.0, 100.0, 100.0),
+        proxy,
+        move || *called_clone.borrow_mut() = true
+  );
+  app.borrow_mut().run_headless();
+
+  assert!(*called.borrow());
+}
+
+#[test]
+fn border_layout_draws_child() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Hello".to_string(),
+        Color::BLACK,
+        Color::from_rgba8(255, 0, 0, 255)
+  )));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(label.clone()));
+  app_ref.run_headless();
+
+  // The label's top left corner is its background color
+  let (x, y) = label.borrow().get_location();
+  assert_eq!(pixel_at(app_ref.get_pixmap(), x as u32 + 1, y as u32 + 1), (255, 0, 0));
+}
+
+#[test]
+fn row_layout_places_children_in_order() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let mut row = RowLayout::new(proxy.clone(), id, Orientation::Horizontal, 5.0);
+  let first = Rc::new(RefCell::new(Label::new(
+        proxy.clone(), id, "First".to_string(), Color::BLACK, Color::WHITE)));
+  let second = Rc::new(RefCell::new(Label::new(
+        proxy.clone(), id, "Second".to_string(), Color::BLACK, Color::WHITE)));
+  row.add_child(first.clone(), LayoutArgs::None).unwrap();
+  row.add_child(second.clone(), LayoutArgs::None).unwrap();
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Layout(Rc::new(RefCell::new(row))));
+  app_ref.run_headless();
+
+  let (first_x, first_y) = first.borrow().get_location();
+  let (second_x, second_y) = second.borrow().get_location();
+  let first_width = first.borrow().get_drawing_size().0;
+  assert!(second_x >= first_x + first_width);
+  assert_eq!(first_y, second_y);
+}
+
+#[test]
+fn changes_are_drawn_after_processing_events() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Hello".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(label.clone()));
+  app_ref.run_headless();
+  let before = app_ref.get_pixmap().clone();
+
+  label.borrow_mut().set_text("Goodbye".to_string());
+  assert!(app_ref.process_events() > 0);
+  assert_ne!(before.data(), app_ref.get_pixmap().data());
+}
+
+#[test]
+fn line_edit_receives_typed_text() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let line_edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, String::new())));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(line_edit.clone()));
+  app_ref.run_headless();
+
+  // Give the LineEdit focus, then type into it
+  let (x, y) = line_edit.borrow().get_location();
+  app_ref.send_click(MouseButton::Left, x + 5.0, y + 5.0);
+  app_ref.send_text("abc");
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+
+  assert_eq!(line_edit.borrow().get_text(), Some("ab".to_string()));
+}
+
+#[test]
+fn resize_changes_frame_size() {
+
+  let (app, _proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+  app_ref.send_resized(150.0, 80.0);
+
+  let pixmap = app_ref.get_pixmap();
+  assert_eq!((pixmap.width(), pixmap.height()), (150, 80));
+}
+
+#[test]
+fn status_bar_is_placed_at_bottom() {
+
+  let (app, proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.enable_statusbar(proxy.clone());
+  app_ref.set_status_message("Ready".to_string());
+  app_ref.run_headless();
+
+  let status_bar = app_ref.get_status_bar().unwrap();
+  let (_x, y) = status_bar.borrow().get_location();
+  let (_width, height) = status_bar.borrow().get_drawing_size();
+  assert!(y > 50.0);
+  assert_eq!(y + height, 100.0);
+}
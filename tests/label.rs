@@ -0,0 +1,57 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::ChildWindow,
+  label::Label,
+  text_font::TextFont,
+};
+
+use tiny_skia::Color;
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn wrap_text_at_words_and_newlines() {
+
+  let font = TextFont::new_default().unwrap();
+  let (width, _height) = font.get_bounds("one two", None);
+
+  assert_eq!(font.wrap_text("one two three\nfour", width), vec!["one two", "three", "four"]);
+  assert_eq!(font.wrap_text("", width), vec![""]);
+
+  // A word that is too long is broken where it no longer fits
+  let (width, _height) = font.get_bounds("abc", None);
+  assert_eq!(font.wrap_text("abcdefg", width), vec!["abc", "def", "g"]);
+}
+
+#[test]
+fn wrapping_label_fits_layout_width() {
+
+  let (app, proxy, id) = create_app(120.0, 300.0);
+  let text = "This message is too long to fit on a single line".to_string();
+  let mut label = Label::new(proxy.clone(), id, text, Color::BLACK, Color::WHITE);
+  let (_width, one_line) = label.get_drawing_size();
+  label.set_word_wrap(true);
+  let label = Rc::new(RefCell::new(label));
+  let other = Rc::new(RefCell::new(Label::new(proxy.clone(), id, "x".to_string(), Color::BLACK, Color::WHITE)));
+  show_windows(&app, &proxy, vec![label.clone(), other], None);
+
+  // The layout gives the label the height it needs for its width
+  let (width, height) = label.borrow().get_drawing_size();
+  assert_eq!(width, 120.0);
+  assert_eq!(Some(height), label.borrow().get_height_for_width(120.0));
+  assert!(height > 2.0 * one_line);
+  assert!(label.borrow().get_height_for_width(60.0).unwrap() > height);
+
+  // Without wrapping, the label fits its lines
+  label.borrow_mut().set_word_wrap(false);
+  label.borrow_mut().set_text("first\nsecond".to_string());
+  let (_width, two_lines) = label.borrow().get_drawing_size();
+  assert_eq!(two_lines, 2.0 * one_line - 4.0);
+  assert_eq!(label.borrow().get_height_for_width(10.0), None);
+}
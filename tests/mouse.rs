@@ -0,0 +1,137 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  list::{List, SelectionMode},
+  table::Table,
+};
+
+use uuid::Uuid;
+
+use winit::{
+  event::MouseButton,
+  keyboard::ModifiersState,
+};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn double_click_opens_list_item() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+  let mut list = List::new(proxy.clone(), id);
+  list.set_items(vec!["one".to_string(), "two".to_string(), "three".to_string()]);
+  let list_rc = Rc::new(RefCell::new(list));
+
+  let opened = Rc::new(RefCell::new(Vec::<Uuid>::new()));
+  let opened_clone = opened.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_process_selected_items_event_callback(Box::new(move |source| {
+    opened_clone.borrow_mut().push(source);
+  }));
+  app_ref.set_contents(ChildType::Window(list_rc.clone()));
+  app_ref.run_headless();
+
+  // A single click only selects the item
+  let (x, y) = list_rc.borrow().get_location();
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 5.0);
+  assert!(opened.borrow().is_empty());
+  assert_eq!(list_rc.borrow().get_selected_items(), vec![0]);
+
+  // The second click opens it, and leaves it selected
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 5.0);
+  assert_eq!(*opened.borrow(), vec![list_rc.borrow().get_uuid()]);
+  assert_eq!(list_rc.borrow().get_selected_items(), vec![0]);
+}
+
+#[test]
+fn clicks_far_apart_are_not_a_double_click() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+  let mut list = List::new(proxy.clone(), id);
+  list.set_items(vec!["one".to_string(), "two".to_string(), "three".to_string()]);
+  let list_rc = Rc::new(RefCell::new(list));
+
+  let opened = Rc::new(RefCell::new(0));
+  let opened_clone = opened.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_process_selected_items_event_callback(Box::new(move |_source| {
+    *opened_clone.borrow_mut() += 1;
+  }));
+  app_ref.set_contents(ChildType::Window(list_rc.clone()));
+  app_ref.run_headless();
+
+  let (x, y) = list_rc.borrow().get_location();
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 5.0);
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 40.0);
+  assert_eq!(*opened.borrow(), 0);
+
+  // A triple click is one double click followed by a single click
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 40.0);
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 40.0);
+  assert_eq!(*opened.borrow(), 1);
+}
+
+#[test]
+fn double_click_opens_table_row() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+  let mut table = Table::new(proxy.clone(), id);
+  table.add_column("Name".to_string());
+  table.add_row(vec!["first".to_string()]);
+  table.add_row(vec!["second".to_string()]);
+  let table_rc = Rc::new(RefCell::new(table));
+
+  let opened = Rc::new(RefCell::new(Vec::<Uuid>::new()));
+  let opened_clone = opened.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_process_selected_items_event_callback(Box::new(move |source| {
+    opened_clone.borrow_mut().push(source);
+  }));
+  app_ref.set_contents(ChildType::Window(table_rc.clone()));
+  app_ref.run_headless();
+
+  // Double click on the second data row, below the header
+  let (x, y) = table_rc.borrow().get_location();
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 50.0);
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 50.0);
+  assert_eq!(*opened.borrow(), vec![table_rc.borrow().get_uuid()]);
+  assert_eq!(table_rc.borrow().get_selected_rows().len(), 1);
+}
+
+#[test]
+fn modifiers_extend_list_selection() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+  let mut list = List::new(proxy.clone(), id);
+  list.set_selection_mode(SelectionMode::Multiple);
+  list.set_items((0..6).map(|index| format!("item {index}")).collect());
+  let list_rc = Rc::new(RefCell::new(list));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(list_rc.clone()));
+  app_ref.run_headless();
+
+  // Rows are about 21 pixels apart
+  let (x, y) = list_rc.borrow().get_location();
+  let row_y = |row: usize| y + 10.0 + 21.0 * row as f64;
+
+  app_ref.send_click(MouseButton::Left, x + 10.0, row_y(0));
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_click(MouseButton::Left, x + 10.0, row_y(2));
+  assert_eq!(list_rc.borrow().get_selected_items(), vec![0, 2]);
+
+  app_ref.send_modifiers(ModifiersState::SHIFT);
+  app_ref.send_click(MouseButton::Left, x + 10.0, row_y(4));
+  assert_eq!(list_rc.borrow().get_selected_items(), vec![0, 2, 3, 4]);
+
+  // Without modifiers, a click replaces the selection
+  app_ref.send_modifiers(ModifiersState::empty());
+  app_ref.send_click(MouseButton::Left, x + 10.0, row_y(1));
+  assert_eq!(list_rc.borrow().get_selected_items(), vec![1]);
+}
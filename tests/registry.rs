@@ -0,0 +1,84 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, Layout, LayoutArgs, Orientation},
+  label::Label,
+  line_edit::LineEdit,
+  row_layout::RowLayout,
+  tab_layout::TabLayout,
+};
+
+use tiny_skia::Color;
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn windows_are_found_by_name_and_type() {
+
+  let (app, proxy, id) = create_app(300.0, 200.0);
+
+  // A form whose fields are in a row and in a tab that isn't active
+  let mut username = LineEdit::new(proxy.clone(), id, "alice".to_string());
+  username.set_name("username".to_string());
+  let username_id = username.get_uuid();
+  let mut form = RowLayout::new(proxy.clone(), id, Orientation::Vertical, 0.0);
+  form.add_child(Rc::new(RefCell::new(username)), LayoutArgs::None).unwrap();
+
+  let mut label = Label::new(proxy.clone(), id, "Server".to_string(), Color::BLACK, Color::WHITE);
+  label.set_name("server".to_string());
+  let mut server = LineEdit::new(proxy.clone(), id, "example.com".to_string());
+  server.set_name("server".to_string());
+  let mut tabs = TabLayout::new(proxy.clone(), id);
+  tabs.add_tab("Server".to_string(), ChildType::Window(Rc::new(RefCell::new(label))));
+  tabs.add_tab("Address".to_string(), ChildType::Window(Rc::new(RefCell::new(server))));
+  tabs.add_tab("Account".to_string(), ChildType::Layout(Rc::new(RefCell::new(form))));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Layout(Rc::new(RefCell::new(tabs))));
+  app_ref.run_headless();
+
+  // The window is only found as its own type
+  let username_ref = app_ref.find::<LineEdit>("username").unwrap();
+  assert_eq!(username_ref.borrow().get_text(), Some("alice".to_string()));
+  assert!(app_ref.find::<Label>("username").is_none());
+  assert!(app_ref.find::<LineEdit>("password").is_none());
+
+  // When names are shared, the type picks the window
+  let server_ref = app_ref.find::<LineEdit>("server").unwrap();
+  assert_eq!(server_ref.borrow().get_text(), Some("example.com".to_string()));
+  assert!(app_ref.find::<Label>("server").is_some());
+
+  // Windows can be changed through the reference
+  username_ref.borrow_mut().set_text("bob".to_string());
+  let by_id = app_ref.find_by_id::<LineEdit>(username_id).unwrap();
+  assert_eq!(by_id.borrow().get_text(), Some("bob".to_string()));
+}
+
+#[test]
+fn removed_windows_are_not_found() {
+
+  let (app, proxy, id) = create_app(300.0, 200.0);
+
+  let mut field = LineEdit::new(proxy.clone(), id, "".to_string());
+  field.set_name("field".to_string());
+  let field_id = field.get_uuid();
+  let mut inner = RowLayout::new(proxy.clone(), id, Orientation::Horizontal, 0.0);
+  inner.add_child(Rc::new(RefCell::new(field)), LayoutArgs::None).unwrap();
+  let outer_rc = Rc::new(RefCell::new(RowLayout::new(proxy.clone(), id, Orientation::Vertical, 0.0)));
+  outer_rc.borrow_mut().add_layout(Rc::new(RefCell::new(inner)), LayoutArgs::None).unwrap();
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Layout(outer_rc.clone()));
+  app_ref.run_headless();
+  assert!(app_ref.find::<LineEdit>("field").is_some());
+
+  // Clearing a layout removes the windows in its inner layouts too
+  outer_rc.borrow_mut().clear();
+  assert!(app_ref.find::<LineEdit>("field").is_none());
+  assert!(app_ref.find_by_id::<LineEdit>(field_id).is_none());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::ChildWindow,
+  font_registry::FONT_WEIGHT_BOLD,
+  label::Label,
+  list::List,
+  rich_text::{RichText, TextStyle},
+  status_bar::StatusBar,
+  table::Table,
+};
+
+use tiny_skia::Color;
+
+#[test]
+fn parse_markup_into_runs() {
+
+  let text = RichText::parse_markup("Found <b>3</b> &lt;matches&gt; <color=#ff000080><u>here</u></color>").unwrap();
+  assert_eq!(text.get_text(), "Found 3 <matches> here");
+
+  let runs = text.get_runs();
+  assert_eq!(runs.len(), 4);
+  assert_eq!(runs[1].text, "3");
+  assert_eq!(runs[1].style.weight, FONT_WEIGHT_BOLD);
+  assert_eq!(runs[2].style, TextStyle::default());
+  assert_eq!(runs[3].style.color, Some(Color::from_rgba8(255, 0, 0, 128)));
+  assert!(runs[3].style.underline);
+
+  // Slicing keeps the styles of the runs it covers
+  let slice = text.slice(6..12);
+  assert_eq!(slice.get_text(), "3 <mat");
+  assert_eq!(slice.get_runs()[0].style.weight, FONT_WEIGHT_BOLD);
+
+  assert!(RichText::parse_markup("<b>open").is_err());
+  assert!(RichText::parse_markup("<b><i>crossed</b></i>").is_err());
+  assert!(RichText::parse_markup("<color=red>x</color>").is_err());
+  assert!(RichText::parse_markup("a & b").is_err());
+  assert_eq!(RichText::parse_markup(&RichText::escape_markup("a & <b>")).unwrap().get_text(), "a & <b>");
+}
+
+#[test]
+fn label_draws_colored_runs() {
+
+  let (_app, proxy, id) = create_app(200.0, 100.0);
+  let mut label = Label::new(proxy.clone(), id, "".to_string(), Color::BLACK, Color::WHITE);
+  label.set_rich_text(RichText::parse_markup("Warning: <color=#ff0000>disk full</color>").unwrap());
+  assert_eq!(label.get_text(), Some("Warning: disk full".to_string()));
+
+  let red = label.get_pixmap().pixels().iter()
+    .filter(|pixel| pixel.red() > 200 && pixel.green() < 50 && pixel.blue() < 50)
+    .count();
+  assert!(red > 0);
+
+  // Plain text replaces the rich text
+  label.set_text("Ready".to_string());
+  assert!(label.get_rich_text().is_none());
+}
+
+#[test]
+fn rich_text_in_status_bar_table_and_list() {
+
+  let (_app, proxy, id) = create_app(200.0, 100.0);
+
+  let mut status_bar = StatusBar::new(proxy.clone(), id, Some(Color::WHITE));
+  status_bar.set_rich_message(RichText::parse_markup("Saved <color=#ff0000>with warnings</color>").unwrap());
+  assert_eq!(status_bar.get_message(), Some("Saved with warnings".to_string()));
+
+  let mut table = Table::new(proxy.clone(), id);
+  table.add_column("Name".to_string());
+  table.add_row(vec!["alpha".to_string()]);
+  assert!(table.set_cell_rich_text(0, 0, RichText::parse_markup("<bg=#ffff00>al</bg>pha").unwrap()).is_ok());
+  assert!(table.set_cell_rich_text(0, 1, RichText::from("beta")).is_err());
+  assert!(table.set_cell_rich_text(1, 0, RichText::from("beta")).is_err());
+
+  let mut list = List::new(proxy.clone(), id);
+  list.set_items(vec!["first".to_string(), "second".to_string()]);
+  assert!(list.set_item_rich_text(1, RichText::parse_markup("sec<b>ond</b>").unwrap()).is_ok());
+  assert!(list.set_item_rich_text(2, RichText::from("third")).is_err());
+
+  // The rich text stays with its item when the items move
+  list.remove(0);
+  assert_eq!(list.get_item_by_index(0), Some(&"second".to_string()));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  label::Label,
+  line_edit::LineEdit,
+  text_font::TextFont,
+};
+
+use tiny_skia::Color;
+
+use winit::{
+  event::{ElementState, MouseButton},
+  keyboard::{Key, NamedKey},
+};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn windows_are_drawn_at_the_scale_factor() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Hello".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+  show_windows(&app, &proxy, vec![label.clone()], None);
+
+  let mut app_ref = app.borrow_mut();
+
+  let (width, height) = (label.borrow().get_width(), label.borrow().get_height());
+  let pixmap_width = label.borrow().get_pixmap().width();
+
+  app_ref.set_scale_factor(2.0);
+  assert_eq!(app_ref.get_scale_factor(), 2.0);
+  assert_eq!(app_ref.get_size(), (200.0, 100.0));
+
+  // The main window's pixmap has twice as many pixels in each direction
+  assert_eq!((app_ref.get_pixmap().width(), app_ref.get_pixmap().height()), (400, 200));
+
+  // The label keeps its logical size, but is drawn with twice the pixels
+  assert!((label.borrow().get_width() - width).abs() <= 1.0);
+  assert!((label.borrow().get_height() - height).abs() <= 1.0);
+  let scaled_width = label.borrow().get_pixmap().width();
+  assert!(scaled_width.abs_diff(pixmap_width * 2) <= 2);
+}
+
+#[test]
+fn clicks_are_in_logical_units() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+
+  let line_edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "abcdef".to_string())));
+  app_ref.set_contents(ChildType::Window(line_edit.clone()));
+  app_ref.run_headless();
+
+  // The caret positions in logical units are those of the unscaled font
+  let font = TextFont::new_default().unwrap();
+  let caret_x = font.layout("abcdef", None).get_caret_x(3) as f64;
+
+  app_ref.set_scale_factor(2.0);
+
+  // Clicking just after "abc" and deleting removes the "c"
+  let (x, y) = line_edit.borrow().get_location();
+  app_ref.send_cursor_moved(x + caret_x + 2.0, y + 4.0);
+  app_ref.send_mouse_input(ElementState::Pressed, MouseButton::Left);
+  app_ref.send_mouse_input(ElementState::Released, MouseButton::Left);
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+  assert_eq!(line_edit.borrow().get_text(), Some("abdef".to_string()));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::ChildWindow,
+  clipboard::Clipboard,
+  multi_line_edit::MultiLineEdit,
+  text_font::TextFont,
+  MainApp,
+};
+
+use winit::{
+  event::{ElementState, MouseButton},
+  keyboard::{Key, ModifiersState, NamedKey},
+};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+// Creates a headless MainApp whose only contents is a MultiLineEdit that has focus
+fn create_editor(text: &str) -> (RefCell<MainApp>, Rc<RefCell<MultiLineEdit>>) {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let mut editor = MultiLineEdit::new(proxy.clone(), id, 2);
+  editor.set_text(text.to_string());
+  let editor = Rc::new(RefCell::new(editor));
+  let uuids = show_windows(&app, &proxy, vec![editor.clone()], Some(0));
+  app.borrow_mut().set_focus(uuids[0]).unwrap();
+
+  (app, editor)
+}
+
+#[test]
+fn shift_keys_select_across_lines() {
+
+  let (app, editor) = create_editor("hello\nworld");
+  let mut app_ref = app.borrow_mut();
+
+  app_ref.send_key(Key::Named(NamedKey::ArrowRight), None);
+  app_ref.send_modifiers(ModifiersState::SHIFT);
+  app_ref.send_key(Key::Named(NamedKey::ArrowDown), None);
+  app_ref.send_key(Key::Named(NamedKey::End), None);
+  assert_eq!(editor.borrow().get_selected_text(), Some("ello\nworld".to_string()));
+
+  // Ctrl+C copies only the selection
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("c".into()), Some("\u{3}"));
+  assert_eq!(Clipboard::get_text(), Ok(Some("ello\nworld".to_string())));
+
+  // Typing replaces the selection
+  app_ref.send_modifiers(ModifiersState::empty());
+  app_ref.send_text("i");
+  assert_eq!(editor.borrow().get_text(), Some("hi".to_string()));
+  assert_eq!(editor.borrow().get_selected_text(), None);
+
+  // Moving without Shift removes the selection
+  app_ref.send_modifiers(ModifiersState::SHIFT);
+  app_ref.send_key(Key::Named(NamedKey::Home), None);
+  assert_eq!(editor.borrow().get_selected_text(), Some("hi".to_string()));
+  app_ref.send_modifiers(ModifiersState::empty());
+  app_ref.send_key(Key::Named(NamedKey::End), None);
+  assert_eq!(editor.borrow().get_selected_text(), None);
+}
+
+#[test]
+fn set_selection_and_delete() {
+
+  let (app, editor) = create_editor("one\ntwo\nthree");
+  let mut app_ref = app.borrow_mut();
+
+  editor.borrow_mut().set_selection(0, 1, 2, 2).unwrap();
+  assert_eq!(editor.borrow().get_selected_text(), Some("ne\ntwo\nth".to_string()));
+  app_ref.send_key(Key::Named(NamedKey::Delete), None);
+  assert_eq!(editor.borrow().get_text(), Some("oree".to_string()));
+  assert!(editor.borrow().is_modified());
+
+  // The selection can end before it starts
+  editor.borrow_mut().set_selection(0, 3, 0, 1).unwrap();
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+  assert_eq!(editor.borrow().get_text(), Some("oe".to_string()));
+
+  assert!(editor.borrow_mut().set_selection(0, 0, 5, 0).is_err());
+}
+
+#[test]
+fn mouse_drag_selects() {
+
+  let (app, editor) = create_editor("first line\nsecond line");
+  let mut app_ref = app.borrow_mut();
+
+  let font = TextFont::new_default().unwrap();
+  let (_width, char_height) = font.get_bounds("Wy", None);
+  let first_x = font.layout("first line", None).get_caret_x(6) as f64;
+  let second_x = font.layout("second line", None).get_caret_x(6) as f64;
+  let (x, y) = editor.borrow().get_location();
+
+  // Drag from the start of "line" on the first line to the end of "second"
+  app_ref.send_cursor_moved(x + first_x + 1.0, y + 2.0);
+  app_ref.send_mouse_input(ElementState::Pressed, MouseButton::Left);
+  app_ref.send_cursor_moved(x + second_x + 1.0, y + char_height as f64 + 4.0);
+  app_ref.send_mouse_input(ElementState::Released, MouseButton::Left);
+  assert_eq!(editor.borrow().get_selected_text(), Some("line\nsecond".to_string()));
+
+  // A click without Shift removes the selection
+  app_ref.send_click(MouseButton::Left, x + 1.0, y + 2.0);
+  assert_eq!(editor.borrow().get_selected_text(), None);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, UserEvent},
+  line_edit::LineEdit,
+  shortcut::{Shortcut, ShortcutAction},
+};
+
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn parse_and_display() {
+
+  let shortcut = Shortcut::parse("ctrl+shift+n").unwrap();
+  assert_eq!(shortcut.get_modifiers(), ModifiersState::CONTROL | ModifiersState::SHIFT);
+  assert_eq!(shortcut.to_string(), "Ctrl+Shift+N");
+  assert_eq!(shortcut, Shortcut::new(ModifiersState::SHIFT | ModifiersState::CONTROL, Key::Character("N".into())));
+
+  assert_eq!(Shortcut::parse("Alt+F4").unwrap().to_string(), "Alt+F4");
+  assert_eq!(Shortcut::parse("Ctrl+PageUp").unwrap().get_key(), Key::Named(NamedKey::PageUp));
+  assert!(Shortcut::parse("Ctrl+Shift").is_err());
+  assert!(Shortcut::parse("Ctrl+Launch").is_err());
+  assert!(Shortcut::parse("S+Ctrl").is_err());
+}
+
+#[test]
+fn shortcut_runs_before_the_focused_window() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+
+  let saves = Rc::new(RefCell::new(0));
+  let saves_clone = saves.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.add_shortcut(
+        Shortcut::parse("Ctrl+S").unwrap(),
+        ShortcutAction::Callback(Box::new(move || *saves_clone.borrow_mut() += 1))
+  );
+  app_ref.set_contents(ChildType::Window(edit.clone()));
+  app_ref.run_headless();
+
+  let edit_uuid = edit.borrow().get_uuid();
+  app_ref.set_focus(edit_uuid).unwrap();
+
+  // Without Ctrl, the key is typed
+  app_ref.send_text("s");
+  assert_eq!(*saves.borrow(), 0);
+
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("s".into()), Some("\u{13}"));
+  assert_eq!(*saves.borrow(), 1);
+  assert_eq!(edit.borrow().get_text(), Some("s".to_string()));
+
+  // The chord must match exactly
+  app_ref.send_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT);
+  app_ref.send_key(Key::Character("S".into()), Some("\u{13}"));
+  assert_eq!(*saves.borrow(), 1);
+
+  app_ref.remove_shortcut(&Shortcut::parse("Ctrl+S").unwrap());
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("s".into()), Some("\u{13}"));
+  assert_eq!(*saves.borrow(), 1);
+}
+
+#[test]
+fn shortcut_sends_user_event() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+
+  let received = Rc::new(RefCell::new(Vec::<u64>::new()));
+  let received_clone = received.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_user_defined_event_callback(Box::new(move |code, _data| {
+    received_clone.borrow_mut().push(code);
+  }));
+  app_ref.add_shortcut(
+        Shortcut::parse("Ctrl+Shift+N").unwrap(),
+        ShortcutAction::Event(UserEvent::UserDefined(id, 7, Vec::new()))
+  );
+  app_ref.set_contents(ChildType::Window(edit.clone()));
+  app_ref.run_headless();
+
+  app_ref.send_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT);
+  app_ref.send_key(Key::Character("N".into()), None);
+  assert_eq!(*received.borrow(), vec![7]);
+}
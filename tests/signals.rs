@@ -0,0 +1,103 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, Layout, Orientation},
+  list::List,
+  slider::Slider,
+  tab_layout::TabLayout,
+};
+
+use uuid::Uuid;
+
+use winit::event::MouseButton;
+
+use std::{
+  cell::RefCell,
+  rc::{Rc, Weak},
+};
+
+#[test]
+fn each_list_has_its_own_listeners() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+  let mut list = List::new(proxy.clone(), id);
+  list.set_items(vec!["one".to_string(), "two".to_string()]);
+
+  // Both listeners are called, until one is disconnected
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let calls_clone = calls.clone();
+  let first = list.on_selection_changed(move || calls_clone.borrow_mut().push("first"));
+  let calls_clone = calls.clone();
+  list.on_selection_changed(move || calls_clone.borrow_mut().push("second"));
+
+  // A list that isn't clicked doesn't call its listener
+  let other_list = List::new(proxy.clone(), id);
+  let calls_clone = calls.clone();
+  other_list.on_selection_changed(move || calls_clone.borrow_mut().push("other"));
+
+  let list_rc = Rc::new(RefCell::new(list));
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(list_rc.clone()));
+  app_ref.run_headless();
+
+  let (x, y) = list_rc.borrow().get_location();
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 5.0);
+  assert_eq!(*calls.borrow(), vec!["first", "second"]);
+
+  assert!(list_rc.borrow().disconnect(first));
+  assert!(!list_rc.borrow().disconnect(first));
+  app_ref.send_click(MouseButton::Left, x + 10.0, y + 40.0);
+  assert_eq!(*calls.borrow(), vec!["first", "second", "second"]);
+}
+
+#[test]
+fn slider_value_change_carries_the_slider_id() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut slider = Slider::new(proxy.clone(), id, Orientation::Horizontal);
+  slider.set_range(0.0, 100.0, 0.0);
+
+  let values = Rc::new(RefCell::new(Vec::new()));
+  let values_clone = values.clone();
+  slider.on_value_changed(move |value| values_clone.borrow_mut().push(value));
+
+  // The global callback is still called, with the slider's ID
+  let sources = Rc::new(RefCell::new(Vec::<Uuid>::new()));
+  let sources_clone = sources.clone();
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_slider_value_changed_event_callback(Box::new(move |source, _value| {
+    sources_clone.borrow_mut().push(source);
+  }));
+  app_ref.run_headless();
+
+  slider.set_value(25.0);
+  app_ref.process_events();
+  assert_eq!(*values.borrow(), vec![25.0]);
+  assert_eq!(*sources.borrow(), vec![slider.get_uuid()]);
+}
+
+#[test]
+fn removing_tabs_disconnects_their_listeners() {
+
+  let (app, proxy, id) = create_app(200.0, 200.0);
+
+  // The listener keeps the list that it is connected to
+  let list_rc = Rc::new(RefCell::new(List::new(proxy.clone(), id)));
+  let list_clone = list_rc.clone();
+  list_rc.borrow().on_selection_changed(move || { list_clone.borrow().get_selected_items(); });
+  let list_weak: Weak<RefCell<List>> = Rc::downgrade(&list_rc);
+
+  let mut tabs = TabLayout::new(proxy.clone(), id);
+  tabs.add_tab("List".to_string(), ChildType::Window(list_rc));
+
+  let tabs_rc = Rc::new(RefCell::new(tabs));
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Layout(tabs_rc.clone()));
+  app_ref.run_headless();
+
+  // Once the tabs are removed, nothing keeps the list
+  tabs_rc.borrow_mut().clear();
+  assert!(list_weak.upgrade().is_none());
+}
@@ -0,0 +1,155 @@
+use fenetre::{
+  child_window::{Alignment, ChildType},
+  event_proxy::EventProxy,
+  label::Label,
+  rich_text::RichText,
+  snapshot::Snapshot,
+  tab_layout::TabLayout,
+  table::Table,
+};
+
+use tiny_skia::{Color, ColorU8, Pixmap};
+
+use uuid::Uuid;
+
+use std::{
+  cell::RefCell,
+  env,
+  fs,
+  path::PathBuf,
+  rc::Rc,
+};
+
+// Allowed difference in each color channel
+const TOLERANCE: u8 = 2;
+
+// Returns the path of a reference image
+fn reference(name: &str) -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{name}.png"))
+}
+
+#[test]
+fn label_matches_reference() {
+
+  let proxy = Rc::new(EventProxy::new_headless());
+  let label = Label::new(
+        proxy,
+        Uuid::new_v4(),
+        "Hello, fenetre".to_string(),
+        Color::BLACK,
+        Color::from_rgba8(255, 255, 200, 255)
+  );
+
+  let snapshot = Snapshot::of_window(Rc::new(RefCell::new(label)), 160, 40);
+  snapshot.compare(reference("label"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn wrapped_label_matches_reference() {
+
+  let proxy = Rc::new(EventProxy::new_headless());
+  let mut label = Label::new(
+        proxy,
+        Uuid::new_v4(),
+        "The file could not be saved.\nCheck the permissions and try again.".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  );
+  label.set_word_wrap(true);
+  label.set_alignment(Alignment::RIGHT);
+
+  let snapshot = Snapshot::of_window(Rc::new(RefCell::new(label)), 160, 80);
+  snapshot.compare(reference("wrapped_label"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn rich_label_matches_reference() {
+
+  let proxy = Rc::new(EventProxy::new_headless());
+  let mut label = Label::new(proxy, Uuid::new_v4(), "".to_string(), Color::BLACK, Color::WHITE);
+  label.set_rich_text(RichText::parse_markup(
+        "<b>Bold</b> <i>italic</i> <u>under</u>\n<s>struck</s> <color=#c80000>red</color> <bg=#ffff00>marked</bg>"
+  ).unwrap());
+
+  let snapshot = Snapshot::of_window(Rc::new(RefCell::new(label)), 240, 50);
+  snapshot.compare(reference("rich_label"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn table_matches_reference() {
+
+  let proxy = Rc::new(EventProxy::new_headless());
+  let mut table = Table::new(proxy, Uuid::new_v4());
+  table.add_column("Name".to_string());
+  table.add_column("Size".to_string());
+  table.add_row(vec!["alpha.txt".to_string(), "12".to_string()]);
+  table.add_row(vec!["beta.png".to_string(), "3456".to_string()]);
+
+  let snapshot = Snapshot::of_window(Rc::new(RefCell::new(table)), 240, 120);
+  snapshot.compare(reference("table"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn tab_layout_matches_reference() {
+
+  let proxy = Rc::new(EventProxy::new_headless());
+  let main_win_uuid = Uuid::new_v4();
+  let mut tabs = TabLayout::new(proxy.clone(), main_win_uuid);
+  for title in ["First", "Second"] {
+    let label = Label::new(
+          proxy.clone(),
+          main_win_uuid,
+          format!("{title} tab"),
+          Color::BLACK,
+          Color::WHITE
+    );
+    tabs.add_tab(title.to_string(), ChildType::Window(Rc::new(RefCell::new(label))));
+  }
+  tabs.set_active_tab(1);
+
+  let snapshot = Snapshot::of_layout(Rc::new(RefCell::new(tabs)), 300, 120);
+  snapshot.compare(reference("tab_layout"), TOLERANCE).unwrap();
+}
+
+#[test]
+fn compare_honours_tolerance_and_writes_diff() {
+
+  let dir = env::temp_dir().join(format!("fenetre-snapshot-{}", Uuid::new_v4()));
+  let reference_path = dir.join("square.png");
+
+  let mut pixmap = Pixmap::new(4, 4).unwrap();
+  pixmap.fill(Color::from_rgba8(100, 100, 100, 255));
+  Snapshot::from_pixmap(pixmap.clone()).save_png(&reference_path).unwrap();
+
+  // A difference within the tolerance matches
+  pixmap.pixels_mut()[0] = ColorU8::from_rgba(102, 100, 98, 255).premultiply();
+  assert!(Snapshot::from_pixmap(pixmap.clone()).compare(&reference_path, 2).is_ok());
+  assert!(!dir.join("square.diff.png").exists());
+
+  // A larger difference doesn't, and the diff marks the pixel in red
+  pixmap.pixels_mut()[5] = ColorU8::from_rgba(0, 0, 0, 255).premultiply();
+  let err = Snapshot::from_pixmap(pixmap).compare(&reference_path, 2).unwrap_err();
+  assert!(err.starts_with("1 pixels differ"), "{err}");
+  let diff = Pixmap::load_png(dir.join("square.diff.png")).unwrap();
+  assert_eq!(diff.pixel(1, 1).unwrap().red(), 255);
+  assert_eq!(diff.pixel(1, 1).unwrap().green(), 0);
+  assert!(dir.join("square.actual.png").exists());
+
+  // A missing reference is reported
+  assert!(Snapshot::from_pixmap(Pixmap::new(4, 4).unwrap()).compare(dir.join("missing.png"), 0).is_err());
+
+  fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn compare_reports_size_mismatch() {
+
+  let dir = env::temp_dir().join(format!("fenetre-snapshot-{}", Uuid::new_v4()));
+  let reference_path = dir.join("small.png");
+  Snapshot::from_pixmap(Pixmap::new(2, 2).unwrap()).save_png(&reference_path).unwrap();
+
+  let err = Snapshot::from_pixmap(Pixmap::new(3, 2).unwrap()).compare(&reference_path, 0).unwrap_err();
+  assert!(err.contains("expected 2x2, rendered 3x2"), "{err}");
+
+  fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow, UserEvent},
+  label::Label,
+};
+
+use tiny_skia::Color;
+
+use uuid::Uuid;
+
+use std::{
+  cell::{Cell, RefCell},
+  future,
+  rc::Rc,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  task::Poll,
+  thread,
+  time::Duration,
+};
+
+#[test]
+fn output_is_given_to_the_ui_thread() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "Loading".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+  app_ref.set_contents(ChildType::Window(label.clone()));
+  app_ref.run_headless();
+
+  // The future runs on another thread, and the callback on this one
+  let ui_thread = thread::current().id();
+  let label_clone = label.clone();
+  app_ref.spawn(
+    async move { (thread::current().id(), 6 * 7) },
+    move |_app, (task_thread, answer)| {
+      assert_ne!(task_thread, thread::current().id());
+      assert_eq!(thread::current().id(), ui_thread);
+      label_clone.borrow_mut().set_text(answer.to_string());
+    }
+  );
+
+  assert!(app_ref.wait_for_tasks(Duration::from_secs(10)));
+  assert_eq!(label.borrow().get_text(), Some("42".to_string()));
+}
+
+#[test]
+fn closing_a_tab_cancels_its_tasks() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.log_unhandled_events(false);
+  app_ref.run_headless();
+
+  // Both tasks wait until they are released
+  let released = Arc::new(AtomicBool::new(false));
+  let wait_for_release = |released: Arc<AtomicBool>| future::poll_fn(move |_context| {
+    match released.load(Ordering::SeqCst) {
+      true => Poll::Ready(()),
+      false => Poll::Pending,
+    }
+  });
+
+  let (closed_tab, open_tab) = (Uuid::new_v4(), Uuid::new_v4());
+  let (closed_done, open_done) = (Rc::new(Cell::new(false)), Rc::new(Cell::new(false)));
+  let done = closed_done.clone();
+  let closed_task = app_ref.spawn_for(closed_tab, wait_for_release(released.clone()), move |_app, _| done.set(true));
+  let done = open_done.clone();
+  app_ref.spawn_for(open_tab, wait_for_release(released.clone()), move |_app, _| done.set(true));
+
+  proxy.send_event(UserEvent::CloseTab(id, Uuid::new_v4(), closed_tab)).unwrap();
+  app_ref.process_events();
+  assert!(!app_ref.cancel_task(closed_task));
+
+  released.store(true, Ordering::SeqCst);
+  assert!(app_ref.wait_for_tasks(Duration::from_secs(10)));
+  assert!(!closed_done.get());
+  assert!(open_done.get());
+}
+
+#[test]
+fn panicking_tasks_are_removed() {
+
+  let (app, _proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  // More tasks panic than there are threads in the pool
+  let called = Rc::new(Cell::new(false));
+  for _i in 0..12 {
+    let called = called.clone();
+    app_ref.spawn(
+      async { panic!("task failed") },
+      move |_app, ()| called.set(true)
+    );
+  }
+  assert!(app_ref.wait_for_tasks(Duration::from_secs(10)));
+  assert!(!called.get());
+
+  // The threads are still there to run the next task
+  let answer = Rc::new(Cell::new(0));
+  let answer_clone = answer.clone();
+  app_ref.spawn(async { 42 }, move |_app, output| answer_clone.set(output));
+  assert!(app_ref.wait_for_tasks(Duration::from_secs(10)));
+  assert_eq!(answer.get(), 42);
+}
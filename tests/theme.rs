@@ -0,0 +1,82 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  line_edit::LineEdit,
+  table::Table,
+  theme::Theme,
+};
+
+use tiny_skia::{Color, Pixmap};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+// Returns the RGB value of a pixel
+fn pixel_at(pixmap: &Pixmap, x: u32, y: u32) -> (u8, u8, u8) {
+  let pixel = pixmap.pixel(x, y).unwrap();
+  (pixel.red(), pixel.green(), pixel.blue())
+}
+
+#[test]
+fn switching_theme_redraws_windows() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+
+  let line_edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+  app_ref.set_contents(ChildType::Window(line_edit.clone()));
+  app_ref.run_headless();
+
+  let (x, y) = line_edit.borrow().get_location();
+  let (width, height) = (line_edit.borrow().get_width(), line_edit.borrow().get_height());
+  let (center_x, center_y) = ((x + width / 2.0) as u32, (y + height / 2.0) as u32);
+  let light = pixel_at(app_ref.get_pixmap(), center_x, center_y);
+
+  app_ref.set_theme(Theme::dark());
+  assert_eq!(app_ref.get_theme().name, "Dark");
+
+  // Both the main window and the LineEdit are drawn with the dark palette
+  let (red, green, blue) = pixel_at(app_ref.get_pixmap(), center_x, center_y);
+  assert_ne!((red, green, blue), light);
+  assert!(red < 100 && green < 100 && blue < 100);
+}
+
+#[test]
+fn window_colors_override_theme() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_theme(Theme::dark());
+
+  let table = Rc::new(RefCell::new(Table::new(proxy.clone(), id)));
+  table.borrow_mut().add_column("Name".to_string());
+  table.borrow_mut().set_header_bg_color(Color::from_rgba8(255, 0, 0, 255));
+  app_ref.set_contents(ChildType::Window(table.clone()));
+  app_ref.run_headless();
+
+  // The header has the table's color, and the cells have the theme's
+  assert_eq!(pixel_at(app_ref.get_pixmap(), 190, 5), (255, 0, 0));
+  assert_eq!(pixel_at(app_ref.get_pixmap(), 190, 80), (30, 30, 33));
+}
+
+#[test]
+fn status_bar_uses_theme() {
+
+  let (app, proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.enable_statusbar(proxy.clone());
+  app_ref.run_headless();
+
+  let status_bar = app_ref.get_status_bar().unwrap();
+  let (x, y) = status_bar.borrow().get_location();
+  let (center_x, center_y) = ((x + status_bar.borrow().get_width() / 2.0) as u32, (y + 2.0) as u32);
+  assert_eq!(pixel_at(app_ref.get_pixmap(), center_x, center_y), (191, 191, 191));
+
+  app_ref.set_theme(Theme::dark());
+  assert_eq!(pixel_at(app_ref.get_pixmap(), center_x, center_y), (80, 80, 86));
+}
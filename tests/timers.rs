@@ -0,0 +1,87 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  label::Label,
+};
+
+use tiny_skia::Color;
+
+use std::{
+  cell::{Cell, RefCell},
+  rc::Rc,
+  time::Duration,
+};
+
+#[test]
+fn timers_run_until_they_are_cancelled() {
+
+  let (app, _proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  let once_count = Rc::new(Cell::new(0));
+  let repeat_count = Rc::new(Cell::new(0));
+  let later_count = Rc::new(Cell::new(0));
+
+  let count = once_count.clone();
+  app_ref.add_timer(Duration::ZERO, false, move |_app| count.set(count.get() + 1));
+  let count = repeat_count.clone();
+  let repeating = app_ref.add_timer(Duration::ZERO, true, move |_app| count.set(count.get() + 1));
+  let count = later_count.clone();
+  let later = app_ref.add_timer(Duration::from_secs(3600), false, move |_app| count.set(count.get() + 1));
+
+  // The one-shot timer only runs once, and the other one hasn't expired
+  for _ in 0..3 {
+    app_ref.process_timers();
+  }
+  assert_eq!(once_count.get(), 1);
+  assert_eq!(repeat_count.get(), 3);
+  assert_eq!(later_count.get(), 0);
+
+  // A cancelled timer doesn't run again
+  assert!(app_ref.cancel_timer(repeating));
+  assert!(!app_ref.cancel_timer(repeating));
+  assert_eq!(app_ref.process_timers(), 0);
+  assert_eq!(repeat_count.get(), 3);
+  assert!(app_ref.cancel_timer(later));
+}
+
+#[test]
+fn timer_callbacks_can_change_the_app() {
+
+  let (app, proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+
+  let label = Rc::new(RefCell::new(Label::new(
+        proxy.clone(),
+        id,
+        "0".to_string(),
+        Color::BLACK,
+        Color::WHITE
+  )));
+  app_ref.set_contents(ChildType::Window(label.clone()));
+  app_ref.run_headless();
+  let before = app_ref.get_pixmap().clone();
+
+  // A repeating timer that counts to 2, and then cancels itself
+  let timer_id = Rc::new(Cell::new(None));
+  let count = Rc::new(Cell::new(0));
+  let (timer_label, callback_id, callback_count) = (label.clone(), timer_id.clone(), count.clone());
+  timer_id.set(Some(app_ref.add_timer(Duration::ZERO, true, move |app| {
+    callback_count.set(callback_count.get() + 1);
+    timer_label.borrow_mut().set_text(callback_count.get().to_string());
+    if callback_count.get() == 2 {
+      app.cancel_timer(callback_id.get().unwrap());
+    }
+  })));
+
+  for _ in 0..4 {
+    app_ref.process_timers();
+  }
+  assert_eq!(count.get(), 2);
+  assert_eq!(label.borrow().get_text(), Some("2".to_string()));
+  assert_ne!(before.data(), app_ref.get_pixmap().data());
+}
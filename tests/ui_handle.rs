@@ -0,0 +1,73 @@
+mod common;
+
+use common::create_app;
+
+use fenetre::{
+  child_window::UserEvent,
+  theme::Theme,
+};
+
+use uuid::Uuid;
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+  sync::{Arc, Mutex},
+  thread,
+};
+
+#[test]
+fn events_are_sent_from_other_threads() {
+
+  let (app, _proxy, id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  let received = Rc::new(RefCell::new(Vec::new()));
+  let received_clone = received.clone();
+  app_ref.set_set_list_event_callback(Box::new(move |_dest, list| {
+    received_clone.borrow_mut().extend(list);
+  }));
+
+  // Each thread sends one item
+  let ui_handle = app_ref.get_ui_handle();
+  let threads: Vec<_> = (0..4).map(|index| {
+    let ui_handle = ui_handle.clone();
+    thread::spawn(move || {
+      ui_handle.post(UserEvent::SetList(id, Uuid::new_v4(), vec![format!("Item {index}")])).unwrap();
+    })
+  }).collect();
+  for thread in threads {
+    thread.join().unwrap();
+  }
+
+  assert_eq!(app_ref.process_events(), 4);
+  let mut items = received.borrow().clone();
+  items.sort();
+  assert_eq!(items, vec!["Item 0", "Item 1", "Item 2", "Item 3"]);
+}
+
+#[test]
+fn functions_are_called_on_the_ui_thread() {
+
+  let (app, _proxy, _id) = create_app(200.0, 100.0);
+  let mut app_ref = app.borrow_mut();
+  app_ref.run_headless();
+
+  // The function runs when the events are processed, not when it is sent
+  let ui_thread = thread::current().id();
+  let called_on = Arc::new(Mutex::new(None));
+  let called_on_clone = called_on.clone();
+  let ui_handle = app_ref.get_ui_handle();
+  thread::spawn(move || {
+    ui_handle.run(move |app| {
+      *called_on_clone.lock().unwrap() = Some(thread::current().id());
+      app.set_theme(Theme::dark());
+    }).unwrap();
+  }).join().unwrap();
+  assert_eq!(*called_on.lock().unwrap(), None);
+
+  app_ref.process_events();
+  assert_eq!(*called_on.lock().unwrap(), Some(ui_thread));
+  assert_eq!(app_ref.get_theme().name, "Dark");
+}
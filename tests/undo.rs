@@ -0,0 +1,97 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  line_edit::LineEdit,
+  multi_line_edit::MultiLineEdit,
+};
+
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn undo_and_redo_in_multi_line_edit() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let editor = Rc::new(RefCell::new(MultiLineEdit::new(proxy.clone(), id, 2)));
+  let uuids = show_windows(&app, &proxy, vec![editor.clone()], Some(0));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(uuids[0]).unwrap();
+
+  // Consecutive typing is undone in one step
+  app_ref.send_text("abc");
+  app_ref.send_key(Key::Named(NamedKey::Enter), Some("\r"));
+  app_ref.send_text("de");
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+  assert_eq!(editor.borrow().get_text(), Some("abc\nd".to_string()));
+
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(editor.borrow().get_text(), Some("abc\nde".to_string()));
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(editor.borrow().get_text(), Some("abc\n".to_string()));
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(editor.borrow().get_text(), Some("abc".to_string()));
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(editor.borrow().get_text(), Some("".to_string()));
+  assert!(!editor.borrow().can_undo());
+  assert!(!editor.borrow().is_modified());
+
+  // Redoing restores the text and the caret
+  for _ in 0..4 {
+    app_ref.send_key(Key::Character("y".into()), Some("\u{19}"));
+  }
+  assert_eq!(editor.borrow().get_text(), Some("abc\nd".to_string()));
+  app_ref.send_modifiers(ModifiersState::empty());
+  app_ref.send_text("!");
+  assert_eq!(editor.borrow().get_text(), Some("abc\nd!".to_string()));
+  assert!(!editor.borrow().can_redo());
+
+  // Replacing the selection is a single step
+  editor.borrow_mut().set_selection(0, 1, 1, 1).unwrap();
+  app_ref.send_text("xy");
+  assert_eq!(editor.borrow().get_text(), Some("axy!".to_string()));
+  editor.borrow_mut().undo();
+  assert_eq!(editor.borrow().get_text(), Some("abc\nd!".to_string()));
+}
+
+#[test]
+fn undo_returns_to_the_saved_state() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(edit.clone()));
+  app_ref.run_headless();
+
+  let edit_uuid = edit.borrow().get_uuid();
+  app_ref.set_focus(edit_uuid).unwrap();
+  app_ref.send_text("save");
+  edit.borrow_mut().set_modified(false);
+
+  // Typing after the save is a separate step
+  app_ref.send_text("d");
+  assert!(edit.borrow().is_modified());
+  app_ref.send_modifiers(ModifiersState::CONTROL);
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(edit.borrow().get_text(), Some("save".to_string()));
+  assert!(!edit.borrow().is_modified());
+
+  app_ref.send_key(Key::Character("z".into()), Some("\u{1a}"));
+  assert_eq!(edit.borrow().get_text(), Some("".to_string()));
+  assert!(edit.borrow().is_modified());
+
+  // Ctrl+Shift+Z also redoes
+  app_ref.send_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT);
+  app_ref.send_key(Key::Character("Z".into()), Some("\u{1a}"));
+  assert_eq!(edit.borrow().get_text(), Some("save".to_string()));
+  assert!(!edit.borrow().is_modified());
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{create_app, show_windows};
+
+use fenetre::{
+  child_window::{ChildType, ChildWindow},
+  line_edit::LineEdit,
+  multi_line_edit::MultiLineEdit,
+  text_font::TextFont,
+};
+
+use winit::keyboard::{Key, NamedKey};
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+};
+
+#[test]
+fn line_edit_edits_grapheme_clusters() {
+
+  let (app, proxy, id) = create_app(300.0, 100.0);
+  let edit = Rc::new(RefCell::new(LineEdit::new(proxy.clone(), id, "".to_string())));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_contents(ChildType::Window(edit.clone()));
+  app_ref.run_headless();
+
+  let edit_uuid = edit.borrow().get_uuid();
+  app_ref.set_focus(edit_uuid).unwrap();
+
+  // The combining accent joins the "e" in a single cluster
+  app_ref.send_text("ae\u{301}b");
+  app_ref.send_key(Key::Named(NamedKey::ArrowLeft), None);
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+  assert_eq!(edit.borrow().get_text(), Some("ab".to_string()));
+
+  app_ref.send_text("ü👍");
+  app_ref.send_key(Key::Named(NamedKey::ArrowLeft), None);
+  app_ref.send_key(Key::Named(NamedKey::ArrowLeft), None);
+  app_ref.send_key(Key::Named(NamedKey::Delete), None);
+  assert_eq!(edit.borrow().get_text(), Some("a👍b".to_string()));
+}
+
+#[test]
+fn multi_line_edit_keeps_the_column_between_lines() {
+
+  let (app, proxy, id) = create_app(300.0, 300.0);
+  let mut editor = MultiLineEdit::new(proxy.clone(), id, 2);
+  editor.set_text("日本語\nabcd".to_string());
+  let editor = Rc::new(RefCell::new(editor));
+  let uuids = show_windows(&app, &proxy, vec![editor.clone()], Some(0));
+
+  let mut app_ref = app.borrow_mut();
+  app_ref.set_focus(uuids[0]).unwrap();
+
+  app_ref.send_key(Key::Named(NamedKey::ArrowRight), None);
+  app_ref.send_key(Key::Named(NamedKey::ArrowRight), None);
+  app_ref.send_key(Key::Named(NamedKey::ArrowDown), None);
+  app_ref.send_text("é");
+  assert_eq!(editor.borrow().get_text(), Some("日本語\nabécd".to_string()));
+
+  app_ref.send_key(Key::Named(NamedKey::ArrowUp), None);
+  app_ref.send_key(Key::Named(NamedKey::Backspace), None);
+  assert_eq!(editor.borrow().get_text(), Some("日本\nabécd".to_string()));
+
+  editor.borrow_mut().set_selection(1, 2, 1, 3).unwrap();
+  assert_eq!(editor.borrow().get_selected_text(), Some("é".to_string()));
+}
+
+#[test]
+fn cluster_is_drawn_in_one_cell() {
+
+  let font = TextFont::new_default().unwrap();
+  let (char_width, _height) = font.get_bounds("W", None);
+
+  let (plain_width, _height) = font.get_bounds("ae", Some(char_width));
+  let (accented_width, _height) = font.get_bounds("ae\u{301}", Some(char_width));
+  assert_eq!(plain_width, accented_width);
+}